@@ -1,36 +1,148 @@
+#[cfg(feature = "s3")]
+use crate::storages::{S3Storage, S3StorageConfig};
 use crate::{
-    controllers::{graphiql, graphql, Mutation, Query},
+    controllers::{
+        download_attachment, graphiql, graphql, healthz, metrics, rate_limit, readyz, Metrics,
+        Mutation, Query, RateLimiter,
+    },
     entities::Validator,
-    repositories::{MongoRepository, MongoRepositoryConfig, MongoRepositoryError},
+    repositories::{
+        MetricsRepository, MongoRepository, MongoRepositoryConfig, MongoRepositoryError,
+        RepositoryError,
+    },
+    storages::{LocalStorage, Storage},
     usecases::UseCase,
 };
-use async_graphql::{EmptySubscription, Schema};
-use axum::{routing::get, Router};
-use clap::Parser;
+use async_graphql::{extensions::Tracing, EmptySubscription, Schema};
+use axum::{
+    http::{HeaderValue, Request},
+    middleware::from_fn_with_state,
+    routing::{get, post},
+    Router,
+};
+use clap::{Parser, ValueEnum};
+use futures::future::try_join_all;
+use metrics_exporter_prometheus::{BuildError, PrometheusBuilder, PrometheusHandle};
+use nanoid::nanoid;
 use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    future::IntoFuture,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
 use thiserror::Error;
-use tokio::net::TcpListener;
+use tokio::{
+    net::TcpListener,
+    signal,
+    sync::watch,
+    time::{interval, MissedTickBehavior},
+};
+use tower_http::{
+    limit::RequestBodyLimitLayer,
+    request_id::{MakeRequestId, PropagateRequestIdLayer, RequestId, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 use url::Url;
 
 #[derive(Debug, Parser)]
 pub struct Args {
+    /// Addresses to listen on, comma separated. Use `0.0.0.0` or `::` inside containers.
+    #[arg(long, env, value_delimiter = ',', default_value = "127.0.0.1")]
+    pub host: Vec<IpAddr>,
+
     #[arg(long, env)]
     pub port: u16,
 
+    /// Stop serving GraphiQL on `GET /`.
+    #[arg(long, env)]
+    pub disable_graphiql: bool,
+
+    /// Reject introspection queries.
+    #[arg(long, env)]
+    pub disable_introspection: bool,
+
+    /// Maximum nesting depth of a GraphQL query.
+    #[arg(long, env, default_value_t = 12)]
+    pub max_query_depth: usize,
+
+    /// Maximum complexity of a GraphQL query, where list fields count
+    /// their children once per expected item.
+    #[arg(long, env, default_value_t = 5000)]
+    pub max_query_complexity: usize,
+
+    /// Requests per minute allowed for each authenticated user. 0 disables the limit.
+    #[arg(long, env, default_value_t = 120)]
+    pub rate_limit: u32,
+
+    /// Seconds during which retrying a create mutation with the same
+    /// idempotency key returns the entity created by the first attempt.
+    #[arg(long, env, default_value_t = 86400)]
+    pub idempotency_window: u32,
+
+    /// Seconds between runs of the scheduler that creates due recurring
+    /// payments. 0 disables the scheduler on this instance.
+    #[arg(long, env, default_value_t = 60)]
+    pub recurring_payment_interval: u64,
+
+    /// Directory attachments are stored in, unless an S3 bucket is configured.
+    #[arg(long, env, default_value = "attachments")]
+    pub storage_path: PathBuf,
+
+    #[cfg(feature = "s3")]
+    #[command(flatten)]
+    pub s3: S3Args,
+
+    /// Largest attachment accepted, in bytes.
+    #[arg(long, env, default_value_t = 10 * 1024 * 1024)]
+    pub max_attachment_size: usize,
+
     #[arg(long, env)]
     pub auth0_issuer: Url,
 
     #[arg(long, env)]
     pub auth0_audience: String,
 
+    /// Reject requests carrying an invalid token instead of treating them as anonymous.
+    #[arg(long, env)]
+    pub strict_auth: bool,
+
     #[arg(long, env)]
     pub mongo_uri: String,
 
     #[arg(long, env)]
     pub mongo_db: String,
+
+    #[arg(long, env, value_enum, default_value_t = LogFormat::Pretty)]
+    pub log_format: LogFormat,
+}
+
+/// Stores attachments in an S3-compatible bucket instead of the local filesystem.
+#[cfg(feature = "s3")]
+#[derive(Debug, clap::Args)]
+pub struct S3Args {
+    #[arg(long, env, requires_all = ["s3_endpoint", "s3_access_key", "s3_secret_key"])]
+    pub s3_bucket: Option<String>,
+
+    #[arg(long, env)]
+    pub s3_endpoint: Option<Url>,
+
+    #[arg(long, env, default_value = "us-east-1")]
+    pub s3_region: String,
+
+    #[arg(long, env)]
+    pub s3_access_key: Option<String>,
+
+    #[arg(long, env)]
+    pub s3_secret_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    Pretty,
+    Json,
 }
 
 #[derive(Debug, Error)]
@@ -43,12 +155,22 @@ pub enum Error {
 
     #[error("mongo")]
     Mongo(#[from] MongoRepositoryError),
+
+    #[error("metrics")]
+    Metrics(#[from] BuildError),
+
+    #[error("repository")]
+    Repository(#[from] RepositoryError),
 }
 
 #[derive(Clone)]
 pub struct State {
     pub schema: Schema<Query, Mutation, EmptySubscription>,
+    pub usecase: UseCase,
     pub validator: Validator,
+    pub strict_auth: bool,
+    pub metrics: PrometheusHandle,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 pub struct App {
@@ -62,13 +184,37 @@ impl App {
 
     pub async fn serve(self) -> Result<(), Error> {
         let Args {
+            host,
             port,
+            disable_graphiql,
+            disable_introspection,
+            max_query_depth,
+            max_query_complexity,
+            rate_limit: requests_per_minute,
+            idempotency_window,
+            recurring_payment_interval,
+            storage_path,
+            #[cfg(feature = "s3")]
+            s3,
+            max_attachment_size,
             auth0_issuer,
             auth0_audience,
+            strict_auth,
             mongo_uri,
             mongo_db,
+            log_format,
         } = self.args;
 
+        // Tracing
+        init_tracing(log_format);
+
+        // Metrics
+        let metrics_handle = PrometheusBuilder::new()
+            .set_buckets(&[
+                0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+            ])?
+            .install_recorder()?;
+
         // Mongo
         let mongo = MongoRepository::new(MongoRepositoryConfig {
             uri: &mongo_uri,
@@ -76,29 +222,195 @@ impl App {
         })
         .await?;
 
+        // Storage
+        let storage: Arc<dyn Storage> = Arc::new(LocalStorage::new(storage_path));
+        #[cfg(feature = "s3")]
+        let storage: Arc<dyn Storage> = match s3 {
+            S3Args {
+                s3_bucket: Some(bucket),
+                s3_endpoint: Some(endpoint),
+                s3_region: region,
+                s3_access_key: Some(access_key),
+                s3_secret_key: Some(secret_key),
+            } => Arc::new(S3Storage::new(S3StorageConfig {
+                endpoint,
+                bucket,
+                region,
+                access_key,
+                secret_key,
+            })),
+            _ => storage,
+        };
+
         // UseCase
-        let usecase = UseCase::new(Arc::new(mongo));
+        let repository = MetricsRepository::new(mongo);
+        repository.seed().await?;
+        let usecase = UseCase::new(Arc::new(repository))
+            .with_storage(storage)
+            .with_idempotency_window(chrono::Duration::seconds(idempotency_window.into()))
+            .with_max_attachment_size(max_attachment_size);
 
         // GraphQL
-        let schema = Schema::build(Query::default(), Mutation::default(), EmptySubscription)
-            .data(usecase)
-            .finish();
+        let mut schema = Schema::build(Query::default(), Mutation::default(), EmptySubscription)
+            .data(usecase.clone())
+            .extension(Tracing)
+            .extension(Metrics)
+            .limit_depth(max_query_depth)
+            .limit_complexity(max_query_complexity);
+        if disable_introspection {
+            schema = schema.disable_introspection();
+        }
+        let schema = schema.finish();
 
         // Auth
         let validator = Validator::new(auth0_issuer, auth0_audience).await?;
 
+        // Scheduler
+        let (shutdown, _) = watch::channel(());
+        if recurring_payment_interval > 0 {
+            tokio::spawn(schedule_recurring_payments(
+                usecase.clone(),
+                Duration::from_secs(recurring_payment_interval),
+                shutdown.subscribe(),
+            ));
+        }
+
         // Server
-        let state = State { schema, validator };
+        let state = State {
+            schema,
+            usecase,
+            validator,
+            strict_auth,
+            metrics: metrics_handle,
+            rate_limiter: (requests_per_minute > 0)
+                .then(|| Arc::new(RateLimiter::new(requests_per_minute))),
+        };
+        let graphql = post(graphql)
+            .route_layer(from_fn_with_state(state.clone(), rate_limit))
+            // Multipart uploads carry the operation alongside the file.
+            .layer(RequestBodyLimitLayer::new(max_attachment_size + 64 * 1024));
+        let download =
+            get(download_attachment).route_layer(from_fn_with_state(state.clone(), rate_limit));
+        let root = if disable_graphiql {
+            graphql
+        } else {
+            get(graphiql).merge(graphql)
+        };
         let router = Router::new()
-            .route("/", get(graphiql).post(graphql))
-            .with_state(state);
-        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
-        println!("Listening on http://{}", addr);
-        axum::serve(TcpListener::bind(addr).await?, router).await?;
+            .route("/", root)
+            .route("/attachments/:id", download)
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            .route("/metrics", get(metrics))
+            .with_state(state)
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(
+                TraceLayer::new_for_http().make_span_with(|req: &Request<_>| {
+                    let request_id = req
+                        .headers()
+                        .get("x-request-id")
+                        .and_then(|id| id.to_str().ok())
+                        .unwrap_or_default();
+                    tracing::info_span!(
+                        "http",
+                        method = %req.method(),
+                        uri = %req.uri(),
+                        request_id,
+                    )
+                }),
+            )
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestNanoid));
+
+        let mut servers = Vec::new();
+        for ip in host {
+            let addr = SocketAddr::new(ip, port);
+            let listener = TcpListener::bind(addr).await?;
+            info!("Listening on http://{}", addr);
+
+            let mut stop = shutdown.subscribe();
+            let server = axum::serve(listener, router.clone()).with_graceful_shutdown(async move {
+                let _ = stop.changed().await;
+            });
+            servers.push(server.into_future());
+        }
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            info!("Shutting down, draining in-flight requests");
+            let _ = shutdown.send(());
+        });
+        try_join_all(servers).await?;
         Ok(())
     }
 }
 
+/// Creates due recurring payments every `period` until shutdown. Runs on
+/// several instances at once are safe: each occurrence is created under its
+/// own idempotency key and progress is saved with a version check.
+async fn schedule_recurring_payments(
+    usecase: UseCase,
+    period: Duration,
+    mut stop: watch::Receiver<()>,
+) {
+    let mut ticker = interval(period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                match usecase.run_recurring_payments(chrono::Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(created) => info!(created, "Created recurring payments"),
+                    Err(err) => warn!(%err, "Failed to run recurring payments"),
+                }
+            }
+            _ = stop.changed() => break,
+        }
+    }
+}
+
+fn init_tracing(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match format {
+        LogFormat::Pretty => subscriber.pretty().init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+/// Request IDs share the alphabet of the entity IDs. A client-supplied
+/// `x-request-id` header takes precedence over a generated one.
+#[derive(Clone, Copy)]
+struct MakeRequestNanoid;
+
+impl MakeRequestId for MakeRequestNanoid {
+    fn make_request_id<B>(&mut self, _: &Request<B>) -> Option<RequestId> {
+        HeaderValue::from_str(&nanoid!()).ok().map(RequestId::new)
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
 impl Default for App {
     fn default() -> Self {
         let args = Args::parse();
@@ -0,0 +1,88 @@
+use crate::{
+    controllers::LIST_COMPLEXITY,
+    entities::{AccessToken, AccessTokenID, AuthState, Scope, UserID},
+    usecases::{CreateAccessTokenInput, CreatedAccessToken, DeleteAccessTokenInput, UseCase},
+};
+use async_graphql::{Context, Object, ResultExt};
+use chrono::{DateTime, Utc};
+
+#[Object]
+impl AccessToken {
+    async fn id(&self) -> AccessTokenID {
+        self.id.clone()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    async fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn scopes(&self) -> Vec<Scope> {
+        self.scopes.clone()
+    }
+
+    async fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+}
+
+#[Object]
+impl CreatedAccessToken {
+    async fn access_token(&self) -> AccessToken {
+        self.access_token.clone()
+    }
+
+    async fn secret(&self) -> String {
+        self.secret.clone()
+    }
+}
+
+#[derive(Default)]
+pub struct AccessTokenQuery;
+
+#[Object]
+impl AccessTokenQuery {
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn access_tokens(
+        &self,
+        ctx: &Context<'_>,
+        id: UserID,
+    ) -> async_graphql::Result<Vec<AccessToken>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.get_access_tokens_by_user(auth, &id).await.extend()
+    }
+}
+
+#[derive(Default)]
+pub struct AccessTokenMutation;
+
+#[Object]
+impl AccessTokenMutation {
+    async fn create_access_token(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateAccessTokenInput,
+    ) -> async_graphql::Result<CreatedAccessToken> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.create_access_token(auth, input).await.extend()
+    }
+
+    async fn delete_access_token(
+        &self,
+        ctx: &Context<'_>,
+        input: DeleteAccessTokenInput,
+    ) -> async_graphql::Result<AccessTokenID> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.delete_access_token(auth, input).await.extend()
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    app,
+    controllers::Loaders,
+    entities::{Attachment, AttachmentID, AuthState, PaymentID, User},
+    usecases::{CreateAttachmentInput, DeleteAttachmentInput, UseCase, UseCaseError},
+};
+use async_graphql::{Context, Object, ResultExt, Upload};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::io::Read;
+
+#[Object]
+impl Attachment {
+    async fn id(&self) -> AttachmentID {
+        self.id.clone()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    async fn filename(&self) -> String {
+        self.filename.clone()
+    }
+
+    async fn content_type(&self) -> String {
+        self.content_type.clone()
+    }
+
+    /// Size in bytes.
+    async fn size(&self) -> i64 {
+        self.size
+    }
+
+    /// Downloads the content when requested with the same credentials.
+    #[graphql(name = "url")]
+    async fn download_url(&self) -> String {
+        self.url()
+    }
+
+    async fn uploaded_by(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .users
+            .load_one(self.uploaded_by.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
+    }
+}
+
+#[derive(Default)]
+pub struct AttachmentMutation;
+
+#[Object]
+impl AttachmentMutation {
+    /// Expects a GraphQL multipart request carrying the file.
+    async fn create_attachment(
+        &self,
+        ctx: &Context<'_>,
+        payment: PaymentID,
+        file: Upload,
+    ) -> async_graphql::Result<Attachment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        let file = file.value(ctx)?;
+        let filename = file.filename.clone();
+        let content_type = file.content_type.clone().unwrap_or_default();
+        let mut content = Vec::new();
+        file.into_read().read_to_end(&mut content)?;
+        let input = CreateAttachmentInput {
+            payment,
+            filename,
+            content_type,
+            content,
+        };
+        usecase.create_attachment(auth, input).await.extend()
+    }
+
+    async fn delete_attachment(
+        &self,
+        ctx: &Context<'_>,
+        input: DeleteAttachmentInput,
+    ) -> async_graphql::Result<AttachmentID> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.delete_attachment(auth, input).await.extend()
+    }
+}
+
+/// `GET /attachments/:id`, authorized like the payment the attachment belongs to.
+pub async fn download_attachment(
+    State(state): State<app::State>,
+    auth: AuthState,
+    Path(id): Path<String>,
+) -> Response {
+    match state
+        .usecase
+        .download_attachment(&auth, &AttachmentID::new(id))
+        .await
+    {
+        Ok((attachment, content)) => (
+            [
+                (header::CONTENT_TYPE, attachment.content_type.clone()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        r#"inline; filename="{}""#,
+                        header_safe(&attachment.filename)
+                    ),
+                ),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+                (header::CACHE_CONTROL, "private".to_string()),
+            ],
+            content,
+        )
+            .into_response(),
+        Err(err) => {
+            let status = match err {
+                UseCaseError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+                UseCaseError::Forbidden => StatusCode::FORBIDDEN,
+                UseCaseError::NotFound => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            let body = json!({
+                "errors": [{
+                    "message": err.to_string(),
+                    "extensions": { "code": err.code() },
+                }],
+            });
+            (status, Json(body)).into_response()
+        }
+    }
+}
+
+/// Filenames are user input; keep what is safe inside a quoted header parameter.
+fn header_safe(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_safe_filename() {
+        assert_eq!(header_safe("receipt 1.png"), "receipt 1.png");
+        assert_eq!(header_safe("a\"b\r\n.pdf"), "a_b__.pdf");
+        assert_eq!(header_safe("領収書.jpg"), "___.jpg");
+    }
+}
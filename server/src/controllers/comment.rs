@@ -0,0 +1,77 @@
+use crate::{
+    controllers::Loaders,
+    entities::{AuthState, Comment, CommentID, User},
+    usecases::{CreateCommentInput, DeleteCommentInput, UpdateCommentInput, UseCase, UseCaseError},
+};
+use async_graphql::{Context, Object, ResultExt};
+use chrono::{DateTime, Utc};
+
+#[Object]
+impl Comment {
+    async fn id(&self) -> CommentID {
+        self.id.clone()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    /// Whether the author changed the body after posting it.
+    async fn edited(&self) -> bool {
+        self.is_edited()
+    }
+
+    async fn body(&self) -> String {
+        self.body.clone()
+    }
+
+    async fn author(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .users
+            .load_one(self.author.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
+    }
+}
+
+#[derive(Default)]
+pub struct CommentMutation;
+
+#[Object]
+impl CommentMutation {
+    async fn create_comment(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateCommentInput,
+    ) -> async_graphql::Result<Comment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.create_comment(auth, input).await.extend()
+    }
+
+    async fn update_comment(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateCommentInput,
+    ) -> async_graphql::Result<Comment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.update_comment(auth, input).await.extend()
+    }
+
+    async fn delete_comment(
+        &self,
+        ctx: &Context<'_>,
+        input: DeleteCommentInput,
+    ) -> async_graphql::Result<CommentID> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.delete_comment(auth, input).await.extend()
+    }
+}
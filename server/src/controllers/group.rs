@@ -1,8 +1,13 @@
 use crate::{
-    entities::{AuthState, Group, GroupID, Notification, Payment, User, UserID, Warikan},
-    usecases::{CreateGroupInput, DeleteGroupInput, UpdateGroupInput, UseCase},
+    controllers::{page_complexity, paginate, CursorConnection, Loaders, LIST_COMPLEXITY},
+    entities::{
+        Amount, AuthState, Contribution, Group, GroupID, Household, HouseholdBalance, Notification,
+        Payment, PaymentFilter, RecurringPayment, SettlementOptions, StatementEntry, User, UserID,
+        Warikan,
+    },
+    usecases::{CreateGroupInput, DeleteGroupInput, UpdateGroupInput, UseCase, UseCaseError},
 };
-use async_graphql::{Context, Object};
+use async_graphql::{Context, Object, ResultExt};
 use chrono::{DateTime, Utc};
 
 #[Object]
@@ -19,54 +24,308 @@ impl Group {
         self.updated_at
     }
 
+    async fn version(&self) -> i32 {
+        self.version
+    }
+
     async fn title(&self) -> String {
         self.title.clone()
     }
 
+    async fn categories(&self) -> Vec<String> {
+        self.categories.clone()
+    }
+
+    async fn require_confirmation(&self) -> bool {
+        self.require_confirmation
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn households(&self) -> Vec<Household> {
+        self.households.clone()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
     async fn participants(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<User>> {
+        let loaders = ctx.data::<Loaders>()?;
+        let users = loaders
+            .users
+            .load_many(self.participants.iter().cloned())
+            .await
+            .extend()?;
+        let users = self
+            .participants
+            .iter()
+            .filter_map(|id| users.get(id).cloned())
+            .collect();
+        Ok(users)
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn payments(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<PaymentFilter>,
+    ) -> async_graphql::Result<Vec<Payment>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.get_user_vec(auth, &self.participants).await?)
+        match filter {
+            Some(filter) => usecase.find_payments_by_group(auth, &self.id, filter).await,
+            None => usecase.get_payments_by_group(auth, &self.id).await,
+        }
+        .extend()
     }
 
-    async fn payments(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Payment>> {
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn payments_connection(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<CursorConnection<Payment>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.get_payments_by_group(auth, &self.id).await?)
+        paginate(after, before, first, last, |page| {
+            usecase.get_payments_page_by_group(auth, &self.id, page)
+        })
+        .await
     }
 
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
     async fn notifications(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Notification>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.get_notifications_by_group(auth, &self.id).await?)
+        usecase
+            .get_notifications_by_group(auth, &self.id)
+            .await
+            .extend()
     }
 
-    async fn warikan(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Warikan>> {
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn notifications_connection(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<CursorConnection<Notification>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.warikan_by_group(auth, &self.id).await?)
+        paginate(after, before, first, last, |page| {
+            usecase.get_notifications_page_by_group(auth, &self.id, page)
+        })
+        .await
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn recurring_payments(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<RecurringPayment>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase
+            .get_recurring_payments_by_group(auth, &self.id)
+            .await
+            .extend()
+    }
+
+    /// Transfers that settle the group. Only confirmed payments count unless
+    /// `includeUnconfirmed` is set; without `requireConfirmation` every
+    /// payment is confirmed. With `byHousehold`, each household settles as one
+    /// unit through its representative. `options` constrain who pays whom and
+    /// how much; options that cannot be satisfied are an error.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn warikan(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] include_unconfirmed: bool,
+        #[graphql(default)] by_household: bool,
+        #[graphql(default)] options: SettlementOptions,
+    ) -> async_graphql::Result<Vec<Warikan>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase
+            .warikan_by_group(auth, &self.id, include_unconfirmed, by_household, &options)
+            .await
+            .extend()
+    }
+
+    /// Every payment that moved `user`'s balance, oldest first, with the
+    /// running balance. The last balance is all `user` owes or is owed;
+    /// `warikan` may leave up to its `rounding` of it unsettled.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn statement(
+        &self,
+        ctx: &Context<'_>,
+        user: UserID,
+        #[graphql(default)] include_unconfirmed: bool,
+    ) -> async_graphql::Result<Vec<StatementEntry>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase
+            .statement_by_group(auth, &self.id, &user, include_unconfirmed)
+            .await
+            .extend()
+    }
+
+    /// What each household owes or is owed, broken down per member.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn household_balances(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] include_unconfirmed: bool,
+    ) -> async_graphql::Result<Vec<HouseholdBalance>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase
+            .household_balances_by_group(auth, &self.id, include_unconfirmed)
+            .await
+            .extend()
+    }
+}
+
+#[Object]
+impl Household {
+    async fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn members(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<User>> {
+        let loaders = ctx.data::<Loaders>()?;
+        let users = loaders
+            .users
+            .load_many(self.members.iter().cloned())
+            .await
+            .extend()?;
+        let users = self
+            .members
+            .iter()
+            .filter_map(|id| users.get(id).cloned())
+            .collect();
+        Ok(users)
+    }
+
+    #[graphql(name = "representative")]
+    async fn representative_user(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let loaders = ctx.data::<Loaders>()?;
+        let representative = self
+            .representative()
+            .ok_or(UseCaseError::NotFound)
+            .extend()?;
+        loaders
+            .users
+            .load_one(representative.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
+    }
+}
+
+#[Object]
+impl HouseholdBalance {
+    async fn household(&self) -> Household {
+        self.household.clone()
+    }
+
+    /// Positive when the group owes the household.
+    async fn amount(&self) -> i32 {
+        self.amount
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn members(&self) -> Vec<Amount> {
+        self.members.clone()
     }
 }
 
 #[Object]
 impl Warikan {
     async fn from(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
-        let usecase = ctx.data::<UseCase>()?;
-        let auth = ctx.data::<AuthState>()?;
-        let user = usecase.get_user(auth, &self.from).await?;
-        Ok(user)
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .users
+            .load_one(self.from.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
     }
 
     async fn to(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
-        let usecase = ctx.data::<UseCase>()?;
-        let auth = ctx.data::<AuthState>()?;
-        let user = usecase.get_user(auth, &self.to).await?;
-        Ok(user)
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .users
+            .load_one(self.to.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
     }
 
     async fn amount(&self) -> i32 {
         self.amount
     }
+
+    /// Payments behind the sender's balance; for a household, its members'.
+    #[graphql(
+        name = "fromContributions",
+        complexity = "LIST_COMPLEXITY * child_complexity"
+    )]
+    async fn sender_contributions(&self) -> Vec<Contribution> {
+        self.from_contributions()
+    }
+
+    /// Payments behind the recipient's balance; for a household, its members'.
+    #[graphql(
+        name = "toContributions",
+        complexity = "LIST_COMPLEXITY * child_complexity"
+    )]
+    async fn recipient_contributions(&self) -> Vec<Contribution> {
+        self.to_contributions()
+    }
+}
+
+#[Object]
+impl Contribution {
+    async fn payment(&self, ctx: &Context<'_>) -> async_graphql::Result<Payment> {
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .payments
+            .load_one(self.payment.clone())
+            .await
+            .and_then(|payment| payment.ok_or(UseCaseError::NotFound))
+            .extend()
+    }
+
+    /// Positive when the payment raised what the group owes.
+    async fn amount(&self) -> i32 {
+        self.amount
+    }
+}
+
+#[Object]
+impl StatementEntry {
+    async fn payment(&self, ctx: &Context<'_>) -> async_graphql::Result<Payment> {
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .payments
+            .load_one(self.payment.clone())
+            .await
+            .and_then(|payment| payment.ok_or(UseCaseError::NotFound))
+            .extend()
+    }
+
+    async fn amount(&self) -> i32 {
+        self.amount
+    }
+
+    /// The balance after this payment.
+    async fn balance(&self) -> i32 {
+        self.balance
+    }
 }
 
 #[derive(Default)]
@@ -77,13 +336,32 @@ impl GroupQuery {
     async fn group(&self, ctx: &Context<'_>, id: GroupID) -> async_graphql::Result<Option<Group>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.get_group(auth, &id).await?)
+        usecase.get_group(auth, &id).await.extend()
     }
 
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
     async fn groups(&self, ctx: &Context<'_>, id: UserID) -> async_graphql::Result<Vec<Group>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.get_groups_by_user(auth, &id).await?)
+        usecase.get_groups_by_user(auth, &id).await.extend()
+    }
+
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn groups_connection(
+        &self,
+        ctx: &Context<'_>,
+        id: UserID,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<CursorConnection<Group>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        paginate(after, before, first, last, |page| {
+            usecase.get_groups_page_by_user(auth, &id, page)
+        })
+        .await
     }
 }
 
@@ -99,7 +377,7 @@ impl GroupMutation {
     ) -> async_graphql::Result<Group> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.create_group(auth, input).await?)
+        usecase.create_group(auth, input).await.extend()
     }
 
     async fn update_group(
@@ -109,7 +387,7 @@ impl GroupMutation {
     ) -> async_graphql::Result<Group> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.update_group(auth, input).await?)
+        usecase.update_group(auth, input).await.extend()
     }
 
     async fn delete_group(
@@ -119,6 +397,6 @@ impl GroupMutation {
     ) -> async_graphql::Result<GroupID> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.delete_group(auth, input).await?)
+        usecase.delete_group(auth, input).await.extend()
     }
 }
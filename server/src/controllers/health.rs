@@ -0,0 +1,15 @@
+use crate::app;
+use axum::{extract::State, http::StatusCode};
+
+/// Liveness: the process is up and serving HTTP.
+pub async fn healthz() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Readiness: the repository backing the use cases answers.
+pub async fn readyz(State(state): State<app::State>) -> StatusCode {
+    match state.usecase.ping().await {
+        Ok(()) => StatusCode::OK,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    entities::{Attachment, AuthState, Comment, Group, GroupID, Payment, PaymentID, User, UserID},
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::dataloader::{DataLoader, Loader};
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+/// Per-request data loaders. Lookups made while resolving a single request are
+/// batched into one repository call per entity type.
+pub struct Loaders {
+    pub users: DataLoader<UserLoader>,
+    pub groups: DataLoader<GroupLoader>,
+    pub payments: DataLoader<PaymentLoader>,
+    pub attachments: DataLoader<AttachmentLoader>,
+    pub comments: DataLoader<CommentLoader>,
+}
+
+impl Loaders {
+    pub fn new(usecase: &UseCase, auth: &AuthState) -> Self {
+        Self {
+            users: DataLoader::new(
+                UserLoader {
+                    usecase: usecase.clone(),
+                    auth: auth.clone(),
+                },
+                spawn,
+            ),
+            groups: DataLoader::new(
+                GroupLoader {
+                    usecase: usecase.clone(),
+                    auth: auth.clone(),
+                },
+                spawn,
+            ),
+            payments: DataLoader::new(
+                PaymentLoader {
+                    usecase: usecase.clone(),
+                    auth: auth.clone(),
+                },
+                spawn,
+            ),
+            attachments: DataLoader::new(
+                AttachmentLoader {
+                    usecase: usecase.clone(),
+                    auth: auth.clone(),
+                },
+                spawn,
+            ),
+            comments: DataLoader::new(
+                CommentLoader {
+                    usecase: usecase.clone(),
+                    auth: auth.clone(),
+                },
+                spawn,
+            ),
+        }
+    }
+}
+
+/// Runs a batch in the span of the request that started it, so its
+/// repository calls keep the request id.
+fn spawn(batch: BoxFuture<'static, ()>) -> JoinHandle<()> {
+    tokio::spawn(batch.in_current_span())
+}
+
+pub struct UserLoader {
+    usecase: UseCase,
+    auth: AuthState,
+}
+
+impl Loader<UserID> for UserLoader {
+    type Value = User;
+    type Error = UseCaseError;
+
+    async fn load(&self, keys: &[UserID]) -> Result<HashMap<UserID, User>, UseCaseError> {
+        let users = self.usecase.get_user_vec(&self.auth, keys).await?;
+        Ok(users.into_iter().map(|u| (u.id.clone(), u)).collect())
+    }
+}
+
+pub struct GroupLoader {
+    usecase: UseCase,
+    auth: AuthState,
+}
+
+impl Loader<GroupID> for GroupLoader {
+    type Value = Group;
+    type Error = UseCaseError;
+
+    async fn load(&self, keys: &[GroupID]) -> Result<HashMap<GroupID, Group>, UseCaseError> {
+        let groups = self.usecase.get_groups_by_ids(&self.auth, keys).await?;
+        Ok(groups.into_iter().map(|g| (g.id.clone(), g)).collect())
+    }
+}
+
+pub struct PaymentLoader {
+    usecase: UseCase,
+    auth: AuthState,
+}
+
+impl Loader<PaymentID> for PaymentLoader {
+    type Value = Payment;
+    type Error = UseCaseError;
+
+    async fn load(&self, keys: &[PaymentID]) -> Result<HashMap<PaymentID, Payment>, UseCaseError> {
+        let payments = self.usecase.get_payments_by_ids(&self.auth, keys).await?;
+        Ok(payments.into_iter().map(|p| (p.id.clone(), p)).collect())
+    }
+}
+
+/// Attachments keyed by the payment they belong to. Payments without any are
+/// missing from the result.
+pub struct AttachmentLoader {
+    usecase: UseCase,
+    auth: AuthState,
+}
+
+impl Loader<PaymentID> for AttachmentLoader {
+    type Value = Vec<Attachment>;
+    type Error = UseCaseError;
+
+    async fn load(
+        &self,
+        keys: &[PaymentID],
+    ) -> Result<HashMap<PaymentID, Vec<Attachment>>, UseCaseError> {
+        let attachments = self
+            .usecase
+            .get_attachments_by_payments(&self.auth, keys)
+            .await?;
+        let mut map: HashMap<PaymentID, Vec<Attachment>> = HashMap::new();
+        for attachment in attachments {
+            map.entry(attachment.payment.clone())
+                .or_default()
+                .push(attachment);
+        }
+        Ok(map)
+    }
+}
+
+/// Comments keyed by the payment they belong to, oldest first. Payments
+/// without any are missing from the result.
+pub struct CommentLoader {
+    usecase: UseCase,
+    auth: AuthState,
+}
+
+impl Loader<PaymentID> for CommentLoader {
+    type Value = Vec<Comment>;
+    type Error = UseCaseError;
+
+    async fn load(
+        &self,
+        keys: &[PaymentID],
+    ) -> Result<HashMap<PaymentID, Vec<Comment>>, UseCaseError> {
+        let comments = self
+            .usecase
+            .get_comments_by_payments(&self.auth, keys)
+            .await?;
+        let mut map: HashMap<PaymentID, Vec<Comment>> = HashMap::new();
+        for comment in comments {
+            map.entry(comment.payment.clone())
+                .or_default()
+                .push(comment);
+        }
+        Ok(map)
+    }
+}
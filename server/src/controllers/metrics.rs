@@ -0,0 +1,153 @@
+use crate::app;
+use async_graphql::{
+    async_trait,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextParseQuery},
+    parser::types::{ExecutableDocument, OperationDefinition, OperationType, Selection},
+    registry::Registry,
+    Response, ServerResult, Variables,
+};
+use axum::extract::State;
+use metrics::{counter, histogram};
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+/// Prometheus text exposition of everything recorded through the `metrics` facade.
+pub async fn metrics(State(state): State<app::State>) -> String {
+    state.metrics.render()
+}
+
+/// Records the count and latency of every GraphQL operation, labelled by
+/// its type and root field such as `mutation createPayment`. Operations whose
+/// root field the schema does not know are labelled `other`, so clients
+/// cannot add series of their own.
+pub struct Metrics;
+
+impl ExtensionFactory for Metrics {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(MetricsExtension::default())
+    }
+}
+
+#[derive(Default)]
+struct MetricsExtension {
+    /// Label of every operation in the parsed document, by operation name.
+    labels: Mutex<Vec<(Option<String>, String)>>,
+}
+
+#[async_trait::async_trait]
+impl Extension for MetricsExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        let registry = &ctx.schema_env.registry;
+        *self.labels.lock().unwrap() = document
+            .operations
+            .iter()
+            .map(|(name, operation)| {
+                (
+                    name.map(|name| name.to_string()),
+                    label(registry, &operation.node),
+                )
+            })
+            .collect();
+        Ok(document)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let start = Instant::now();
+        let response = next.run(ctx, operation_name).await;
+        let operation = self
+            .labels
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(name, _)| operation_name.is_none() || name.as_deref() == operation_name)
+            .map(|(_, label)| label.clone())
+            .unwrap_or_else(|| "other".to_string());
+        let status = if response.is_ok() { "ok" } else { "error" };
+        histogram!("graphql_operation_duration_seconds", "operation" => operation.clone())
+            .record(start.elapsed().as_secs_f64());
+        counter!("graphql_operations_total", "operation" => operation, "status" => status)
+            .increment(1);
+        response
+    }
+}
+
+/// `<type> <root field>` when the schema defines the operation's first root
+/// field, `other` otherwise.
+fn label(registry: &Registry, operation: &OperationDefinition) -> String {
+    let root = match operation.ty {
+        OperationType::Query => Some(&registry.query_type),
+        OperationType::Mutation => registry.mutation_type.as_ref(),
+        OperationType::Subscription => registry.subscription_type.as_ref(),
+    };
+    let field = match operation
+        .selection_set
+        .node
+        .items
+        .first()
+        .map(|item| &item.node)
+    {
+        Some(Selection::Field(field)) => Some(field.node.name.node.as_str()),
+        _ => None,
+    };
+    match (root.and_then(|root| registry.types.get(root)), field) {
+        (Some(root), Some(field)) if root.field_by_name(field).is_some() => {
+            format!("{} {}", operation.ty, field)
+        }
+        _ => "other".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+    use futures::executor::block_on;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn ping(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn labels_by_root_field() {
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription)
+            .extension(Metrics)
+            .finish();
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            block_on(async {
+                schema.execute("query Anything { ping }").await;
+                schema.execute("query Unbounded { __typename }").await;
+            })
+        });
+
+        let rendered = handle.render();
+        assert!(
+            rendered.contains(r#"graphql_operations_total{operation="query ping",status="ok"} 1"#)
+        );
+        assert!(rendered.contains(r#"graphql_operations_total{operation="other",status="ok"} 1"#));
+        assert!(!rendered.contains("Anything"), "{rendered}");
+        assert!(!rendered.contains("Unbounded"), "{rendered}");
+    }
+}
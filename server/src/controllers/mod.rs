@@ -1,44 +1,99 @@
+mod access_token;
+mod attachment;
+mod comment;
 mod group;
+mod health;
+mod loader;
+mod metrics;
 mod notification;
+mod page;
 mod payment;
+mod rate_limit;
+mod recurring_payment;
 mod user;
 
+pub use access_token::*;
+pub use attachment::*;
+pub use comment::*;
 pub use group::*;
+pub use health::*;
+pub use loader::*;
+pub use metrics::*;
 pub use notification::*;
+pub use page::*;
 pub use payment::*;
+pub use rate_limit::*;
+pub use recurring_payment::*;
 pub use user::*;
 
 use crate::{
     app,
-    entities::{AuthState, Claims},
+    entities::{AuthError, AuthState, Claims, Scope, ACCESS_TOKEN_PREFIX},
+    usecases::UseCaseError,
+};
+use ::metrics::counter;
+use async_graphql::{
+    http::GraphiQLSource,
+    parser::{parse_query, types::OperationType},
+    ErrorExtensions, MergedObject, Response, ServerError,
 };
-use async_graphql::{http::GraphiQLSource, MergedObject};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use async_trait::async_trait;
 use axum::{
     extract::FromRequestParts,
     extract::State,
-    http::request::Parts,
+    http::{header, request::Parts, StatusCode},
     response::{self, IntoResponse},
-    RequestPartsExt,
+    Json, RequestPartsExt,
 };
 use axum_extra::{
     headers::{authorization::Bearer, Authorization},
+    typed_header::TypedHeaderRejectionReason,
     TypedHeader,
 };
+use serde_json::json;
+use tracing::debug;
+
+/// Expected number of items in an unpaginated list field, used to estimate query complexity.
+pub const LIST_COMPLEXITY: usize = 10;
 
 #[derive(Default, MergedObject)]
-pub struct Query(GroupQuery, NotificationQuery, PaymentQuery, UserQuery);
+pub struct Query(
+    AccessTokenQuery,
+    GroupQuery,
+    NotificationQuery,
+    PaymentQuery,
+    RecurringPaymentQuery,
+    UserQuery,
+);
 
 #[derive(Default, MergedObject)]
-pub struct Mutation(GroupMutation, PaymentMutation, UserMutation);
+pub struct Mutation(
+    AccessTokenMutation,
+    AttachmentMutation,
+    CommentMutation,
+    GroupMutation,
+    PaymentMutation,
+    RecurringPaymentMutation,
+    UserMutation,
+);
 
 pub async fn graphql(
     State(state): State<app::State>,
     auth: AuthState,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    let req = req.into_inner().data(auth);
+    let req = req.into_inner();
+    if let AuthState::Authorized(claims) = &auth {
+        if !claims.has_scope(required_scope(&req)) {
+            let mut err = ServerError::new("403 forbidden: insufficient token scope", None);
+            err.extensions
+                .get_or_insert_with(Default::default)
+                .set("code", "INSUFFICIENT_SCOPE");
+            return Response::from_errors(vec![err]).into();
+        }
+    }
+    let req = req.data(Loaders::new(&state.usecase, &auth)).data(auth);
     state.schema.execute(req).await.into()
 }
 
@@ -46,32 +101,108 @@ pub async fn graphiql() -> impl IntoResponse {
     response::Html(GraphiQLSource::build().endpoint("/").finish())
 }
 
+/// Mutations need `write`, everything else needs `read`.
+fn required_scope(req: &async_graphql::Request) -> Scope {
+    let Ok(document) = parse_query(&req.query) else {
+        return Scope::Read;
+    };
+    let is_mutation = document.operations.iter().any(|(name, operation)| {
+        let selected = match (&req.operation_name, name) {
+            (Some(expected), Some(name)) => expected.as_str() == name.as_str(),
+            _ => true,
+        };
+        selected && operation.node.ty == OperationType::Mutation
+    });
+    if is_mutation {
+        Scope::Write
+    } else {
+        Scope::Read
+    }
+}
+
+impl ErrorExtensions for UseCaseError {
+    fn extend(&self) -> async_graphql::Error {
+        counter!("usecase_errors_total", "code" => self.code()).increment(1);
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| {
+            e.set("code", self.code());
+            if let UseCaseError::InvalidInput(fields) = self {
+                if let Ok(fields) = async_graphql::to_value(fields) {
+                    e.set("fields", fields);
+                }
+            }
+        })
+    }
+}
+
+/// Returned instead of an anonymous [`AuthState`] when strict authentication is enabled.
+#[derive(Debug)]
+pub struct AuthRejection(pub AuthError);
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> response::Response {
+        let (status, error) = match self.0 {
+            AuthError::MalformedHeader => (StatusCode::BAD_REQUEST, "invalid_request"),
+            AuthError::Unavailable => (StatusCode::SERVICE_UNAVAILABLE, "temporarily_unavailable"),
+            _ => (StatusCode::UNAUTHORIZED, "invalid_token"),
+        };
+        let challenge = format!(
+            r#"Bearer realm="warikan", error="{}", error_description="{}""#,
+            error, self.0
+        );
+        let body = json!({
+            "errors": [{
+                "message": self.0.to_string(),
+                "extensions": { "code": self.0.code() },
+            }],
+        });
+        (status, [(header::WWW_AUTHENTICATE, challenge)], Json(body)).into_response()
+    }
+}
+
 #[async_trait]
 impl FromRequestParts<app::State> for AuthState {
-    type Rejection = ();
+    type Rejection = AuthRejection;
 
     async fn from_request_parts(
         parts: &mut Parts,
         state: &app::State,
     ) -> Result<Self, Self::Rejection> {
-        Ok(match validate(parts, state).await {
-            Ok(claims) => AuthState::Authorized(claims),
-            Err(err) => {
-                dbg!(err);
-                AuthState::Unauthorized
-            }
-        })
+        if let Some(auth) = parts.extensions.get::<AuthState>() {
+            return Ok(auth.clone());
+        }
+        let result = validate(parts, state).await;
+        if let Err(reason) = &result {
+            debug!(%reason, code = reason.code(), "request is not authenticated");
+        }
+        match result {
+            Ok(claims) => Ok(AuthState::Authorized(claims)),
+            Err(AuthError::MissingToken) => Ok(AuthState::Unauthorized(AuthError::MissingToken)),
+            Err(reason) if state.strict_auth => Err(AuthRejection(reason)),
+            Err(reason) => Ok(AuthState::Unauthorized(reason)),
+        }
     }
 }
 
-async fn validate(
-    parts: &mut Parts,
-    state: &app::State,
-) -> Result<Claims, Box<dyn std::error::Error>> {
+async fn validate(parts: &mut Parts, state: &app::State) -> Result<Claims, AuthError> {
     let TypedHeader(Authorization(bearer)) = parts
         .extract::<TypedHeader<Authorization<Bearer>>>()
-        .await?;
+        .await
+        .map_err(|rejection| match rejection.reason() {
+            TypedHeaderRejectionReason::Missing => AuthError::MissingToken,
+            _ => AuthError::MalformedHeader,
+        })?;
     let token = bearer.token();
-    let claims = state.validator.validate(token)?;
+    let claims = if token.starts_with(ACCESS_TOKEN_PREFIX) {
+        state
+            .usecase
+            .authenticate_access_token(token)
+            .await
+            .map_err(|err| match err {
+                UseCaseError::Unauthorized(reason) => reason,
+                _ => AuthError::Unavailable,
+            })?
+    } else {
+        state.validator.validate(token)?
+    };
     Ok(claims)
 }
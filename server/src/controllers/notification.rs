@@ -1,8 +1,9 @@
 use crate::{
-    entities::{AuthState, Notification, NotificationID},
+    controllers::{Loaders, LIST_COMPLEXITY},
+    entities::{AuthState, Notification, NotificationID, User},
     usecases::UseCase,
 };
-use async_graphql::{Context, Object};
+use async_graphql::{Context, Object, ResultExt};
 use chrono::{DateTime, Utc};
 
 #[Object]
@@ -22,6 +23,24 @@ impl Notification {
     async fn message(&self) -> String {
         self.message.clone()
     }
+
+    /// Users the notification is addressed to and shown to; empty when it concerns
+    /// the whole group.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn recipients(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<User>> {
+        let loaders = ctx.data::<Loaders>()?;
+        let users = loaders
+            .users
+            .load_many(self.recipients.iter().cloned())
+            .await
+            .extend()?;
+        let users = self
+            .recipients
+            .iter()
+            .filter_map(|id| users.get(id).cloned())
+            .collect();
+        Ok(users)
+    }
 }
 
 #[derive(Default)]
@@ -36,7 +55,7 @@ impl NotificationQuery {
     ) -> async_graphql::Result<Option<Notification>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        let notification = usecase.get_notification(auth, &id).await?;
+        let notification = usecase.get_notification(auth, &id).await.extend()?;
         Ok(notification)
     }
 }
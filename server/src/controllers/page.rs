@@ -0,0 +1,57 @@
+use crate::{
+    entities::{Cursor, Page, PageRequest, Paginated, DEFAULT_PAGE_SIZE},
+    usecases::UseCaseError,
+};
+use async_graphql::{
+    connection::{query, Connection, Edge, OpaqueCursor},
+    ErrorExtensions, OutputType,
+};
+use std::future::Future;
+
+pub type CursorConnection<T> = Connection<OpaqueCursor<Cursor>, T>;
+
+/// Complexity of a connection field, scaled by the number of edges requested.
+pub fn page_complexity(first: Option<i32>, last: Option<i32>, child_complexity: usize) -> usize {
+    let size = first
+        .or(last)
+        .map_or(DEFAULT_PAGE_SIZE, |size| size.max(0) as usize);
+    size * child_complexity
+}
+
+/// Resolves a Relay connection from the standard `first`/`after`/`last`/`before` arguments.
+pub async fn paginate<T, F, R>(
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    load: F,
+) -> async_graphql::Result<CursorConnection<T>>
+where
+    T: OutputType + Paginated,
+    F: FnOnce(PageRequest) -> R,
+    R: Future<Output = Result<Page<T>, UseCaseError>>,
+{
+    query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<OpaqueCursor<Cursor>>, before: Option<OpaqueCursor<Cursor>>, first, last| async move {
+            let page = PageRequest {
+                after: after.map(|cursor| cursor.0),
+                before: before.map(|cursor| cursor.0),
+                first,
+                last,
+            };
+            let page = load(page).await.map_err(|err| err.extend())?;
+            let mut connection = Connection::new(page.has_previous_page, page.has_next_page);
+            connection.edges.extend(
+                page.items
+                    .into_iter()
+                    .map(|item| Edge::new(OpaqueCursor(item.cursor()), item)),
+            );
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
@@ -1,8 +1,16 @@
 use crate::{
-    entities::{Amount, AuthState, Payment, PaymentID, User},
-    usecases::{CreatePaymentInput, DeletePaymentInput, UpdatePaymentInput, UseCase},
+    controllers::{Loaders, LIST_COMPLEXITY},
+    entities::{
+        Amount, Attachment, AuthState, Comment, Confirmation, GroupID, InvalidPayment, Item,
+        Payment, PaymentFilter, PaymentID, PaymentKind, PaymentStatus, PaymentViolation, Receipt,
+        Settlement, SettlementOptions, User, Warikan, WarikanPreview,
+    },
+    usecases::{
+        ConfirmPaymentInput, CreatePaymentInput, DeletePaymentInput, DisputePaymentInput,
+        PreviewWarikanInput, UpdatePaymentInput, UseCase, UseCaseError,
+    },
 };
-use async_graphql::{Context, Object};
+use async_graphql::{Context, Object, ResultExt};
 use chrono::{DateTime, Utc};
 
 #[Object]
@@ -19,26 +27,164 @@ impl Payment {
         self.updated_at
     }
 
+    async fn version(&self) -> i32 {
+        self.version
+    }
+
     async fn title(&self) -> String {
         self.title.clone()
     }
 
+    async fn kind(&self) -> PaymentKind {
+        self.kind
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
     async fn creditors(&self) -> Vec<Amount> {
         self.creditors.clone()
     }
 
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
     async fn debtors(&self) -> Vec<Amount> {
         self.debtors.clone()
     }
+
+    /// Net effect on each user's balance given the kind of the payment:
+    /// positive when the group owes the user, negative when the user owes it.
+    #[graphql(name = "balances", complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn net_balances(&self) -> Vec<Amount> {
+        self.balances()
+    }
+
+    async fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    async fn category(&self) -> Option<String> {
+        self.category.clone()
+    }
+
+    async fn occurred_at(&self) -> DateTime<Utc> {
+        self.occurred_at.unwrap_or(self.created_at)
+    }
+
+    async fn currency(&self) -> String {
+        self.currency.clone()
+    }
+
+    async fn receipt(&self) -> Option<Receipt> {
+        self.receipt.clone()
+    }
+
+    /// Whether the debtors accepted their shares; see `Group.requireConfirmation`.
+    #[graphql(name = "status")]
+    async fn confirmation_status(&self, ctx: &Context<'_>) -> async_graphql::Result<PaymentStatus> {
+        let loaders = ctx.data::<Loaders>()?;
+        let group = loaders
+            .groups
+            .load_one(self.group.clone())
+            .await
+            .and_then(|group| group.ok_or(UseCaseError::NotFound))
+            .extend()?;
+        Ok(self.status(&group))
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn confirmations(&self) -> Vec<Confirmation> {
+        self.confirmations.clone()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn attachments(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Attachment>> {
+        let loaders = ctx.data::<Loaders>()?;
+        let attachments = loaders
+            .attachments
+            .load_one(self.id.clone())
+            .await
+            .extend()?;
+        Ok(attachments.unwrap_or_default())
+    }
+
+    /// Discussion about the payment, oldest first.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn comments(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Comment>> {
+        let loaders = ctx.data::<Loaders>()?;
+        let comments = loaders.comments.load_one(self.id.clone()).await.extend()?;
+        Ok(comments.unwrap_or_default())
+    }
+}
+
+#[Object]
+impl Receipt {
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn items(&self) -> Vec<Item> {
+        self.items.clone()
+    }
+
+    async fn tax(&self) -> i32 {
+        self.tax
+    }
+
+    async fn service(&self) -> i32 {
+        self.service
+    }
+
+    async fn tip(&self) -> i32 {
+        self.tip
+    }
+
+    #[graphql(name = "total")]
+    async fn total_amount(&self) -> i64 {
+        self.total()
+    }
+}
+
+#[Object]
+impl Item {
+    async fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn price(&self) -> i32 {
+        self.price
+    }
+
+    async fn quantity(&self) -> i32 {
+        self.quantity
+    }
+
+    #[graphql(name = "subtotal")]
+    async fn subtotal_amount(&self) -> i64 {
+        self.subtotal()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn users(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<User>> {
+        let loaders = ctx.data::<Loaders>()?;
+        let users = loaders
+            .users
+            .load_many(self.users.iter().cloned())
+            .await
+            .extend()?;
+        let users = self
+            .users
+            .iter()
+            .filter_map(|id| users.get(id).cloned())
+            .collect();
+        Ok(users)
+    }
 }
 
 #[Object]
 impl Amount {
     async fn user(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
-        let usecase = ctx.data::<UseCase>()?;
-        let auth = ctx.data::<AuthState>()?;
-        let user = usecase.get_user(auth, &self.user).await?;
-        Ok(user)
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .users
+            .load_one(self.user.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
     }
 
     async fn amount(&self) -> i32 {
@@ -46,6 +192,59 @@ impl Amount {
     }
 }
 
+#[Object]
+impl Confirmation {
+    async fn user(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .users
+            .load_one(self.user.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
+    }
+
+    async fn status(&self) -> PaymentStatus {
+        self.status
+    }
+
+    async fn reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    async fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+}
+
+#[Object]
+impl InvalidPayment {
+    async fn payment(&self) -> Payment {
+        self.payment.clone()
+    }
+
+    async fn violations(&self) -> Vec<PaymentViolation> {
+        self.violations.clone()
+    }
+}
+
+#[Object]
+impl PaymentViolation {
+    #[graphql(name = "field")]
+    async fn field_path(&self) -> String {
+        self.field().to_string()
+    }
+
+    #[graphql(name = "code")]
+    async fn error_code(&self) -> String {
+        self.code().to_string()
+    }
+
+    async fn message(&self) -> String {
+        self.to_string()
+    }
+}
+
 #[derive(Default)]
 pub struct PaymentQuery;
 
@@ -58,8 +257,82 @@ impl PaymentQuery {
     ) -> async_graphql::Result<Option<Payment>> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        let payment = usecase.get_payment(auth, &id).await?;
-        Ok(payment)
+        usecase.get_payment(auth, &id).await.extend()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn validate_payments(
+        &self,
+        ctx: &Context<'_>,
+        group: GroupID,
+    ) -> async_graphql::Result<Vec<InvalidPayment>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.validate_payments(auth, &group).await.extend()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn search_payments(
+        &self,
+        ctx: &Context<'_>,
+        filter: PaymentFilter,
+    ) -> async_graphql::Result<Vec<Payment>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.search_payments(auth, filter).await.extend()
+    }
+
+    /// Settles the group with and without the proposed changes, storing
+    /// nothing. Payments to add must name this group; their idempotency keys
+    /// are ignored.
+    #[allow(clippy::too_many_arguments)]
+    async fn preview_warikan(
+        &self,
+        ctx: &Context<'_>,
+        group: GroupID,
+        #[graphql(default)] add_payments: Vec<CreatePaymentInput>,
+        #[graphql(default)] remove_payment_ids: Vec<PaymentID>,
+        #[graphql(default)] modify_payments: Vec<UpdatePaymentInput>,
+        #[graphql(default)] include_unconfirmed: bool,
+        #[graphql(default)] by_household: bool,
+        #[graphql(default)] options: SettlementOptions,
+    ) -> async_graphql::Result<WarikanPreview> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        let input = PreviewWarikanInput {
+            add_payments,
+            remove_payment_ids,
+            modify_payments,
+            include_unconfirmed,
+            by_household,
+            options,
+        };
+        usecase.preview_warikan(auth, &group, input).await.extend()
+    }
+}
+
+#[Object]
+impl WarikanPreview {
+    async fn before(&self) -> Settlement {
+        self.before.clone()
+    }
+
+    async fn after(&self) -> Settlement {
+        self.after.clone()
+    }
+}
+
+#[Object]
+impl Settlement {
+    /// Positive when the group owes the user.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn balances(&self) -> Vec<Amount> {
+        self.balances.clone()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn warikans(&self) -> Vec<Warikan> {
+        self.warikans.clone()
     }
 }
 
@@ -75,7 +348,7 @@ impl PaymentMutation {
     ) -> async_graphql::Result<Payment> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.create_payment(auth, input).await?)
+        usecase.create_payment(auth, input).await.extend()
     }
 
     async fn update_payment(
@@ -85,7 +358,7 @@ impl PaymentMutation {
     ) -> async_graphql::Result<Payment> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.update_payment(auth, input).await?)
+        usecase.update_payment(auth, input).await.extend()
     }
 
     async fn delete_payment(
@@ -95,6 +368,28 @@ impl PaymentMutation {
     ) -> async_graphql::Result<PaymentID> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.delete_payment(auth, input).await?)
+        usecase.delete_payment(auth, input).await.extend()
+    }
+
+    /// Accepts the caller's share of a payment in a group that requires confirmation.
+    async fn confirm_payment(
+        &self,
+        ctx: &Context<'_>,
+        input: ConfirmPaymentInput,
+    ) -> async_graphql::Result<Payment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.confirm_payment(auth, input).await.extend()
+    }
+
+    /// Rejects the caller's share of a payment in a group that requires confirmation.
+    async fn dispute_payment(
+        &self,
+        ctx: &Context<'_>,
+        input: DisputePaymentInput,
+    ) -> async_graphql::Result<Payment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.dispute_payment(auth, input).await.extend()
     }
 }
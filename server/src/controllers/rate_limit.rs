@@ -0,0 +1,142 @@
+use crate::{app, entities::AuthState};
+use axum::{
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+
+/// Buckets are dropped once this many users are tracked and theirs is full again.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Token bucket per user: `limit` requests per minute, refilled continuously.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: u32,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Takes a token from `key`'s bucket and returns how many are left, or how
+    /// long the caller has to wait for the next one.
+    pub fn check(&self, key: &str, now: Instant) -> Result<u32, Duration> {
+        let capacity = f64::from(self.limit);
+        let per_second = capacity / 60.0;
+        let refill = |bucket: &Bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated_at);
+            (bucket.tokens + elapsed.as_secs_f64() * per_second).min(capacity)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| refill(bucket) < capacity);
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        bucket.tokens = refill(bucket);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(bucket.tokens as u32)
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+/// Limits authenticated requests per `Claims.sub`. Anonymous requests pass through.
+pub async fn rate_limit(
+    State(state): State<app::State>,
+    auth: AuthState,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let key = match &auth {
+        AuthState::Authorized(claims) => Some(claims.sub.clone()),
+        AuthState::Unauthorized(_) => None,
+    };
+    // Handed on so the GraphQL handler does not authenticate the request twice.
+    req.extensions_mut().insert(auth);
+    let (Some(limiter), Some(key)) = (state.rate_limiter.as_deref(), key) else {
+        return next.run(req).await;
+    };
+    let limit = HeaderValue::from(limiter.limit());
+    match limiter.check(&key, Instant::now()) {
+        Ok(remaining) => {
+            let mut res = next.run(req).await;
+            let headers = res.headers_mut();
+            headers.insert(RATE_LIMIT_LIMIT, limit);
+            headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(remaining));
+            res
+        }
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil() as u64;
+            let body = json!({
+                "errors": [{
+                    "message": "429 too many requests",
+                    "extensions": { "code": "RATE_LIMITED", "retryAfter": retry_after },
+                }],
+            });
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [
+                    (RETRY_AFTER, HeaderValue::from(retry_after)),
+                    (RATE_LIMIT_LIMIT, limit),
+                    (RATE_LIMIT_REMAINING, HeaderValue::from(0)),
+                ],
+                Json(body),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_exhausts_and_refills() {
+        let limiter = RateLimiter::new(2);
+        let now = Instant::now();
+
+        assert_eq!(limiter.check("alice", now), Ok(1));
+        assert_eq!(limiter.check("alice", now), Ok(0));
+        let wait = limiter.check("alice", now).unwrap_err();
+        assert_eq!(wait.as_secs_f64().round(), 30.0);
+        assert_eq!(limiter.check("bob", now), Ok(1));
+
+        let later = now + Duration::from_secs(31);
+        assert_eq!(limiter.check("alice", later), Ok(0));
+    }
+}
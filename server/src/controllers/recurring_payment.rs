@@ -0,0 +1,154 @@
+use crate::{
+    controllers::{Loaders, LIST_COMPLEXITY},
+    entities::{Amount, AuthState, RecurrenceUnit, RecurringPayment, RecurringPaymentID, User},
+    usecases::{
+        CreateRecurringPaymentInput, DeleteRecurringPaymentInput, SkipRecurringPaymentInput,
+        UpdateRecurringPaymentInput, UseCase, UseCaseError,
+    },
+};
+use async_graphql::{Context, Object, ResultExt};
+use chrono::{DateTime, Utc};
+
+#[Object]
+impl RecurringPayment {
+    async fn id(&self) -> RecurringPaymentID {
+        self.id.clone()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    async fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Participant the payments are created on behalf of.
+    async fn owner(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let loaders = ctx.data::<Loaders>()?;
+        loaders
+            .users
+            .load_one(self.owner.clone())
+            .await
+            .and_then(|user| user.ok_or(UseCaseError::NotFound))
+            .extend()
+    }
+
+    async fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn creditors(&self) -> Vec<Amount> {
+        self.creditors.clone()
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn debtors(&self) -> Vec<Amount> {
+        self.debtors.clone()
+    }
+
+    async fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    async fn category(&self) -> Option<String> {
+        self.category.clone()
+    }
+
+    async fn currency(&self) -> String {
+        self.currency.clone()
+    }
+
+    async fn unit(&self) -> RecurrenceUnit {
+        self.unit
+    }
+
+    async fn interval(&self) -> i32 {
+        self.interval
+    }
+
+    async fn starts_at(&self) -> DateTime<Utc> {
+        self.starts_at
+    }
+
+    async fn ends_at(&self) -> Option<DateTime<Utc>> {
+        self.ends_at
+    }
+
+    /// `null` once the schedule has ended.
+    async fn next_at(&self) -> Option<DateTime<Utc>> {
+        self.next_at
+    }
+
+    async fn paused(&self) -> bool {
+        self.paused
+    }
+}
+
+#[derive(Default)]
+pub struct RecurringPaymentQuery;
+
+#[Object]
+impl RecurringPaymentQuery {
+    async fn recurring_payment(
+        &self,
+        ctx: &Context<'_>,
+        id: RecurringPaymentID,
+    ) -> async_graphql::Result<Option<RecurringPayment>> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        let recurring_payment = usecase.get_recurring_payment(auth, &id).await.extend()?;
+        Ok(recurring_payment)
+    }
+}
+
+#[derive(Default)]
+pub struct RecurringPaymentMutation;
+
+#[Object]
+impl RecurringPaymentMutation {
+    async fn create_recurring_payment(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateRecurringPaymentInput,
+    ) -> async_graphql::Result<RecurringPayment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.create_recurring_payment(auth, input).await.extend()
+    }
+
+    async fn update_recurring_payment(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateRecurringPaymentInput,
+    ) -> async_graphql::Result<RecurringPayment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.update_recurring_payment(auth, input).await.extend()
+    }
+
+    async fn skip_recurring_payment(
+        &self,
+        ctx: &Context<'_>,
+        input: SkipRecurringPaymentInput,
+    ) -> async_graphql::Result<RecurringPayment> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.skip_recurring_payment(auth, input).await.extend()
+    }
+
+    async fn delete_recurring_payment(
+        &self,
+        ctx: &Context<'_>,
+        input: DeleteRecurringPaymentInput,
+    ) -> async_graphql::Result<RecurringPaymentID> {
+        let usecase = ctx.data::<UseCase>()?;
+        let auth = ctx.data::<AuthState>()?;
+        usecase.delete_recurring_payment(auth, input).await.extend()
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
+    controllers::Loaders,
     entities::{AuthState, User, UserID},
     usecases::{CreateUserInput, DeleteUserInput, UpdateUserInput, UseCase},
 };
-use async_graphql::{Context, Object};
+use async_graphql::{Context, Object, ResultExt};
 use chrono::{DateTime, Utc};
 
 #[Object]
@@ -30,10 +31,8 @@ pub struct UserQuery;
 #[Object]
 impl UserQuery {
     async fn user(&self, ctx: &Context<'_>, id: UserID) -> async_graphql::Result<Option<User>> {
-        let usecase = ctx.data::<UseCase>()?;
-        let auth = ctx.data::<AuthState>()?;
-        let user = usecase.get_user_opt(auth, &id).await?;
-        Ok(user)
+        let loaders = ctx.data::<Loaders>()?;
+        loaders.users.load_one(id).await.extend()
     }
 }
 
@@ -49,7 +48,7 @@ impl UserMutation {
     ) -> async_graphql::Result<User> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.create_user(auth, input).await?)
+        usecase.create_user(auth, input).await.extend()
     }
 
     async fn update_user(
@@ -59,7 +58,7 @@ impl UserMutation {
     ) -> async_graphql::Result<User> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.update_user(auth, input).await?)
+        usecase.update_user(auth, input).await.extend()
     }
 
     async fn delete_user(
@@ -69,6 +68,6 @@ impl UserMutation {
     ) -> async_graphql::Result<UserID> {
         let usecase = ctx.data::<UseCase>()?;
         let auth = ctx.data::<AuthState>()?;
-        Ok(usecase.delete_user(auth, input).await?)
+        usecase.delete_user(auth, input).await.extend()
     }
 }
@@ -0,0 +1,98 @@
+use crate::entities::{Claims, UserID};
+use async_graphql::{types::ID, Enum, NewType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[cfg(test)]
+use fake::{Dummy, Faker};
+#[cfg(test)]
+use rand::Rng;
+
+pub const ACCESS_TOKEN_PREFIX: &str = "wpat_";
+pub const ACCESS_TOKEN_ISSUER: &str = "warikan:access-token";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, NewType)]
+pub struct AccessTokenID(pub ID);
+
+impl AccessTokenID {
+    pub fn new<T: ToString>(id: T) -> Self {
+        AccessTokenID(ID(id.to_string()))
+    }
+}
+
+#[allow(clippy::to_string_trait_impl)]
+impl ToString for AccessTokenID {
+    fn to_string(&self) -> String {
+        self.0 .0.to_string()
+    }
+}
+
+#[cfg(test)]
+impl Dummy<Faker> for AccessTokenID {
+    fn dummy_with_rng<R: Rng + ?Sized>(config: &Faker, rng: &mut R) -> Self {
+        let id = String::dummy_with_rng(config, rng);
+        AccessTokenID::new(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[cfg_attr(test, derive(Dummy))]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Read,
+    Write,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+        }
+    }
+}
+
+/// A personal access token. Only the SHA-256 hash of the secret is stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct AccessToken {
+    pub id: AccessTokenID,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub name: String,
+    pub hash: String,
+    pub scopes: Vec<Scope>,
+    pub expires_at: DateTime<Utc>,
+
+    pub user: UserID,
+}
+
+impl AccessToken {
+    pub fn hash(secret: &str) -> String {
+        format!("{:x}", Sha256::digest(secret.as_bytes()))
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Builds the same identity a validated JWT would produce.
+    pub fn claims(&self) -> Claims {
+        Claims {
+            iss: ACCESS_TOKEN_ISSUER.to_string(),
+            sub: self.user.to_string(),
+            aud: Vec::new(),
+            iat: self.created_at.timestamp().max(0) as u64,
+            exp: self.expires_at.timestamp().max(0) as u64,
+            azp: self.id.to_string(),
+            scope: self
+                .scopes
+                .iter()
+                .map(|scope| scope.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
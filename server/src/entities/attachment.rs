@@ -0,0 +1,80 @@
+use crate::entities::{GroupID, PaymentID, UserID};
+use async_graphql::{types::ID, NewType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use fake::{Dummy, Faker};
+#[cfg(test)]
+use rand::Rng;
+
+/// Content types accepted for attachments: photos of receipts and PDF invoices.
+pub const ATTACHMENT_CONTENT_TYPES: [&str; 5] = [
+    "image/jpeg",
+    "image/png",
+    "image/webp",
+    "image/heic",
+    "application/pdf",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, NewType)]
+pub struct AttachmentID(pub ID);
+
+impl AttachmentID {
+    pub fn new<T: ToString>(id: T) -> Self {
+        AttachmentID(ID(id.to_string()))
+    }
+}
+
+#[allow(clippy::to_string_trait_impl)]
+impl ToString for AttachmentID {
+    fn to_string(&self) -> String {
+        self.0 .0.to_string()
+    }
+}
+
+#[cfg(test)]
+impl Dummy<Faker> for AttachmentID {
+    fn dummy_with_rng<R: Rng + ?Sized>(config: &Faker, rng: &mut R) -> Self {
+        let id = String::dummy_with_rng(config, rng);
+        AttachmentID::new(id)
+    }
+}
+
+/// A file attached to a payment. The content lives in blob storage under
+/// `key`; only its metadata is kept in the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct Attachment {
+    pub id: AttachmentID,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub filename: String,
+    pub content_type: String,
+    /// Size in bytes.
+    pub size: i64,
+    pub key: String,
+    pub uploaded_by: UserID,
+
+    pub payment: PaymentID,
+    pub group: GroupID,
+}
+
+impl Attachment {
+    /// Blob key for a new attachment, grouped by group and payment so that a
+    /// bucket can be browsed or expired per group.
+    pub fn key_for(group: &GroupID, payment: &PaymentID, id: &AttachmentID) -> String {
+        format!(
+            "{}/{}/{}",
+            group.to_string(),
+            payment.to_string(),
+            id.to_string()
+        )
+    }
+
+    /// Path of the authenticated download endpoint.
+    pub fn url(&self) -> String {
+        format!("/attachments/{}", self.id.to_string())
+    }
+}
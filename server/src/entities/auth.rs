@@ -1,5 +1,7 @@
+use crate::entities::{Scope, ACCESS_TOKEN_ISSUER};
 use jsonwebtoken::{
     decode, decode_header,
+    errors::ErrorKind,
     jwk::{AlgorithmParameters, JwkSet},
     Algorithm, DecodingKey, Validation,
 };
@@ -11,7 +13,7 @@ use url::Url;
 #[cfg(test)]
 use fake::Dummy;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Dummy))]
 pub struct Claims {
     pub iss: String,
@@ -23,11 +25,70 @@ pub struct Claims {
     pub scope: String,
 }
 
-#[derive(Debug)]
+impl Claims {
+    pub fn is_access_token(&self) -> bool {
+        self.iss == ACCESS_TOKEN_ISSUER
+    }
+
+    /// Personal access tokens are limited to their scopes; JWTs are not.
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        !self.is_access_token() || self.scope.split(' ').any(|s| s == scope.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
 #[cfg_attr(test, derive(Dummy))]
 pub enum AuthState {
     Authorized(Claims),
-    Unauthorized,
+    Unauthorized(AuthError),
+}
+
+/// Why a request could not be authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[cfg_attr(test, derive(Dummy))]
+pub enum AuthError {
+    #[error("no bearer token was provided")]
+    MissingToken,
+
+    #[error("the authorization header is malformed")]
+    MalformedHeader,
+
+    #[error("the token has expired")]
+    TokenExpired,
+
+    #[error("the token was issued for a different audience")]
+    InvalidAudience,
+
+    #[error("the token was issued by an unexpected issuer")]
+    InvalidIssuer,
+
+    #[error("the token signature is invalid")]
+    InvalidSignature,
+
+    #[error("the token was signed with an unknown key")]
+    UnknownKey,
+
+    #[error("the token is invalid")]
+    InvalidToken,
+
+    #[error("the token could not be verified")]
+    Unavailable,
+}
+
+impl AuthError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthError::MissingToken => "MISSING_TOKEN",
+            AuthError::MalformedHeader => "MALFORMED_HEADER",
+            AuthError::TokenExpired => "TOKEN_EXPIRED",
+            AuthError::InvalidAudience => "INVALID_AUDIENCE",
+            AuthError::InvalidIssuer => "INVALID_ISSUER",
+            AuthError::InvalidSignature => "INVALID_SIGNATURE",
+            AuthError::UnknownKey => "UNKNOWN_KEY",
+            AuthError::InvalidToken => "INVALID_TOKEN",
+            AuthError::Unavailable => "AUTH_UNAVAILABLE",
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -42,6 +103,22 @@ pub enum JwtError {
     NoJwk,
 }
 
+impl From<JwtError> for AuthError {
+    fn from(err: JwtError) -> Self {
+        match err {
+            JwtError::Jwt(err) => match err.kind() {
+                ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                ErrorKind::InvalidAudience => AuthError::InvalidAudience,
+                ErrorKind::InvalidIssuer => AuthError::InvalidIssuer,
+                ErrorKind::InvalidSignature => AuthError::InvalidSignature,
+                _ => AuthError::InvalidToken,
+            },
+            JwtError::NoKid => AuthError::InvalidToken,
+            JwtError::NoJwk => AuthError::UnknownKey,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Validator {
     jwks: JwkSet,
@@ -0,0 +1,65 @@
+use crate::entities::{GroupID, PaymentID, UserID};
+use async_graphql::{types::ID, NewType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::entities::Millis;
+#[cfg(test)]
+use fake::{Dummy, Faker};
+#[cfg(test)]
+use rand::Rng;
+
+/// Longest comment body accepted, in characters.
+pub const MAX_COMMENT_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, NewType)]
+pub struct CommentID(pub ID);
+
+impl CommentID {
+    pub fn new<T: ToString>(id: T) -> Self {
+        CommentID(ID(id.to_string()))
+    }
+}
+
+#[allow(clippy::to_string_trait_impl)]
+impl ToString for CommentID {
+    fn to_string(&self) -> String {
+        self.0 .0.to_string()
+    }
+}
+
+#[cfg(test)]
+impl Dummy<Faker> for CommentID {
+    fn dummy_with_rng<R: Rng + ?Sized>(config: &Faker, rng: &mut R) -> Self {
+        let id = String::dummy_with_rng(config, rng);
+        CommentID::new(id)
+    }
+}
+
+/// A message in the discussion thread of a payment. Only its author can edit
+/// or delete it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct Comment {
+    pub id: CommentID,
+    #[cfg_attr(
+        feature = "mongodb",
+        serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")
+    )]
+    #[cfg_attr(test, dummy(faker = "Millis"))]
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub author: UserID,
+    pub body: String,
+
+    pub payment: PaymentID,
+    pub group: GroupID,
+}
+
+impl Comment {
+    pub fn is_edited(&self) -> bool {
+        self.updated_at > self.created_at
+    }
+}
@@ -3,6 +3,8 @@ use async_graphql::{types::ID, NewType};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+#[cfg(test)]
+use crate::entities::Millis;
 #[cfg(test)]
 use fake::{Dummy, Faker};
 #[cfg(test)]
@@ -17,6 +19,7 @@ impl GroupID {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for GroupID {
     fn to_string(&self) -> String {
         self.0 .0.to_string()
@@ -31,13 +34,58 @@ impl Dummy<Faker> for GroupID {
     }
 }
 
+pub const DEFAULT_CATEGORIES: [&str; 5] =
+    ["food", "transport", "lodging", "entertainment", "other"];
+
+pub fn default_categories() -> Vec<String> {
+    DEFAULT_CATEGORIES.iter().map(|c| c.to_string()).collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Dummy))]
 pub struct Group {
     pub id: GroupID,
+    #[cfg_attr(
+        feature = "mongodb",
+        serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")
+    )]
+    #[cfg_attr(test, dummy(faker = "Millis"))]
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 
     pub title: String,
     pub participants: Vec<UserID>,
+    /// Categories payments of the group can be filed under.
+    #[serde(default = "default_categories")]
+    pub categories: Vec<String>,
+    /// Whether debtors must confirm their share before a payment counts
+    /// towards `warikan` by default.
+    #[serde(default)]
+    pub require_confirmation: bool,
+    /// Participants that settle as one unit in household mode.
+    #[serde(default)]
+    #[cfg_attr(test, dummy(default))]
+    pub households: Vec<Household>,
+
+    /// Incremented on every update so that concurrent edits can be detected.
+    #[serde(default)]
+    pub version: i32,
+}
+
+/// A named subset of a group's participants, such as a couple, that settles
+/// as one unit. Expenses are still split per person.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct Household {
+    pub name: String,
+    pub members: Vec<UserID>,
+    /// Member who sends and receives the household's transfers; the first
+    /// member when unset.
+    pub representative: Option<UserID>,
+}
+
+impl Household {
+    pub fn representative(&self) -> Option<&UserID> {
+        self.representative.as_ref().or(self.members.first())
+    }
 }
@@ -0,0 +1,24 @@
+use crate::entities::{GroupID, UserID};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use fake::Dummy;
+
+/// Remembers which entity a create mutation produced for a client supplied
+/// key, so that a retried request returns it instead of creating a duplicate.
+/// Keys are scoped to the user that sent them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct IdempotencyKey {
+    pub user: UserID,
+    pub key: String,
+    /// Mutation the key was first used with, such as `createPayment`.
+    pub operation: String,
+    /// ID of the entity created under the key.
+    pub entity: String,
+    /// Group the entity was created in, for mutations scoped to one.
+    pub group: Option<GroupID>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
@@ -1,19 +1,52 @@
+mod access_token;
+mod attachment;
 mod auth;
+mod comment;
 mod group;
+mod idempotency;
 mod notification;
+mod page;
 mod payment;
+mod receipt;
+mod recurring_payment;
 mod user;
 mod warikan;
 
+pub use access_token::*;
+pub use attachment::*;
 pub use auth::*;
+pub use comment::*;
 pub use group::*;
+pub use idempotency::*;
 pub use notification::*;
+pub use page::*;
 pub use payment::*;
+pub use receipt::*;
+pub use recurring_payment::*;
 pub use user::*;
 pub use warikan::*;
 
 use thiserror::Error;
 
+#[cfg(test)]
+use chrono::{DateTime, Utc};
+#[cfg(test)]
+use fake::{chrono::Precision, Dummy, Fake};
+#[cfg(test)]
+use rand::Rng;
+
+/// Fakes datetimes at the millisecond precision they are stored with, so
+/// entities survive a round trip through the repository unchanged.
+#[cfg(test)]
+pub struct Millis;
+
+#[cfg(test)]
+impl Dummy<Millis> for DateTime<Utc> {
+    fn dummy_with_rng<R: Rng + ?Sized>(_: &Millis, rng: &mut R) -> Self {
+        Precision::<3>.fake_with_rng(rng)
+    }
+}
+
 #[derive(Debug, Error)]
 pub struct UnreachableError;
 
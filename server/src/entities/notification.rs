@@ -1,8 +1,10 @@
-use crate::entities::GroupID;
+use crate::entities::{GroupID, UserID};
 use async_graphql::{types::ID, NewType};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+#[cfg(test)]
+use crate::entities::Millis;
 #[cfg(test)]
 use fake::{Dummy, Faker};
 #[cfg(test)]
@@ -17,6 +19,7 @@ impl NotificationID {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for NotificationID {
     fn to_string(&self) -> String {
         self.0 .0.to_string()
@@ -35,10 +38,26 @@ impl Dummy<Faker> for NotificationID {
 #[cfg_attr(test, derive(Dummy))]
 pub struct Notification {
     pub id: NotificationID,
+    #[cfg_attr(
+        feature = "mongodb",
+        serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")
+    )]
+    #[cfg_attr(test, dummy(faker = "Millis"))]
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 
     pub message: String,
+    /// Users the notification is addressed to and shown to; empty when it concerns
+    /// the whole group.
+    #[serde(default)]
+    pub recipients: Vec<UserID>,
 
     pub group: GroupID,
 }
+
+impl Notification {
+    /// Whether `user` gets to see the notification, given they are in its group.
+    pub fn is_for(&self, user: &UserID) -> bool {
+        self.recipients.is_empty() || self.recipients.contains(user)
+    }
+}
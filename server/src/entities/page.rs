@@ -0,0 +1,143 @@
+use crate::entities::{Group, Notification, Payment};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// Position of an entity in the `created_at` + id ordering used for pagination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: String,
+}
+
+/// Relay-style window over a collection ordered by `created_at` and then id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageRequest {
+    pub after: Option<Cursor>,
+    pub before: Option<Cursor>,
+    pub first: Option<usize>,
+    pub last: Option<usize>,
+}
+
+impl PageRequest {
+    /// Applies the default page size and rejects pages larger than [`MAX_PAGE_SIZE`].
+    pub fn normalize(mut self) -> Option<Self> {
+        if self.first.is_none() && self.last.is_none() {
+            self.first = Some(DEFAULT_PAGE_SIZE);
+        }
+        let within = |size: Option<usize>| size.is_none_or(|size| size <= MAX_PAGE_SIZE);
+        (within(self.first) && within(self.last)).then_some(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+}
+
+impl<T> Page<T> {
+    /// Cuts a page out of `items`, which must hold the entities between the
+    /// `after` and `before` cursors in ascending order, with at most one extra
+    /// entity past the requested size on the side that is being paged towards.
+    pub fn from_window(mut items: Vec<T>, page: &PageRequest) -> Self {
+        let mut has_previous_page = page.after.is_some();
+        let mut has_next_page = page.before.is_some();
+        if let Some(first) = page.first {
+            if items.len() > first {
+                items.truncate(first);
+                has_next_page = true;
+            }
+        }
+        if let Some(last) = page.last {
+            if items.len() > last {
+                items.drain(..items.len() - last);
+                has_previous_page = true;
+            }
+        }
+        Page {
+            items,
+            has_previous_page,
+            has_next_page,
+        }
+    }
+}
+
+pub trait Paginated {
+    fn cursor(&self) -> Cursor;
+}
+
+impl Paginated for Group {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            created_at: self.created_at,
+            id: self.id.to_string(),
+        }
+    }
+}
+
+impl Paginated for Notification {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            created_at: self.created_at,
+            id: self.id.to_string(),
+        }
+    }
+}
+
+impl Paginated for Payment {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            created_at: self.created_at,
+            id: self.id.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_defaults_to_first_page() {
+        let page = PageRequest::default().normalize().unwrap();
+        assert_eq!(page.first, Some(DEFAULT_PAGE_SIZE));
+        assert_eq!(page.last, None);
+    }
+
+    #[test]
+    fn normalize_rejects_large_pages() {
+        let page = PageRequest {
+            last: Some(MAX_PAGE_SIZE + 1),
+            ..Default::default()
+        };
+        assert_eq!(page.normalize(), None);
+    }
+
+    #[test]
+    fn from_window_first() {
+        let page = PageRequest {
+            first: Some(2),
+            ..Default::default()
+        };
+        let page = Page::from_window(vec![1, 2, 3], &page);
+        assert_eq!(page.items, vec![1, 2]);
+        assert!(!page.has_previous_page);
+        assert!(page.has_next_page);
+    }
+
+    #[test]
+    fn from_window_last() {
+        let page = PageRequest {
+            last: Some(2),
+            ..Default::default()
+        };
+        let page = Page::from_window(vec![1, 2, 3], &page);
+        assert_eq!(page.items, vec![2, 3]);
+        assert!(page.has_previous_page);
+        assert!(!page.has_next_page);
+    }
+}
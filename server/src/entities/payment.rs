@@ -1,8 +1,12 @@
-use crate::entities::{GroupID, UserID};
-use async_graphql::{types::ID, NewType};
+use crate::entities::{Group, GroupID, Receipt, UserID};
+use async_graphql::{types::ID, Enum, InputObject, NewType};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
 
+#[cfg(test)]
+use crate::entities::Millis;
 #[cfg(test)]
 use fake::{Dummy, Faker};
 #[cfg(test)]
@@ -17,6 +21,7 @@ impl PaymentID {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for PaymentID {
     fn to_string(&self) -> String {
         self.0 .0.to_string()
@@ -35,14 +40,417 @@ impl Dummy<Faker> for PaymentID {
 #[cfg_attr(test, derive(Dummy))]
 pub struct Payment {
     pub id: PaymentID,
+    #[cfg_attr(
+        feature = "mongodb",
+        serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")
+    )]
+    #[cfg_attr(test, dummy(faker = "Millis"))]
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 
     pub title: String,
+    /// What `creditors` and `debtors` mean; see [`PaymentKind`].
+    #[serde(default)]
+    #[cfg_attr(test, dummy(default))]
+    pub kind: PaymentKind,
     pub creditors: Vec<Amount>,
     pub debtors: Vec<Amount>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// One of the group's `categories`.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// When the payment happened. Payments stored before this was recorded fall back to `created_at`.
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "mongodb",
+        serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")
+    )]
+    #[cfg_attr(test, dummy(faker = "Millis"))]
+    pub occurred_at: Option<DateTime<Utc>>,
+    /// ISO 4217 currency code.
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// Itemized bill the debtors are derived from.
+    #[serde(default)]
+    #[cfg_attr(test, dummy(default))]
+    pub receipt: Option<Receipt>,
+    /// Debtors' answers, for groups that require them to confirm their share.
+    #[serde(default)]
+    pub confirmations: Vec<Confirmation>,
 
     pub group: GroupID,
+
+    /// Incremented on every update so that concurrent edits can be detected.
+    #[serde(default)]
+    pub version: i32,
+}
+
+impl Payment {
+    /// Records `user`'s answer, replacing an earlier one. Returns `false`
+    /// without recording anything if `user` is not a debtor of the payment.
+    pub fn answer(
+        &mut self,
+        user: &UserID,
+        status: PaymentStatus,
+        reason: Option<String>,
+        at: DateTime<Utc>,
+    ) -> bool {
+        if !self.debtors.iter().any(|debtor| &debtor.user == user) {
+            return false;
+        }
+        self.confirmations
+            .retain(|confirmation| &confirmation.user != user);
+        self.confirmations.push(Confirmation {
+            user: user.clone(),
+            status,
+            reason,
+            at,
+        });
+        true
+    }
+
+    /// Status of `user`'s share; pending until they answer.
+    pub fn status_of(&self, user: &UserID) -> PaymentStatus {
+        self.confirmations
+            .iter()
+            .find(|confirmation| &confirmation.user == user)
+            .map_or(PaymentStatus::Pending, |confirmation| confirmation.status)
+    }
+
+    /// Disputed as soon as one debtor disputes their share, and confirmed once
+    /// every debtor confirmed it. Payments of groups that do not require
+    /// confirmation are always confirmed.
+    pub fn status(&self, group: &Group) -> PaymentStatus {
+        if !group.require_confirmation {
+            return PaymentStatus::Confirmed;
+        }
+        let statuses: Vec<PaymentStatus> = self
+            .debtors
+            .iter()
+            .map(|debtor| self.status_of(&debtor.user))
+            .collect();
+        if statuses.contains(&PaymentStatus::Disputed) {
+            PaymentStatus::Disputed
+        } else if statuses.contains(&PaymentStatus::Pending) {
+            PaymentStatus::Pending
+        } else {
+            PaymentStatus::Confirmed
+        }
+    }
+
+    /// Net effect on each user's balance, in order of first appearance:
+    /// positive when the group owes the user, negative when the user owes it.
+    pub fn balances(&self) -> Vec<Amount> {
+        let sign = match self.kind {
+            PaymentKind::Expense | PaymentKind::Transfer => 1,
+            PaymentKind::Refund | PaymentKind::Income => -1,
+        };
+        let mut balances: Vec<Amount> = Vec::new();
+        let amounts = self
+            .creditors
+            .iter()
+            .map(|creditor| (&creditor.user, sign * creditor.amount))
+            .chain(
+                self.debtors
+                    .iter()
+                    .map(|debtor| (&debtor.user, -sign * debtor.amount)),
+            );
+        for (user, amount) in amounts {
+            match balances.iter_mut().find(|balance| &balance.user == user) {
+                Some(balance) => balance.amount += amount,
+                None => balances.push(Amount {
+                    user: user.clone(),
+                    amount,
+                }),
+            }
+        }
+        balances
+    }
+
+    /// Checks the invariants `warikan` relies on against the payment's group.
+    pub fn violations(&self, group: &Group) -> Vec<PaymentViolation> {
+        let mut violations = Vec::new();
+        check_kind(self, &mut violations);
+        if !is_currency_code(&self.currency) {
+            violations.push(PaymentViolation::InvalidCurrency);
+        }
+        if let Some(category) = &self.category {
+            if !group.categories.contains(category) {
+                violations.push(PaymentViolation::UnknownCategory);
+            }
+        }
+        check_amounts(group, "creditors", &self.creditors, &mut violations);
+        check_amounts(group, "debtors", &self.debtors, &mut violations);
+        if let Some(receipt) = &self.receipt {
+            check_receipt(group, receipt, &mut violations);
+            match receipt.debtors() {
+                Some(debtors) if debtors != self.debtors => {
+                    violations.push(PaymentViolation::ReceiptMismatch)
+                }
+                Some(_) => {}
+                None => violations.push(PaymentViolation::TotalOverflow),
+            }
+        }
+
+        if self.creditors.is_empty() {
+            violations.push(PaymentViolation::Empty {
+                field: "creditors".to_string(),
+            });
+        }
+
+        let creditors: i64 = self.creditors.iter().map(|a| i64::from(a.amount)).sum();
+        let debtors: i64 = self.debtors.iter().map(|a| i64::from(a.amount)).sum();
+        if creditors == 0 {
+            violations.push(PaymentViolation::ZeroTotal);
+        }
+        if creditors != debtors {
+            violations.push(PaymentViolation::TotalMismatch { creditors, debtors });
+        }
+        if i32::try_from(creditors).is_err() {
+            violations.push(PaymentViolation::TotalOverflow);
+        }
+        violations
+    }
+}
+
+fn check_kind(payment: &Payment, violations: &mut Vec<PaymentViolation>) {
+    if payment.kind != PaymentKind::Expense && payment.receipt.is_some() {
+        violations.push(PaymentViolation::NotAllowedForKind {
+            field: "receipt".to_string(),
+        });
+    }
+    if payment.kind == PaymentKind::Transfer {
+        if payment.category.is_some() {
+            violations.push(PaymentViolation::NotAllowedForKind {
+                field: "category".to_string(),
+            });
+        }
+        match (payment.creditors.as_slice(), payment.debtors.as_slice()) {
+            ([creditor], [debtor]) if creditor.user == debtor.user => {
+                violations.push(PaymentViolation::SelfTransfer)
+            }
+            ([_], [_]) => {}
+            _ => violations.push(PaymentViolation::TransferParties),
+        }
+    }
+}
+
+fn check_amounts(
+    group: &Group,
+    field: &str,
+    amounts: &[Amount],
+    violations: &mut Vec<PaymentViolation>,
+) {
+    let mut seen = HashSet::new();
+    for (i, amount) in amounts.iter().enumerate() {
+        if !group.participants.contains(&amount.user) {
+            violations.push(PaymentViolation::NotParticipant {
+                field: format!("{field}[{i}].user"),
+            });
+        } else if !seen.insert(&amount.user) {
+            violations.push(PaymentViolation::DuplicateUser {
+                field: format!("{field}[{i}].user"),
+            });
+        }
+        if amount.amount <= 0 {
+            violations.push(PaymentViolation::NonPositiveAmount {
+                field: format!("{field}[{i}].amount"),
+            });
+        }
+    }
+}
+
+fn check_receipt(group: &Group, receipt: &Receipt, violations: &mut Vec<PaymentViolation>) {
+    for (i, item) in receipt.items.iter().enumerate() {
+        let field = format!("receipt.items[{i}]");
+        if item.price < 0 {
+            violations.push(PaymentViolation::NegativeAmount {
+                field: format!("{field}.price"),
+            });
+        }
+        if item.quantity <= 0 {
+            violations.push(PaymentViolation::NonPositiveAmount {
+                field: format!("{field}.quantity"),
+            });
+        }
+        if item.users.is_empty() {
+            violations.push(PaymentViolation::Unassigned {
+                field: format!("{field}.users"),
+            });
+        }
+        let mut seen = HashSet::new();
+        for (j, user) in item.users.iter().enumerate() {
+            if !group.participants.contains(user) {
+                violations.push(PaymentViolation::NotParticipant {
+                    field: format!("{field}.users[{j}]"),
+                });
+            } else if !seen.insert(user) {
+                violations.push(PaymentViolation::DuplicateUser {
+                    field: format!("{field}.users[{j}]"),
+                });
+            }
+        }
+    }
+    for (field, amount) in [
+        ("receipt.tax", receipt.tax),
+        ("receipt.service", receipt.service),
+        ("receipt.tip", receipt.tip),
+    ] {
+        if amount < 0 {
+            violations.push(PaymentViolation::NegativeAmount {
+                field: field.to_string(),
+            });
+        }
+    }
+}
+
+/// A broken payment invariant. `field` is a path into the payment, such as `debtors[1].amount`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PaymentViolation {
+    #[error("is not a participant of the group")]
+    NotParticipant { field: String },
+
+    #[error("is listed more than once")]
+    DuplicateUser { field: String },
+
+    #[error("must be positive")]
+    NonPositiveAmount { field: String },
+
+    #[error("must not be negative")]
+    NegativeAmount { field: String },
+
+    #[error("must not be empty")]
+    Empty { field: String },
+
+    #[error("must be assigned to at least one participant")]
+    Unassigned { field: String },
+
+    #[error("does not match the split of the receipt")]
+    ReceiptMismatch,
+
+    #[error("total {debtors} does not match the creditors' total {creditors}")]
+    TotalMismatch { creditors: i64, debtors: i64 },
+
+    #[error("total must not be zero")]
+    ZeroTotal,
+
+    #[error("total is too large")]
+    TotalOverflow,
+
+    #[error("must be an ISO 4217 code")]
+    InvalidCurrency,
+
+    #[error("is not a category of the group")]
+    UnknownCategory,
+
+    #[error("is not allowed for this kind of payment")]
+    NotAllowedForKind { field: String },
+
+    #[error("a transfer must have exactly one creditor and one debtor")]
+    TransferParties,
+
+    #[error("cannot transfer money to oneself")]
+    SelfTransfer,
+}
+
+impl PaymentViolation {
+    pub fn field(&self) -> &str {
+        match self {
+            PaymentViolation::NotParticipant { field }
+            | PaymentViolation::DuplicateUser { field }
+            | PaymentViolation::NonPositiveAmount { field }
+            | PaymentViolation::NegativeAmount { field }
+            | PaymentViolation::Empty { field }
+            | PaymentViolation::Unassigned { field }
+            | PaymentViolation::NotAllowedForKind { field } => field,
+            PaymentViolation::ReceiptMismatch => "debtors",
+            PaymentViolation::TotalMismatch { .. } => "debtors",
+            PaymentViolation::ZeroTotal => "creditors",
+            PaymentViolation::TotalOverflow => "creditors",
+            PaymentViolation::InvalidCurrency => "currency",
+            PaymentViolation::UnknownCategory => "category",
+            PaymentViolation::TransferParties => "debtors",
+            PaymentViolation::SelfTransfer => "debtors[0].user",
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            PaymentViolation::NotParticipant { .. } => "NOT_PARTICIPANT",
+            PaymentViolation::DuplicateUser { .. } => "DUPLICATE_USER",
+            PaymentViolation::NonPositiveAmount { .. } => "NON_POSITIVE_AMOUNT",
+            PaymentViolation::NegativeAmount { .. } => "NEGATIVE_AMOUNT",
+            PaymentViolation::Empty { .. } => "EMPTY",
+            PaymentViolation::Unassigned { .. } => "UNASSIGNED",
+            PaymentViolation::ReceiptMismatch => "RECEIPT_MISMATCH",
+            PaymentViolation::TotalMismatch { .. } => "TOTAL_MISMATCH",
+            PaymentViolation::ZeroTotal => "ZERO_TOTAL",
+            PaymentViolation::TotalOverflow => "TOTAL_OVERFLOW",
+            PaymentViolation::InvalidCurrency => "INVALID_CURRENCY",
+            PaymentViolation::UnknownCategory => "UNKNOWN_CATEGORY",
+            PaymentViolation::NotAllowedForKind { .. } => "NOT_ALLOWED_FOR_KIND",
+            PaymentViolation::TransferParties => "TRANSFER_PARTIES",
+            PaymentViolation::SelfTransfer => "SELF_TRANSFER",
+        }
+    }
+}
+
+/// A stored payment that breaks one or more invariants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPayment {
+    pub payment: Payment,
+    pub violations: Vec<PaymentViolation>,
+}
+
+pub const DEFAULT_CURRENCY: &str = "JPY";
+
+fn default_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
+}
+
+pub fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// How a payment moves money. Expenses and transfers credit the creditors
+/// and debit the debtors; refunds and incomes work the other way round.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[cfg_attr(test, derive(Dummy))]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentKind {
+    /// The creditors paid for something the debtors share.
+    #[default]
+    Expense,
+    /// The creditor handed money directly to the debtor, such as a loan or
+    /// paying back a debt.
+    Transfer,
+    /// The creditors got money back from a previous expense, lowering the
+    /// debtors' shares of it.
+    Refund,
+    /// The creditors received money that belongs to the debtors, such as a
+    /// returned deposit.
+    Income,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[cfg_attr(test, derive(Dummy))]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    Pending,
+    Confirmed,
+    Disputed,
+}
+
+/// A debtor's answer to the share a payment puts on their tab.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct Confirmation {
+    pub user: UserID,
+    pub status: PaymentStatus,
+    /// Why the share was disputed.
+    pub reason: Option<String>,
+    pub at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -51,3 +459,265 @@ pub struct Amount {
     pub user: UserID,
     pub amount: i32,
 }
+
+/// Divides `total` between `users` in proportion to `weights`. Remainders go
+/// to the users with the largest fractional share, earlier users first on ties.
+pub fn split(total: i32, users: &[UserID], weights: &[i32]) -> Vec<Amount> {
+    let sum: i64 = weights.iter().map(|&w| i64::from(w)).sum();
+    if sum <= 0 {
+        return Vec::new();
+    }
+    let shares: Vec<(i64, i64)> = weights
+        .iter()
+        .map(|&w| {
+            let exact = i64::from(total) * i64::from(w);
+            (exact / sum, exact % sum)
+        })
+        .collect();
+    let mut rest = i64::from(total) - shares.iter().map(|(share, _)| share).sum::<i64>();
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(shares[i].1));
+    let mut amounts: Vec<i64> = shares.iter().map(|(share, _)| *share).collect();
+    for i in order {
+        if rest == 0 {
+            break;
+        }
+        amounts[i] += 1;
+        rest -= 1;
+    }
+    users
+        .iter()
+        .zip(amounts)
+        .map(|(user, amount)| Amount {
+            user: user.clone(),
+            amount: amount as i32,
+        })
+        .collect()
+}
+
+/// Search criteria for payments. Unset fields match every payment; the total
+/// amount of a payment is the sum of its creditors.
+#[derive(Debug, Clone, Default, PartialEq, Eq, InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct PaymentFilter {
+    #[graphql(skip)]
+    pub groups: Vec<GroupID>,
+    /// Case-insensitive substring of the title.
+    pub title: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    /// Compared with `occurredAt`, which falls back to the creation time.
+    pub occurred_after: Option<DateTime<Utc>>,
+    pub occurred_before: Option<DateTime<Utc>>,
+    pub category: Option<String>,
+    pub creditor: Option<UserID>,
+    pub debtor: Option<UserID>,
+    pub min_amount: Option<i32>,
+    pub max_amount: Option<i32>,
+    /// Payments must carry all of these tags.
+    #[graphql(default)]
+    pub tags: Vec<String>,
+}
+
+impl PaymentFilter {
+    pub fn is_valid(&self) -> bool {
+        let range =
+            |after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>| match (after, before) {
+                (Some(after), Some(before)) => after <= before,
+                _ => true,
+            };
+        let dates = range(self.created_after, self.created_before)
+            && range(self.occurred_after, self.occurred_before);
+        let amounts = match (self.min_amount, self.max_amount) {
+            (Some(min), Some(max)) => min <= max,
+            _ => true,
+        };
+        dates && amounts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::default_categories;
+    use fake::Fake;
+
+    #[test]
+    fn split_equal() {
+        let users = vec![UserID::new("a"), UserID::new("b"), UserID::new("c")];
+        let amounts = split(1000, &users, &[1, 1, 1]);
+        let amounts: Vec<i32> = amounts.iter().map(|a| a.amount).collect();
+        assert_eq!(amounts, vec![334, 333, 333]);
+    }
+
+    #[test]
+    fn split_weighted() {
+        let users = vec![UserID::new("a"), UserID::new("b")];
+        let amounts = split(1000, &users, &[1, 3]);
+        let amounts: Vec<i32> = amounts.iter().map(|a| a.amount).collect();
+        assert_eq!(amounts, vec![250, 750]);
+    }
+
+    #[test]
+    fn violations() {
+        let mut group: Group = Faker.fake();
+        group.participants = vec![UserID::new("a"), UserID::new("b")];
+        group.categories = default_categories();
+        let mut payment: Payment = Faker.fake();
+        payment.currency = DEFAULT_CURRENCY.to_string();
+        payment.category = Some("food".to_string());
+        payment.creditors = vec![Amount {
+            user: UserID::new("a"),
+            amount: 1000,
+        }];
+        payment.debtors = vec![
+            Amount {
+                user: UserID::new("b"),
+                amount: 500,
+            },
+            Amount {
+                user: UserID::new("b"),
+                amount: 500,
+            },
+        ];
+        assert_eq!(
+            payment.violations(&group),
+            vec![PaymentViolation::DuplicateUser {
+                field: "debtors[1].user".to_string()
+            }]
+        );
+
+        payment.debtors[1].user = UserID::new("c");
+        payment.debtors[0].amount = 0;
+        assert_eq!(
+            payment
+                .violations(&group)
+                .iter()
+                .map(|v| v.code())
+                .collect::<Vec<_>>(),
+            vec!["NON_POSITIVE_AMOUNT", "NOT_PARTICIPANT", "TOTAL_MISMATCH"]
+        );
+
+        payment.debtors = payment.creditors.clone();
+        payment.category = Some("groceries".to_string());
+        assert_eq!(
+            payment.violations(&group),
+            vec![PaymentViolation::UnknownCategory]
+        );
+
+        payment.category = None;
+        payment.creditors.clear();
+        payment.debtors.clear();
+        assert_eq!(
+            payment
+                .violations(&group)
+                .iter()
+                .map(|v| v.code())
+                .collect::<Vec<_>>(),
+            vec!["EMPTY", "ZERO_TOTAL"]
+        );
+    }
+
+    #[test]
+    fn balances() {
+        let (a, b) = (UserID::new("a"), UserID::new("b"));
+        let mut payment: Payment = Faker.fake();
+        payment.creditors = vec![Amount {
+            user: a.clone(),
+            amount: 600,
+        }];
+        payment.debtors = vec![
+            Amount {
+                user: a.clone(),
+                amount: 300,
+            },
+            Amount {
+                user: b.clone(),
+                amount: 300,
+            },
+        ];
+        let amounts = |payment: &Payment| -> Vec<i32> {
+            payment.balances().iter().map(|b| b.amount).collect()
+        };
+
+        payment.kind = PaymentKind::Expense;
+        assert_eq!(amounts(&payment), vec![300, -300]);
+        payment.kind = PaymentKind::Refund;
+        assert_eq!(amounts(&payment), vec![-300, 300]);
+        payment.kind = PaymentKind::Income;
+        assert_eq!(amounts(&payment), vec![-300, 300]);
+    }
+
+    #[test]
+    fn transfer_violations() {
+        let mut group: Group = Faker.fake();
+        group.participants = vec![UserID::new("a"), UserID::new("b")];
+        let mut payment: Payment = Faker.fake();
+        payment.kind = PaymentKind::Transfer;
+        payment.currency = DEFAULT_CURRENCY.to_string();
+        payment.category = None;
+        payment.creditors = vec![Amount {
+            user: UserID::new("a"),
+            amount: 1000,
+        }];
+        payment.debtors = payment.creditors.clone();
+        assert_eq!(
+            payment.violations(&group),
+            vec![PaymentViolation::SelfTransfer]
+        );
+
+        payment.debtors[0].user = UserID::new("b");
+        assert_eq!(payment.violations(&group), vec![]);
+
+        payment.receipt = Some(Faker.fake());
+        assert_eq!(
+            payment.violations(&group)[0],
+            PaymentViolation::NotAllowedForKind {
+                field: "receipt".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn status() {
+        let mut group: Group = Faker.fake();
+        group.require_confirmation = true;
+        let (a, b) = (UserID::new("a"), UserID::new("b"));
+        let mut payment: Payment = Faker.fake();
+        payment.confirmations = Vec::new();
+        payment.debtors = vec![
+            Amount {
+                user: a.clone(),
+                amount: 500,
+            },
+            Amount {
+                user: b.clone(),
+                amount: 500,
+            },
+        ];
+        let now = Utc::now();
+        assert_eq!(payment.status(&group), PaymentStatus::Pending);
+
+        assert!(payment.answer(&a, PaymentStatus::Confirmed, None, now));
+        assert!(!payment.answer(&UserID::new("c"), PaymentStatus::Confirmed, None, now));
+        assert_eq!(payment.status(&group), PaymentStatus::Pending);
+
+        assert!(payment.answer(&b, PaymentStatus::Disputed, None, now));
+        assert_eq!(payment.status(&group), PaymentStatus::Disputed);
+
+        assert!(payment.answer(&b, PaymentStatus::Confirmed, None, now));
+        assert_eq!(payment.confirmations.len(), 2);
+        assert_eq!(payment.status(&group), PaymentStatus::Confirmed);
+
+        payment.confirmations = Vec::new();
+        group.require_confirmation = false;
+        assert_eq!(payment.status(&group), PaymentStatus::Confirmed);
+    }
+
+    #[test]
+    fn currency_code() {
+        assert!(is_currency_code("JPY"));
+        assert!(!is_currency_code("jpy"));
+        assert!(!is_currency_code("YEN!"));
+    }
+}
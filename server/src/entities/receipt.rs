@@ -0,0 +1,132 @@
+use crate::entities::{split, Amount, UserID};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use fake::Dummy;
+
+/// Line items of a bill. Every item is shared equally by the users it is
+/// assigned to, and tax, service charge and tip are distributed in proportion
+/// to what each user's items cost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct Receipt {
+    pub items: Vec<Item>,
+    pub tax: i32,
+    pub service: i32,
+    pub tip: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct Item {
+    pub name: String,
+    /// Unit price.
+    pub price: i32,
+    pub quantity: i32,
+    pub users: Vec<UserID>,
+}
+
+impl Item {
+    pub fn subtotal(&self) -> i64 {
+        i64::from(self.price) * i64::from(self.quantity)
+    }
+}
+
+impl Receipt {
+    pub fn extras(&self) -> i64 {
+        i64::from(self.tax) + i64::from(self.service) + i64::from(self.tip)
+    }
+
+    pub fn total(&self) -> i64 {
+        self.items.iter().map(Item::subtotal).sum::<i64>() + self.extras()
+    }
+
+    /// Each user's share of the receipt, in the order users first appear on
+    /// it. Users whose share is zero are left out. `None` if an amount does
+    /// not fit in `i32`.
+    pub fn debtors(&self) -> Option<Vec<Amount>> {
+        let mut users: Vec<UserID> = Vec::new();
+        let mut shares: Vec<i32> = Vec::new();
+        for item in &self.items {
+            let subtotal = i32::try_from(item.subtotal()).ok()?;
+            let weights = vec![1; item.users.len()];
+            for amount in split(subtotal, &item.users, &weights) {
+                match users.iter().position(|user| *user == amount.user) {
+                    Some(i) => shares[i] = shares[i].checked_add(amount.amount)?,
+                    None => {
+                        users.push(amount.user);
+                        shares.push(amount.amount);
+                    }
+                }
+            }
+        }
+
+        let extras = i32::try_from(self.extras()).ok()?;
+        let extras = split(extras, &users, &shares);
+        let mut debtors = Vec::with_capacity(users.len());
+        for (i, user) in users.into_iter().enumerate() {
+            let extra = extras.get(i).map_or(0, |extra| extra.amount);
+            let amount = shares[i].checked_add(extra)?;
+            if amount != 0 {
+                debtors.push(Amount { user, amount });
+            }
+        }
+        Some(debtors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(price: i32, quantity: i32, users: &[&str]) -> Item {
+        Item {
+            name: "item".to_string(),
+            price,
+            quantity,
+            users: users.iter().map(UserID::new).collect(),
+        }
+    }
+
+    #[test]
+    fn debtors() {
+        let receipt = Receipt {
+            items: vec![
+                item(1200, 1, &["a"]),
+                item(400, 2, &["b"]),
+                item(1000, 1, &["a", "b", "c"]),
+            ],
+            tax: 300,
+            service: 0,
+            tip: 0,
+        };
+        let debtors = receipt.debtors().unwrap();
+        let amounts: Vec<(String, i32)> = debtors
+            .iter()
+            .map(|a| (a.user.to_string(), a.amount))
+            .collect();
+        assert_eq!(
+            amounts,
+            vec![
+                ("a".to_string(), 1688),
+                ("b".to_string(), 1246),
+                ("c".to_string(), 366)
+            ]
+        );
+        assert_eq!(
+            debtors.iter().map(|a| i64::from(a.amount)).sum::<i64>(),
+            receipt.total()
+        );
+    }
+
+    #[test]
+    fn debtors_overflow() {
+        let receipt = Receipt {
+            items: vec![item(i32::MAX, 2, &["a"])],
+            tax: 0,
+            service: 0,
+            tip: 0,
+        };
+        assert_eq!(receipt.debtors(), None);
+    }
+}
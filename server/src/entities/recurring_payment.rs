@@ -0,0 +1,212 @@
+use crate::entities::{
+    Amount, Claims, GroupID, Payment, PaymentID, PaymentKind, PaymentStatus, UserID,
+};
+use async_graphql::{types::ID, Enum, NewType};
+use chrono::{DateTime, Days, Months, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::entities::Millis;
+#[cfg(test)]
+use fake::{Dummy, Faker};
+#[cfg(test)]
+use rand::Rng;
+
+/// Issuer of the claims the scheduler acts with on behalf of a template's owner.
+pub const SCHEDULER_ISSUER: &str = "warikan:scheduler";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, NewType)]
+pub struct RecurringPaymentID(pub ID);
+
+impl RecurringPaymentID {
+    pub fn new<T: ToString>(id: T) -> Self {
+        RecurringPaymentID(ID(id.to_string()))
+    }
+}
+
+#[allow(clippy::to_string_trait_impl)]
+impl ToString for RecurringPaymentID {
+    fn to_string(&self) -> String {
+        self.0 .0.to_string()
+    }
+}
+
+#[cfg(test)]
+impl Dummy<Faker> for RecurringPaymentID {
+    fn dummy_with_rng<R: Rng + ?Sized>(config: &Faker, rng: &mut R) -> Self {
+        let id = String::dummy_with_rng(config, rng);
+        RecurringPaymentID::new(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[cfg_attr(test, derive(Dummy))]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrenceUnit {
+    Day,
+    Week,
+    Month,
+}
+
+/// Template for a payment that is entered every `interval` `unit`s, starting
+/// at `starts_at`. The scheduler turns every occurrence that is due into a
+/// real payment created on behalf of `owner`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct RecurringPayment {
+    pub id: RecurringPaymentID,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub owner: UserID,
+
+    pub title: String,
+    pub creditors: Vec<Amount>,
+    pub debtors: Vec<Amount>,
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub currency: String,
+
+    pub unit: RecurrenceUnit,
+    pub interval: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Number of occurrences already created or skipped.
+    pub occurrences: i32,
+    /// Date of the next occurrence; `None` once the schedule has ended.
+    #[cfg_attr(
+        feature = "mongodb",
+        serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime_optional")
+    )]
+    #[cfg_attr(test, dummy(faker = "Millis"))]
+    pub next_at: Option<DateTime<Utc>>,
+    pub paused: bool,
+
+    pub group: GroupID,
+    pub version: i32,
+}
+
+impl RecurringPayment {
+    /// Date of the `n`th occurrence counted from `starts_at`, or `None` past
+    /// `ends_at`. Monthly schedules keep the day of the month, clamped to the
+    /// length of shorter months.
+    pub fn occurrence(&self, n: i32) -> Option<DateTime<Utc>> {
+        let steps = u32::try_from(n.checked_mul(self.interval)?).ok()?;
+        let date = match self.unit {
+            RecurrenceUnit::Day => self.starts_at.checked_add_days(Days::new(steps.into())),
+            RecurrenceUnit::Week => self
+                .starts_at
+                .checked_add_days(Days::new(u64::from(steps) * 7)),
+            RecurrenceUnit::Month => self.starts_at.checked_add_months(Months::new(steps)),
+        }?;
+        match self.ends_at {
+            Some(ends_at) if date > ends_at => None,
+            _ => Some(date),
+        }
+    }
+
+    /// Moves on to the occurrence after `next_at`.
+    pub fn advance(&mut self) {
+        self.occurrences += 1;
+        self.next_at = self.occurrence(self.occurrences);
+    }
+
+    /// Restarts the schedule from `at`, e.g. after the interval changed.
+    pub fn restart(&mut self, at: DateTime<Utc>) {
+        self.starts_at = at;
+        self.occurrences = 0;
+        self.next_at = self.occurrence(0);
+    }
+
+    /// Moves past every occurrence before `at`, e.g. the ones missed while paused.
+    pub fn skip_until(&mut self, at: DateTime<Utc>) {
+        while self.next_at.is_some_and(|next_at| next_at < at) {
+            self.advance();
+        }
+    }
+
+    /// Claims the scheduler creates the occurrences with, valid for a minute from `now`.
+    pub fn owner_claims(&self, now: DateTime<Utc>) -> Claims {
+        let iat = u64::try_from(now.timestamp()).unwrap_or_default();
+        Claims {
+            iss: SCHEDULER_ISSUER.to_string(),
+            sub: self.owner.to_string(),
+            aud: vec![],
+            iat,
+            exp: iat + 60,
+            azp: String::new(),
+            scope: String::new(),
+        }
+    }
+
+    /// The payment an occurrence at `at` turns into. The owner's own share
+    /// counts as confirmed, as if they had entered the payment themselves.
+    pub fn payment(&self, id: PaymentID, at: DateTime<Utc>) -> Payment {
+        let mut payment = Payment {
+            id,
+            created_at: at,
+            updated_at: at,
+            title: self.title.clone(),
+            kind: PaymentKind::Expense,
+            creditors: self.creditors.clone(),
+            debtors: self.debtors.clone(),
+            tags: self.tags.clone(),
+            category: self.category.clone(),
+            occurred_at: Some(at),
+            currency: self.currency.clone(),
+            receipt: None,
+            confirmations: Vec::new(),
+            group: self.group.clone(),
+            version: 0,
+        };
+        payment.answer(&self.owner, PaymentStatus::Confirmed, None, at);
+        payment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use fake::Fake;
+
+    fn recurring(unit: RecurrenceUnit, interval: i32) -> RecurringPayment {
+        let mut recurring: RecurringPayment = Faker.fake();
+        recurring.unit = unit;
+        recurring.interval = interval;
+        recurring.starts_at = Utc.with_ymd_and_hms(2024, 1, 31, 9, 0, 0).unwrap();
+        recurring.ends_at = None;
+        recurring
+    }
+
+    #[test]
+    fn occurrence_monthly() {
+        let recurring = recurring(RecurrenceUnit::Month, 1);
+        let dates: Vec<_> = (0..3)
+            .map(|n| recurring.occurrence(n).unwrap().date_naive().to_string())
+            .collect();
+        assert_eq!(dates, vec!["2024-01-31", "2024-02-29", "2024-03-31"]);
+    }
+
+    #[test]
+    fn occurrence_weekly_until_end() {
+        let mut recurring = recurring(RecurrenceUnit::Week, 2);
+        recurring.ends_at = Some(Utc.with_ymd_and_hms(2024, 2, 28, 0, 0, 0).unwrap());
+        assert_eq!(recurring.occurrence(2), None);
+        assert_eq!(
+            recurring.occurrence(1),
+            Some(Utc.with_ymd_and_hms(2024, 2, 14, 9, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn advance() {
+        let mut recurring = recurring(RecurrenceUnit::Day, 10);
+        recurring.restart(recurring.starts_at);
+        recurring.advance();
+        assert_eq!(recurring.occurrences, 1);
+        assert_eq!(
+            recurring.next_at,
+            Some(Utc.with_ymd_and_hms(2024, 2, 10, 9, 0, 0).unwrap())
+        );
+    }
+}
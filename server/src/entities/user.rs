@@ -18,6 +18,7 @@ impl UserID {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for UserID {
     fn to_string(&self) -> String {
         self.0 .0.to_string()
@@ -1,6 +1,7 @@
-use crate::entities::{Payment, UserID};
+use crate::entities::{Amount, Household, Payment, PaymentID, UserID};
+use async_graphql::InputObject;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{cmp::Reverse, collections::HashMap, sync::Arc};
 
 #[cfg(test)]
 use fake::Dummy;
@@ -11,45 +12,597 @@ pub struct Warikan {
     pub from: UserID,
     pub to: UserID,
     pub amount: i32,
+    /// Users the sender settles for; the members of a household.
+    #[serde(skip)]
+    pub from_members: Vec<UserID>,
+    /// Users the recipient settles for; the members of a household.
+    #[serde(skip)]
+    pub to_members: Vec<UserID>,
+    /// Payments the transfer settles, shared by every transfer of the
+    /// settlement. Empty when the transfer cannot be explained.
+    #[serde(skip)]
+    #[cfg_attr(test, dummy(default))]
+    pub payments: Arc<[Payment]>,
 }
 
-pub fn warikan(payments: &[Payment]) -> Option<Vec<Warikan>> {
+impl Warikan {
+    /// Payments behind the sender's balance.
+    pub fn from_contributions(&self) -> Vec<Contribution> {
+        contributions(&self.payments, &self.from_members)
+    }
+
+    /// Payments behind the recipient's balance.
+    pub fn to_contributions(&self) -> Vec<Contribution> {
+        contributions(&self.payments, &self.to_members)
+    }
+}
+
+/// How much a payment moved a balance: positive when it raised what the
+/// group owes, negative when it raised what is owed to the group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct Contribution {
+    pub payment: PaymentID,
+    pub amount: i32,
+}
+
+/// Balances of a group's users and the transfers that settle them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settlement {
+    pub balances: Vec<Amount>,
+    pub warikans: Vec<Warikan>,
+}
+
+/// A group's settlement with and without a set of proposed changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarikanPreview {
+    pub before: Settlement,
+    pub after: Settlement,
+}
+
+/// A payment on a user's statement and the balance it leaves them with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementEntry {
+    pub payment: PaymentID,
+    pub amount: i32,
+    pub balance: i32,
+}
+
+/// Units a settlement may round its transfers to.
+pub const SETTLEMENT_ROUNDINGS: [i32; 3] = [1, 10, 100];
+
+/// Bound on the number of steps the solver tries before giving up on
+/// constraints it cannot meet.
+const SETTLEMENT_SEARCH_LIMIT: usize = 10_000;
+
+/// Two users that must not transfer money to each other, in either direction.
+#[derive(Debug, Clone, PartialEq, Eq, InputObject)]
+pub struct UserPair {
+    pub user: UserID,
+    pub other: UserID,
+}
+
+/// Constraints on the transfers that settle a group. Transfers only ever go
+/// from users who owe money to users who are owed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, InputObject)]
+pub struct SettlementOptions {
+    #[graphql(default)]
+    pub forbidden_pairs: Vec<UserPair>,
+    /// Recipients paid first, in this order, whenever a debtor has a choice.
+    #[graphql(default)]
+    pub preferred_recipients: Vec<UserID>,
+    /// Every transfer is a multiple of this, one of 1, 10 or 100. Balances
+    /// then settle to within this amount. 1 when unset.
+    pub rounding: Option<i32>,
+    /// Most transfers a user may send and receive in total.
+    pub max_transfers_per_user: Option<i32>,
+}
+
+impl SettlementOptions {
+    pub fn is_valid(&self) -> bool {
+        self.rounding
+            .is_none_or(|rounding| SETTLEMENT_ROUNDINGS.contains(&rounding))
+            && self.max_transfers_per_user.is_none_or(|max| max > 0)
+    }
+
+    fn rounding(&self) -> i32 {
+        self.rounding.unwrap_or(1)
+    }
+
+    fn is_forbidden(&self, from: &UserID, to: &UserID) -> bool {
+        self.forbidden_pairs.iter().any(|pair| {
+            (pair.user == *from && pair.other == *to) || (pair.user == *to && pair.other == *from)
+        })
+    }
+
+    fn preference(&self, user: &UserID) -> usize {
+        self.preferred_recipients
+            .iter()
+            .position(|preferred| preferred == user)
+            .unwrap_or(self.preferred_recipients.len())
+    }
+}
+
+/// A household's balance with the per-person breakdown it is made of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HouseholdBalance {
+    pub household: Household,
+    /// Positive when the group owes the household.
+    pub amount: i32,
+    pub members: Vec<Amount>,
+}
+
+/// Net balance of every user over `payments`: positive when the group owes
+/// the user, negative when the user owes the group.
+pub fn balances(payments: &[Payment]) -> HashMap<UserID, i32> {
     let mut balance = HashMap::new();
     for payment in payments.iter() {
-        for creditor in payment.creditors.iter() {
-            *balance.entry(&creditor.user).or_insert(0) += creditor.amount;
-        }
-        for debtor in payment.debtors.iter() {
-            *balance.entry(&debtor.user).or_insert(0) -= debtor.amount;
+        for amount in payment.balances() {
+            *balance.entry(amount.user).or_insert(0) += amount.amount;
         }
     }
+    balance
+}
+
+/// Transfers that settle `payments` under `options`, or `None` when the
+/// options cannot be satisfied or the payments do not balance.
+pub fn warikan(payments: &[Payment], options: &SettlementOptions) -> Option<Vec<Warikan>> {
+    let warikans = settle(balances(payments), options)?;
+    Some(explain(warikans, payments, |user| vec![user.clone()]))
+}
 
+/// Like [`warikan`], but settles every household as one unit: its members'
+/// balances are added up and transferred by its representative.
+pub fn warikan_by_household(
+    payments: &[Payment],
+    households: &[Household],
+    options: &SettlementOptions,
+) -> Option<Vec<Warikan>> {
+    let mut balance = balances(payments);
+    for household in households {
+        let Some(representative) = household.representative() else {
+            continue;
+        };
+        let total: i32 = household
+            .members
+            .iter()
+            .filter_map(|member| balance.remove(member))
+            .sum();
+        *balance.entry(representative.clone()).or_insert(0) += total;
+    }
+    let warikans = settle(balance, options)?;
+    Some(explain(warikans, payments, |user| {
+        households
+            .iter()
+            .find(|household| household.representative() == Some(user))
+            .map(|household| household.members.clone())
+            .unwrap_or_else(|| vec![user.clone()])
+    }))
+}
+
+/// Every payment that moved `user`'s balance, oldest first, with the running
+/// balance after each. The last balance is all `user` owes or is owed; the
+/// transfers of [`warikan`] may leave up to their rounding of it unsettled.
+pub fn statement(payments: &[Payment], user: &UserID) -> Vec<StatementEntry> {
+    let mut payments = payments.to_vec();
+    payments.sort_by_key(|payment| {
+        (
+            payment.occurred_at.unwrap_or(payment.created_at),
+            payment.id.clone(),
+        )
+    });
+    let mut balance = 0;
+    contributions(&payments, std::slice::from_ref(user))
+        .into_iter()
+        .map(|contribution| {
+            balance += contribution.amount;
+            StatementEntry {
+                payment: contribution.payment,
+                amount: contribution.amount,
+                balance,
+            }
+        })
+        .collect()
+}
+
+/// Balance of every household and of each of its members.
+pub fn household_balances(payments: &[Payment], households: &[Household]) -> Vec<HouseholdBalance> {
+    let balance = balances(payments);
+    households
+        .iter()
+        .map(|household| {
+            let members: Vec<Amount> = household
+                .members
+                .iter()
+                .map(|member| Amount {
+                    user: member.clone(),
+                    amount: balance.get(member).copied().unwrap_or(0),
+                })
+                .collect();
+            HouseholdBalance {
+                household: household.clone(),
+                amount: members.iter().map(|member| member.amount).sum(),
+                members,
+            }
+        })
+        .collect()
+}
+
+/// What each payment added to the combined balance of `users`.
+fn contributions(payments: &[Payment], users: &[UserID]) -> Vec<Contribution> {
+    payments
+        .iter()
+        .filter_map(|payment| {
+            let amount: i32 = payment
+                .balances()
+                .iter()
+                .filter(|amount| users.contains(&amount.user))
+                .map(|amount| amount.amount)
+                .sum();
+            (amount != 0).then(|| Contribution {
+                payment: payment.id.clone(),
+                amount,
+            })
+        })
+        .collect()
+}
+
+/// Attaches to every transfer the payments behind it and the users each
+/// party settles for, listed by `members`, so that its contributions can be
+/// worked out when asked for.
+fn explain(
+    warikans: Vec<Warikan>,
+    payments: &[Payment],
+    members: impl Fn(&UserID) -> Vec<UserID>,
+) -> Vec<Warikan> {
+    let payments: Arc<[Payment]> = payments.into();
+    warikans
+        .into_iter()
+        .map(|warikan| Warikan {
+            from_members: members(&warikan.from),
+            to_members: members(&warikan.to),
+            payments: payments.clone(),
+            ..warikan
+        })
+        .collect()
+}
+
+fn settle(balance: HashMap<UserID, i32>, options: &SettlementOptions) -> Option<Vec<Warikan>> {
     if balance.values().sum::<i32>() != 0 {
-        return Some(Vec::new()); // TODO
+        return None;
     }
-    if balance.len() < 2 {
-        return Some(Vec::new());
+
+    let mut balance = round(balance, options.rounding());
+    balance.retain(|_, amount| *amount != 0);
+    let mut solver = Solver {
+        options,
+        counts: HashMap::new(),
+        transfers: Vec::new(),
+        budget: SETTLEMENT_SEARCH_LIMIT,
+    };
+    solver.solve(&mut balance).then_some(solver.transfers)
+}
+
+/// Rounds every balance to a multiple of `unit` while keeping their sum at
+/// zero. No balance moves by `unit` or more.
+fn round(balance: HashMap<UserID, i32>, unit: i32) -> HashMap<UserID, i32> {
+    if unit == 1 {
+        return balance;
     }
 
-    let mut warikans = Vec::new();
     let mut balance = balance
         .into_iter()
-        .map(|(user, amount)| (amount, user))
+        .map(|(user, amount)| {
+            let floor = amount.div_euclid(unit) * unit;
+            (amount - floor, user, floor)
+        })
         .collect::<Vec<_>>();
-    balance.sort();
-
-    while balance[0].0 != 0 {
-        let (debt, credit) = (balance.first().unwrap(), balance.last().unwrap());
-        let amount = debt.0.abs().min(credit.0.abs());
-        warikans.push(Warikan {
-            from: debt.1.clone(),
-            to: credit.1.clone(),
-            amount,
+    // The remainders add up to a multiple of `unit`; the largest round up.
+    let up = balance.iter().map(|(rest, _, _)| rest).sum::<i32>() / unit;
+    balance.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    balance
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, user, floor))| {
+            let amount = if (i as i32) < up { floor + unit } else { floor };
+            (user, amount)
+        })
+        .collect()
+}
+
+struct Solver<'a> {
+    options: &'a SettlementOptions,
+    counts: HashMap<UserID, i32>,
+    transfers: Vec<Warikan>,
+    budget: usize,
+}
+
+impl Solver<'_> {
+    /// Depth-first search that settles the largest debt first with the
+    /// largest credit, so that it finds the plain greedy settlement whenever
+    /// no options get in the way.
+    fn solve(&mut self, balance: &mut HashMap<UserID, i32>) -> bool {
+        let Some((debt, debtor)) = balance
+            .iter()
+            .filter(|(_, amount)| **amount < 0)
+            .map(|(user, amount)| (*amount, user.clone()))
+            .min()
+        else {
+            return true;
+        };
+        if self.budget == 0 || !self.can_transfer(&debtor) {
+            return false;
+        }
+        self.budget -= 1;
+
+        let mut creditors = balance
+            .iter()
+            .filter(|(user, amount)| {
+                **amount > 0 && self.can_transfer(user) && !self.options.is_forbidden(&debtor, user)
+            })
+            .map(|(user, amount)| (*amount, user.clone()))
+            .collect::<Vec<_>>();
+        creditors.sort_by_key(|(amount, user)| {
+            (
+                self.options.preference(user),
+                Reverse((*amount, user.clone())),
+            )
         });
-        balance.first_mut().unwrap().0 += amount;
-        balance.last_mut().unwrap().0 -= amount;
-        balance.sort();
+
+        for (credit, creditor) in creditors {
+            let amount = credit.min(-debt);
+            self.transfer(balance, &debtor, &creditor, amount);
+            if self.solve(balance) {
+                return true;
+            }
+            self.transfer(balance, &debtor, &creditor, -amount);
+        }
+        false
+    }
+
+    fn can_transfer(&self, user: &UserID) -> bool {
+        self.options
+            .max_transfers_per_user
+            .is_none_or(|max| self.counts.get(user).copied().unwrap_or(0) < max)
+    }
+
+    /// Applies a transfer, or undoes the last one when `amount` is negative.
+    fn transfer(
+        &mut self,
+        balance: &mut HashMap<UserID, i32>,
+        from: &UserID,
+        to: &UserID,
+        amount: i32,
+    ) {
+        *balance.get_mut(from).unwrap() += amount;
+        *balance.get_mut(to).unwrap() -= amount;
+        let step = amount.signum();
+        *self.counts.entry(from.clone()).or_insert(0) += step;
+        *self.counts.entry(to.clone()).or_insert(0) += step;
+        if step > 0 {
+            self.transfers.push(Warikan {
+                from: from.clone(),
+                to: to.clone(),
+                amount,
+                from_members: Vec::new(),
+                to_members: Vec::new(),
+                payments: Arc::default(),
+            });
+        } else {
+            self.transfers.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use fake::{Fake, Faker};
+
+    #[test]
+    fn warikan_by_household() {
+        let users: Vec<UserID> = ["a", "b", "c", "d"].map(UserID::new).to_vec();
+        let mut payment: Payment = Faker.fake();
+        payment.creditors = vec![Amount {
+            user: users[0].clone(),
+            amount: 4000,
+        }];
+        payment.debtors = users
+            .iter()
+            .map(|user| Amount {
+                user: user.clone(),
+                amount: 1000,
+            })
+            .collect();
+        let households = vec![Household {
+            name: "c and d".to_string(),
+            members: vec![users[2].clone(), users[3].clone()],
+            representative: Some(users[3].clone()),
+        }];
+        let payments = vec![payment];
+
+        let mut warikans =
+            super::warikan_by_household(&payments, &households, &SettlementOptions::default())
+                .unwrap();
+        warikans.sort_by_key(|w| w.amount);
+        assert_eq!(
+            warikans
+                .iter()
+                .map(|w| (w.from.clone(), w.to.clone(), w.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                (users[1].clone(), users[0].clone(), 1000),
+                (users[3].clone(), users[0].clone(), 2000),
+            ]
+        );
+        assert_eq!(
+            warikans[1].from_contributions(),
+            vec![Contribution {
+                payment: payments[0].id.clone(),
+                amount: -2000,
+            }]
+        );
+        assert_eq!(warikans[1].to_contributions()[0].amount, 3000);
+
+        let balances = household_balances(&payments, &households);
+        assert_eq!(balances[0].amount, -2000);
+        assert_eq!(
+            balances[0]
+                .members
+                .iter()
+                .map(|m| m.amount)
+                .collect::<Vec<_>>(),
+            vec![-1000, -1000]
+        );
     }
 
-    Some(warikans)
+    fn balance_of(amounts: &[(&str, i32)]) -> HashMap<UserID, i32> {
+        amounts
+            .iter()
+            .map(|(user, amount)| (UserID::new(user), *amount))
+            .collect()
+    }
+
+    fn net(warikans: &[Warikan]) -> HashMap<UserID, i32> {
+        let mut balance = HashMap::new();
+        for warikan in warikans {
+            *balance.entry(warikan.from.clone()).or_insert(0) -= warikan.amount;
+            *balance.entry(warikan.to.clone()).or_insert(0) += warikan.amount;
+        }
+        balance
+    }
+
+    #[test]
+    fn settle_unbalanced() {
+        let balance = balance_of(&[("a", 1000), ("b", -900)]);
+
+        let warikans = settle(balance, &SettlementOptions::default());
+        assert_eq!(warikans, None);
+    }
+
+    #[test]
+    fn settle_rounding() {
+        let balance = balance_of(&[("a", 6674), ("b", -3337), ("c", -3337)]);
+        let options = SettlementOptions {
+            rounding: Some(100),
+            ..Default::default()
+        };
+
+        let warikans = settle(balance.clone(), &options).unwrap();
+        assert!(warikans.iter().all(|w| w.amount % 100 == 0));
+        let settled = net(&warikans);
+        for (user, amount) in balance {
+            let diff = settled.get(&user).copied().unwrap_or(0) - amount;
+            assert!(diff.abs() < 100, "{user:?} is off by {diff}");
+        }
+    }
+
+    #[test]
+    fn settle_forbidden_pairs() {
+        let balance = balance_of(&[("a", 2000), ("b", 2000), ("c", -2000), ("d", -2000)]);
+        let options = SettlementOptions {
+            forbidden_pairs: vec![UserPair {
+                user: UserID::new("b"),
+                other: UserID::new("c"),
+            }],
+            ..Default::default()
+        };
+
+        let warikans = settle(balance.clone(), &options).unwrap();
+        assert!(warikans
+            .iter()
+            .all(|w| !(w.from == UserID::new("c") && w.to == UserID::new("b"))));
+        assert_eq!(net(&warikans), balance);
+    }
+
+    #[test]
+    fn settle_preferred_recipients() {
+        let balance = balance_of(&[("a", 1000), ("b", 1000), ("c", -1000), ("d", -1000)]);
+        let options = SettlementOptions {
+            preferred_recipients: vec![UserID::new("b")],
+            ..Default::default()
+        };
+
+        let warikans = settle(balance, &options).unwrap();
+        assert_eq!(
+            (&warikans[0].from, &warikans[0].to),
+            (&UserID::new("c"), &UserID::new("b"))
+        );
+    }
+
+    #[test]
+    fn settle_max_transfers_per_user() {
+        let balance = balance_of(&[("a", 2000), ("b", -1000), ("c", -1000)]);
+        let mut options = SettlementOptions {
+            max_transfers_per_user: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(settle(balance.clone(), &options).map(|w| w.len()), Some(2));
+
+        options.max_transfers_per_user = Some(1);
+        assert_eq!(settle(balance, &options), None);
+    }
+
+    #[test]
+    fn settlement_options_is_valid() {
+        assert!(SettlementOptions::default().is_valid());
+        let options = SettlementOptions {
+            rounding: Some(50),
+            ..Default::default()
+        };
+        assert!(!options.is_valid());
+        let options = SettlementOptions {
+            max_transfers_per_user: Some(0),
+            ..Default::default()
+        };
+        assert!(!options.is_valid());
+    }
+
+    #[test]
+    fn statement() {
+        let (a, b) = (UserID::new("a"), UserID::new("b"));
+        let mut lunch: Payment = Faker.fake();
+        lunch.occurred_at = Some(Utc::now());
+        lunch.creditors = vec![Amount {
+            user: a.clone(),
+            amount: 3000,
+        }];
+        lunch.debtors = vec![
+            Amount {
+                user: a.clone(),
+                amount: 1500,
+            },
+            Amount {
+                user: b.clone(),
+                amount: 1500,
+            },
+        ];
+        let mut taxi = lunch.clone();
+        taxi.id = Faker.fake();
+        taxi.occurred_at = lunch.occurred_at.map(|at| at - Duration::days(1));
+        taxi.creditors[0].user = b.clone();
+        let payments = vec![lunch.clone(), taxi.clone()];
+
+        let entries = super::statement(&payments, &a);
+        assert_eq!(
+            entries,
+            vec![
+                StatementEntry {
+                    payment: taxi.id,
+                    amount: -1500,
+                    balance: -1500,
+                },
+                StatementEntry {
+                    payment: lunch.id,
+                    amount: 1500,
+                    balance: 0,
+                },
+            ]
+        );
+        assert_eq!(
+            entries.last().map(|entry| entry.balance),
+            balances(&payments).get(&a).copied()
+        );
+    }
 }
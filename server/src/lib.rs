@@ -2,4 +2,5 @@ pub mod app;
 pub mod controllers;
 pub mod entities;
 pub mod repositories;
+pub mod storages;
 pub mod usecases;
@@ -0,0 +1,579 @@
+use crate::{
+    entities::{
+        AccessToken, AccessTokenID, Attachment, AttachmentID, Comment, CommentID, Group, GroupID,
+        IdempotencyKey, Notification, NotificationID, Page, PageRequest, Payment, PaymentFilter,
+        PaymentID, RecurringPayment, RecurringPaymentID, User, UserID,
+    },
+    repositories::{
+        AccessTokenRepository, AttachmentRepository, CommentRepository, GroupRepository,
+        HealthRepository, IdempotencyRepository, NotificationRepository, PaymentRepository,
+        RecurringPaymentRepository, RepositoryError, UserRepository,
+    },
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use metrics::{counter, gauge, histogram};
+use std::{future::Future, time::Instant};
+
+/// Wraps a repository and records call counts, latencies and errors per
+/// method, plus gauges of the groups, payments and users stored.
+#[derive(Debug)]
+pub struct MetricsRepository<R> {
+    pub inner: R,
+}
+
+impl<R> MetricsRepository<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+async fn observe<T>(
+    method: &'static str,
+    future: impl Future<Output = Result<T, RepositoryError>>,
+) -> Result<T, RepositoryError> {
+    let start = Instant::now();
+    let result = future.await;
+    histogram!("repository_call_duration_seconds", "method" => method)
+        .record(start.elapsed().as_secs_f64());
+    let status = if result.is_ok() { "ok" } else { "error" };
+    counter!("repository_calls_total", "method" => method, "status" => status).increment(1);
+    result
+}
+
+/// Moves the gauge `name` by `delta` once the write behind `result` succeeded.
+fn track<T>(name: &'static str, delta: f64, result: &Result<T, RepositoryError>) {
+    if result.is_ok() {
+        gauge!(name).increment(delta);
+    }
+}
+
+impl<R: GroupRepository + PaymentRepository + UserRepository> MetricsRepository<R> {
+    /// Sets the entity gauges from the stored counts. Creates and deletes
+    /// through this repository keep them current afterwards.
+    pub async fn seed(&self) -> Result<(), RepositoryError> {
+        gauge!("warikan_groups").set(self.inner.count_groups().await? as f64);
+        gauge!("warikan_payments").set(self.inner.count_payments().await? as f64);
+        gauge!("warikan_users").set(self.inner.count_users().await? as f64);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<R: AccessTokenRepository> AccessTokenRepository for MetricsRepository<R> {
+    async fn create_access_token(
+        &self,
+        access_token: AccessToken,
+    ) -> Result<AccessToken, RepositoryError> {
+        observe(
+            "create_access_token",
+            self.inner.create_access_token(access_token),
+        )
+        .await
+    }
+
+    async fn delete_access_token(&self, id: &AccessTokenID) -> Result<(), RepositoryError> {
+        observe("delete_access_token", self.inner.delete_access_token(id)).await
+    }
+
+    async fn get_access_token(
+        &self,
+        id: &AccessTokenID,
+    ) -> Result<Option<AccessToken>, RepositoryError> {
+        observe("get_access_token", self.inner.get_access_token(id)).await
+    }
+
+    async fn get_access_token_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Option<AccessToken>, RepositoryError> {
+        observe(
+            "get_access_token_by_hash",
+            self.inner.get_access_token_by_hash(hash),
+        )
+        .await
+    }
+
+    async fn get_access_tokens_by_user(
+        &self,
+        user: &UserID,
+    ) -> Result<Vec<AccessToken>, RepositoryError> {
+        observe(
+            "get_access_tokens_by_user",
+            self.inner.get_access_tokens_by_user(user),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<R: AttachmentRepository> AttachmentRepository for MetricsRepository<R> {
+    async fn create_attachment(
+        &self,
+        attachment: Attachment,
+    ) -> Result<Attachment, RepositoryError> {
+        observe(
+            "create_attachment",
+            self.inner.create_attachment(attachment),
+        )
+        .await
+    }
+
+    async fn delete_attachment(&self, id: &AttachmentID) -> Result<(), RepositoryError> {
+        observe("delete_attachment", self.inner.delete_attachment(id)).await
+    }
+
+    async fn get_attachment(
+        &self,
+        id: &AttachmentID,
+    ) -> Result<Option<Attachment>, RepositoryError> {
+        observe("get_attachment", self.inner.get_attachment(id)).await
+    }
+
+    async fn get_attachments_by_payment(
+        &self,
+        payment: &PaymentID,
+    ) -> Result<Vec<Attachment>, RepositoryError> {
+        observe(
+            "get_attachments_by_payment",
+            self.inner.get_attachments_by_payment(payment),
+        )
+        .await
+    }
+
+    async fn get_attachments_by_payments(
+        &self,
+        payments: &[PaymentID],
+    ) -> Result<Vec<Attachment>, RepositoryError> {
+        observe(
+            "get_attachments_by_payments",
+            self.inner.get_attachments_by_payments(payments),
+        )
+        .await
+    }
+
+    async fn get_attachments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<Attachment>, RepositoryError> {
+        observe(
+            "get_attachments_by_group",
+            self.inner.get_attachments_by_group(group),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<R: CommentRepository> CommentRepository for MetricsRepository<R> {
+    async fn create_comment(&self, comment: Comment) -> Result<Comment, RepositoryError> {
+        observe("create_comment", self.inner.create_comment(comment)).await
+    }
+
+    async fn update_comment(&self, comment: Comment) -> Result<Comment, RepositoryError> {
+        observe("update_comment", self.inner.update_comment(comment)).await
+    }
+
+    async fn delete_comment(&self, id: &CommentID) -> Result<(), RepositoryError> {
+        observe("delete_comment", self.inner.delete_comment(id)).await
+    }
+
+    async fn get_comment(&self, id: &CommentID) -> Result<Option<Comment>, RepositoryError> {
+        observe("get_comment", self.inner.get_comment(id)).await
+    }
+
+    async fn get_comments_by_payment(
+        &self,
+        payment: &PaymentID,
+    ) -> Result<Vec<Comment>, RepositoryError> {
+        observe(
+            "get_comments_by_payment",
+            self.inner.get_comments_by_payment(payment),
+        )
+        .await
+    }
+
+    async fn get_comments_by_payments(
+        &self,
+        payments: &[PaymentID],
+    ) -> Result<Vec<Comment>, RepositoryError> {
+        observe(
+            "get_comments_by_payments",
+            self.inner.get_comments_by_payments(payments),
+        )
+        .await
+    }
+
+    async fn get_comments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<Comment>, RepositoryError> {
+        observe(
+            "get_comments_by_group",
+            self.inner.get_comments_by_group(group),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<R: GroupRepository> GroupRepository for MetricsRepository<R> {
+    async fn create_group(&self, group: Group) -> Result<Group, RepositoryError> {
+        let result = observe("create_group", self.inner.create_group(group)).await;
+        track("warikan_groups", 1.0, &result);
+        result
+    }
+
+    async fn update_group(&self, group: Group) -> Result<Group, RepositoryError> {
+        observe("update_group", self.inner.update_group(group)).await
+    }
+
+    async fn delete_group(&self, id: &GroupID) -> Result<(), RepositoryError> {
+        let result = observe("delete_group", self.inner.delete_group(id)).await;
+        track("warikan_groups", -1.0, &result);
+        result
+    }
+
+    async fn get_group(&self, id: &GroupID) -> Result<Option<Group>, RepositoryError> {
+        observe("get_group", self.inner.get_group(id)).await
+    }
+
+    async fn get_groups_by_user(&self, id: &UserID) -> Result<Vec<Group>, RepositoryError> {
+        observe("get_groups_by_user", self.inner.get_groups_by_user(id)).await
+    }
+
+    async fn get_groups_page_by_user(
+        &self,
+        id: &UserID,
+        page: &PageRequest,
+    ) -> Result<Page<Group>, RepositoryError> {
+        observe(
+            "get_groups_page_by_user",
+            self.inner.get_groups_page_by_user(id, page),
+        )
+        .await
+    }
+
+    async fn get_groups_by_ids(&self, ids: &[GroupID]) -> Result<Vec<Group>, RepositoryError> {
+        observe("get_groups_by_ids", self.inner.get_groups_by_ids(ids)).await
+    }
+
+    async fn count_groups(&self) -> Result<u64, RepositoryError> {
+        observe("count_groups", self.inner.count_groups()).await
+    }
+}
+
+#[async_trait]
+impl<R: HealthRepository> HealthRepository for MetricsRepository<R> {
+    async fn ping(&self) -> Result<(), RepositoryError> {
+        observe("ping", self.inner.ping()).await
+    }
+}
+
+#[async_trait]
+impl<R: IdempotencyRepository> IdempotencyRepository for MetricsRepository<R> {
+    async fn create_idempotency_key(
+        &self,
+        key: IdempotencyKey,
+    ) -> Result<IdempotencyKey, RepositoryError> {
+        observe(
+            "create_idempotency_key",
+            self.inner.create_idempotency_key(key),
+        )
+        .await
+    }
+
+    async fn delete_idempotency_key(
+        &self,
+        user: &UserID,
+        key: &str,
+    ) -> Result<(), RepositoryError> {
+        observe(
+            "delete_idempotency_key",
+            self.inner.delete_idempotency_key(user, key),
+        )
+        .await
+    }
+
+    async fn get_idempotency_key(
+        &self,
+        user: &UserID,
+        key: &str,
+    ) -> Result<Option<IdempotencyKey>, RepositoryError> {
+        observe(
+            "get_idempotency_key",
+            self.inner.get_idempotency_key(user, key),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<R: NotificationRepository> NotificationRepository for MetricsRepository<R> {
+    async fn create_notification(
+        &self,
+        notification: Notification,
+    ) -> Result<Notification, RepositoryError> {
+        observe(
+            "create_notification",
+            self.inner.create_notification(notification),
+        )
+        .await
+    }
+
+    async fn delete_notification(&self, id: &NotificationID) -> Result<(), RepositoryError> {
+        observe("delete_notification", self.inner.delete_notification(id)).await
+    }
+
+    async fn get_notification(
+        &self,
+        id: &NotificationID,
+    ) -> Result<Option<Notification>, RepositoryError> {
+        observe("get_notification", self.inner.get_notification(id)).await
+    }
+
+    async fn get_notifications_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<Notification>, RepositoryError> {
+        observe(
+            "get_notifications_by_group",
+            self.inner.get_notifications_by_group(group),
+        )
+        .await
+    }
+
+    async fn get_notifications_page_by_group(
+        &self,
+        group: &GroupID,
+        recipient: &UserID,
+        page: &PageRequest,
+    ) -> Result<Page<Notification>, RepositoryError> {
+        observe(
+            "get_notifications_page_by_group",
+            self.inner
+                .get_notifications_page_by_group(group, recipient, page),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<R: PaymentRepository> PaymentRepository for MetricsRepository<R> {
+    async fn create_payment(&self, payment: Payment) -> Result<Payment, RepositoryError> {
+        let result = observe("create_payment", self.inner.create_payment(payment)).await;
+        track("warikan_payments", 1.0, &result);
+        result
+    }
+
+    async fn update_payment(&self, payment: Payment) -> Result<Payment, RepositoryError> {
+        observe("update_payment", self.inner.update_payment(payment)).await
+    }
+
+    async fn delete_payment(&self, id: &PaymentID) -> Result<(), RepositoryError> {
+        let result = observe("delete_payment", self.inner.delete_payment(id)).await;
+        track("warikan_payments", -1.0, &result);
+        result
+    }
+
+    async fn get_payment(&self, id: &PaymentID) -> Result<Option<Payment>, RepositoryError> {
+        observe("get_payment", self.inner.get_payment(id)).await
+    }
+
+    async fn get_payments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<Payment>, RepositoryError> {
+        observe(
+            "get_payments_by_group",
+            self.inner.get_payments_by_group(group),
+        )
+        .await
+    }
+
+    async fn get_payments_page_by_group(
+        &self,
+        group: &GroupID,
+        page: &PageRequest,
+    ) -> Result<Page<Payment>, RepositoryError> {
+        observe(
+            "get_payments_page_by_group",
+            self.inner.get_payments_page_by_group(group, page),
+        )
+        .await
+    }
+
+    async fn get_payments_by_ids(
+        &self,
+        ids: &[PaymentID],
+    ) -> Result<Vec<Payment>, RepositoryError> {
+        observe("get_payments_by_ids", self.inner.get_payments_by_ids(ids)).await
+    }
+
+    async fn count_payments(&self) -> Result<u64, RepositoryError> {
+        observe("count_payments", self.inner.count_payments()).await
+    }
+
+    async fn find_payments(&self, filter: &PaymentFilter) -> Result<Vec<Payment>, RepositoryError> {
+        observe("find_payments", self.inner.find_payments(filter)).await
+    }
+}
+
+#[async_trait]
+impl<R: RecurringPaymentRepository> RecurringPaymentRepository for MetricsRepository<R> {
+    async fn create_recurring_payment(
+        &self,
+        recurring_payment: RecurringPayment,
+    ) -> Result<RecurringPayment, RepositoryError> {
+        observe(
+            "create_recurring_payment",
+            self.inner.create_recurring_payment(recurring_payment),
+        )
+        .await
+    }
+
+    async fn update_recurring_payment(
+        &self,
+        recurring_payment: RecurringPayment,
+    ) -> Result<RecurringPayment, RepositoryError> {
+        observe(
+            "update_recurring_payment",
+            self.inner.update_recurring_payment(recurring_payment),
+        )
+        .await
+    }
+
+    async fn delete_recurring_payment(
+        &self,
+        id: &RecurringPaymentID,
+    ) -> Result<(), RepositoryError> {
+        observe(
+            "delete_recurring_payment",
+            self.inner.delete_recurring_payment(id),
+        )
+        .await
+    }
+
+    async fn get_recurring_payment(
+        &self,
+        id: &RecurringPaymentID,
+    ) -> Result<Option<RecurringPayment>, RepositoryError> {
+        observe(
+            "get_recurring_payment",
+            self.inner.get_recurring_payment(id),
+        )
+        .await
+    }
+
+    async fn get_recurring_payments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<RecurringPayment>, RepositoryError> {
+        observe(
+            "get_recurring_payments_by_group",
+            self.inner.get_recurring_payments_by_group(group),
+        )
+        .await
+    }
+
+    async fn get_due_recurring_payments(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> Result<Vec<RecurringPayment>, RepositoryError> {
+        observe(
+            "get_due_recurring_payments",
+            self.inner.get_due_recurring_payments(now),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<R: UserRepository> UserRepository for MetricsRepository<R> {
+    async fn create_user(&self, user: User) -> Result<User, RepositoryError> {
+        let result = observe("create_user", self.inner.create_user(user)).await;
+        track("warikan_users", 1.0, &result);
+        result
+    }
+
+    async fn update_user(&self, user: User) -> Result<User, RepositoryError> {
+        observe("update_user", self.inner.update_user(user)).await
+    }
+
+    async fn delete_user(&self, id: &UserID) -> Result<(), RepositoryError> {
+        let result = observe("delete_user", self.inner.delete_user(id)).await;
+        track("warikan_users", -1.0, &result);
+        result
+    }
+
+    async fn get_user(&self, id: &UserID) -> Result<Option<User>, RepositoryError> {
+        observe("get_user", self.inner.get_user(id)).await
+    }
+
+    async fn get_users_by_ids(&self, ids: &[UserID]) -> Result<Vec<User>, RepositoryError> {
+        observe("get_users_by_ids", self.inner.get_users_by_ids(ids)).await
+    }
+
+    async fn count_users(&self) -> Result<u64, RepositoryError> {
+        observe("count_users", self.inner.count_users()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::MockRepository;
+    use fake::{Fake, Faker};
+    use futures::executor::block_on;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    #[tokio::test]
+    async fn delegates_to_inner() {
+        let group: Group = Faker.fake();
+        let id = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_group().returning(Ok);
+        mock.expect_get_group()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let repository = MetricsRepository::new(mock);
+
+        let create = repository.create_group(group.clone()).await.unwrap();
+        assert_eq!(create, group);
+        assert!(repository.get_group(&id).await.is_err());
+    }
+
+    #[test]
+    fn tracks_entity_gauges() {
+        let group: Group = Faker.fake();
+        let id = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_count_groups().returning(|| Ok(3));
+        mock.expect_count_payments().returning(|| Ok(5));
+        mock.expect_count_users().returning(|| Ok(2));
+        mock.expect_create_group().returning(Ok);
+        mock.expect_delete_group()
+            .returning(|_| Err(RepositoryError::NotFound));
+
+        let repository = MetricsRepository::new(mock);
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            block_on(async {
+                repository.seed().await.unwrap();
+                repository.create_group(group).await.unwrap();
+                assert!(repository.delete_group(&id).await.is_err());
+            })
+        });
+
+        let rendered = handle.render();
+        assert!(rendered.contains("warikan_groups 4"), "{rendered}");
+        assert!(rendered.contains("warikan_payments 5"), "{rendered}");
+        assert!(
+            rendered.contains(r#"repository_calls_total{method="delete_group",status="error"} 1"#)
+        );
+    }
+}
@@ -1,13 +1,24 @@
+mod metrics;
 #[cfg(feature = "mongodb")]
 mod mongo;
+
+pub use metrics::*;
 #[cfg(feature = "mongodb")]
 pub use mongo::*;
 
 use crate::entities::{
-    Group, GroupID, Notification, NotificationID, Payment, PaymentID, User, UserID,
+    AccessToken, AccessTokenID, Attachment, AttachmentID, Comment, CommentID, Group, GroupID,
+    IdempotencyKey, Notification, NotificationID, Page, PageRequest, Payment, PaymentFilter,
+    PaymentID, RecurringPayment, RecurringPaymentID, User, UserID,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
 
+#[cfg(test)]
+use crate::entities::{Amount, Paginated};
+#[cfg(test)]
+use chrono::{Duration, SubsecRound};
 #[cfg(test)]
 use fake::{Fake, Faker};
 #[cfg(test)]
@@ -15,45 +26,195 @@ use mockall::predicate::*;
 #[cfg(test)]
 use mockall::*;
 
+/// Failure of a repository call, classified by each backend so that use cases
+/// can tell a client mistake from an outage.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum RepositoryError {
+    #[error("entity not found")]
+    NotFound,
+
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    #[error("repository unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("serialization failed: {0}")]
+    Serialization(String),
+
+    #[error("repository error: {0}")]
+    Other(String),
+}
+
 #[async_trait]
 pub trait Repository:
-    GroupRepository + NotificationRepository + PaymentRepository + UserRepository + Send + Sync
+    AccessTokenRepository
+    + AttachmentRepository
+    + CommentRepository
+    + GroupRepository
+    + HealthRepository
+    + IdempotencyRepository
+    + NotificationRepository
+    + PaymentRepository
+    + RecurringPaymentRepository
+    + UserRepository
+    + Send
+    + Sync
 {
 }
 
 impl<
-        T: GroupRepository + NotificationRepository + PaymentRepository + UserRepository + Send + Sync,
+        T: AccessTokenRepository
+            + AttachmentRepository
+            + CommentRepository
+            + GroupRepository
+            + HealthRepository
+            + IdempotencyRepository
+            + NotificationRepository
+            + PaymentRepository
+            + RecurringPaymentRepository
+            + UserRepository
+            + Send
+            + Sync,
     > Repository for T
 {
 }
 
 #[async_trait]
 #[cfg_attr(test, automock)]
-pub trait GroupRepository: Send + Sync {
-    async fn create_group(
+pub trait AccessTokenRepository: Send + Sync {
+    async fn create_access_token(
+        &self,
+        access_token: AccessToken,
+    ) -> Result<AccessToken, RepositoryError>;
+
+    async fn delete_access_token(&self, id: &AccessTokenID) -> Result<(), RepositoryError>;
+
+    async fn get_access_token(
+        &self,
+        id: &AccessTokenID,
+    ) -> Result<Option<AccessToken>, RepositoryError>;
+
+    async fn get_access_token_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Option<AccessToken>, RepositoryError>;
+
+    async fn get_access_tokens_by_user(
+        &self,
+        user: &UserID,
+    ) -> Result<Vec<AccessToken>, RepositoryError>;
+}
+
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub trait AttachmentRepository: Send + Sync {
+    async fn create_attachment(
         &self,
-        group: Group,
-    ) -> Result<Group, Box<dyn std::error::Error + Send + Sync>>;
+        attachment: Attachment,
+    ) -> Result<Attachment, RepositoryError>;
+
+    async fn delete_attachment(&self, id: &AttachmentID) -> Result<(), RepositoryError>;
 
-    async fn update_group(
+    async fn get_attachment(
         &self,
-        group: Group,
-    ) -> Result<Group, Box<dyn std::error::Error + Send + Sync>>;
+        id: &AttachmentID,
+    ) -> Result<Option<Attachment>, RepositoryError>;
 
-    async fn delete_group(
+    async fn get_attachments_by_payment(
         &self,
-        id: &GroupID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+        payment: &PaymentID,
+    ) -> Result<Vec<Attachment>, RepositoryError>;
 
-    async fn get_group(
+    async fn get_attachments_by_payments(
         &self,
-        id: &GroupID,
-    ) -> Result<Option<Group>, Box<dyn std::error::Error + Send + Sync>>;
+        payments: &[PaymentID],
+    ) -> Result<Vec<Attachment>, RepositoryError>;
+
+    async fn get_attachments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<Attachment>, RepositoryError>;
+}
+
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub trait CommentRepository: Send + Sync {
+    async fn create_comment(&self, comment: Comment) -> Result<Comment, RepositoryError>;
+
+    async fn update_comment(&self, comment: Comment) -> Result<Comment, RepositoryError>;
+
+    async fn delete_comment(&self, id: &CommentID) -> Result<(), RepositoryError>;
+
+    async fn get_comment(&self, id: &CommentID) -> Result<Option<Comment>, RepositoryError>;
+
+    /// Comments on `payment`, oldest first.
+    async fn get_comments_by_payment(
+        &self,
+        payment: &PaymentID,
+    ) -> Result<Vec<Comment>, RepositoryError>;
+
+    /// Comments on any of `payments`, oldest first.
+    async fn get_comments_by_payments(
+        &self,
+        payments: &[PaymentID],
+    ) -> Result<Vec<Comment>, RepositoryError>;
+
+    async fn get_comments_by_group(&self, group: &GroupID)
+        -> Result<Vec<Comment>, RepositoryError>;
+}
+
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub trait GroupRepository: Send + Sync {
+    async fn create_group(&self, group: Group) -> Result<Group, RepositoryError>;
+
+    /// Replaces the group if the stored one is still at `group.version`, and
+    /// returns it with the version incremented. Fails with
+    /// [`RepositoryError::Conflict`] if another write got there first.
+    async fn update_group(&self, group: Group) -> Result<Group, RepositoryError>;
+
+    async fn delete_group(&self, id: &GroupID) -> Result<(), RepositoryError>;
+
+    async fn get_group(&self, id: &GroupID) -> Result<Option<Group>, RepositoryError>;
+
+    async fn get_groups_by_user(&self, id: &UserID) -> Result<Vec<Group>, RepositoryError>;
 
-    async fn get_groups_by_user(
+    async fn get_groups_page_by_user(
         &self,
         id: &UserID,
-    ) -> Result<Vec<Group>, Box<dyn std::error::Error + Send + Sync>>;
+        page: &PageRequest,
+    ) -> Result<Page<Group>, RepositoryError>;
+
+    async fn get_groups_by_ids(&self, ids: &[GroupID]) -> Result<Vec<Group>, RepositoryError>;
+
+    async fn count_groups(&self) -> Result<u64, RepositoryError>;
+}
+
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub trait HealthRepository: Send + Sync {
+    async fn ping(&self) -> Result<(), RepositoryError>;
+}
+
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub trait IdempotencyRepository: Send + Sync {
+    /// Stores `key`, failing with [`RepositoryError::Conflict`] if the user
+    /// already holds a key with the same value.
+    async fn create_idempotency_key(
+        &self,
+        key: IdempotencyKey,
+    ) -> Result<IdempotencyKey, RepositoryError>;
+
+    async fn delete_idempotency_key(&self, user: &UserID, key: &str)
+        -> Result<(), RepositoryError>;
+
+    async fn get_idempotency_key(
+        &self,
+        user: &UserID,
+        key: &str,
+    ) -> Result<Option<IdempotencyKey>, RepositoryError>;
 }
 
 #[async_trait]
@@ -62,107 +223,282 @@ pub trait NotificationRepository: Send + Sync {
     async fn create_notification(
         &self,
         notification: Notification,
-    ) -> Result<Notification, Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<Notification, RepositoryError>;
 
-    async fn delete_notification(
-        &self,
-        id: &NotificationID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    async fn delete_notification(&self, id: &NotificationID) -> Result<(), RepositoryError>;
 
     async fn get_notification(
         &self,
         id: &NotificationID,
-    ) -> Result<Option<Notification>, Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<Option<Notification>, RepositoryError>;
 
     async fn get_notifications_by_group(
         &self,
         group: &GroupID,
-    ) -> Result<Vec<Notification>, Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<Vec<Notification>, RepositoryError>;
+
+    /// Only notifications addressed to `recipient` or to the whole group.
+    async fn get_notifications_page_by_group(
+        &self,
+        group: &GroupID,
+        recipient: &UserID,
+        page: &PageRequest,
+    ) -> Result<Page<Notification>, RepositoryError>;
 }
 
 #[async_trait]
 #[cfg_attr(test, automock)]
 pub trait PaymentRepository: Send + Sync {
-    async fn create_payment(
+    async fn create_payment(&self, payment: Payment) -> Result<Payment, RepositoryError>;
+
+    /// Replaces the payment if the stored one is still at `payment.version`,
+    /// and returns it with the version incremented. Fails with
+    /// [`RepositoryError::Conflict`] if another write got there first.
+    async fn update_payment(&self, payment: Payment) -> Result<Payment, RepositoryError>;
+
+    async fn delete_payment(&self, id: &PaymentID) -> Result<(), RepositoryError>;
+
+    async fn get_payment(&self, id: &PaymentID) -> Result<Option<Payment>, RepositoryError>;
+
+    async fn get_payments_by_group(&self, group: &GroupID)
+        -> Result<Vec<Payment>, RepositoryError>;
+
+    async fn get_payments_page_by_group(
         &self,
-        payment: Payment,
-    ) -> Result<Payment, Box<dyn std::error::Error + Send + Sync>>;
+        group: &GroupID,
+        page: &PageRequest,
+    ) -> Result<Page<Payment>, RepositoryError>;
+
+    async fn get_payments_by_ids(&self, ids: &[PaymentID])
+        -> Result<Vec<Payment>, RepositoryError>;
+
+    async fn count_payments(&self) -> Result<u64, RepositoryError>;
+
+    async fn find_payments(&self, filter: &PaymentFilter) -> Result<Vec<Payment>, RepositoryError>;
+}
 
-    async fn update_payment(
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub trait RecurringPaymentRepository: Send + Sync {
+    async fn create_recurring_payment(
+        &self,
+        recurring_payment: RecurringPayment,
+    ) -> Result<RecurringPayment, RepositoryError>;
+
+    /// Replaces the template if the stored one is still at
+    /// `recurring_payment.version`, and returns it with the version
+    /// incremented. Fails with [`RepositoryError::Conflict`] if another write
+    /// got there first.
+    async fn update_recurring_payment(
         &self,
-        payment: Payment,
-    ) -> Result<Payment, Box<dyn std::error::Error + Send + Sync>>;
+        recurring_payment: RecurringPayment,
+    ) -> Result<RecurringPayment, RepositoryError>;
 
-    async fn delete_payment(
+    async fn delete_recurring_payment(
         &self,
-        id: &PaymentID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+        id: &RecurringPaymentID,
+    ) -> Result<(), RepositoryError>;
 
-    async fn get_payment(
+    async fn get_recurring_payment(
         &self,
-        id: &PaymentID,
-    ) -> Result<Option<Payment>, Box<dyn std::error::Error + Send + Sync>>;
+        id: &RecurringPaymentID,
+    ) -> Result<Option<RecurringPayment>, RepositoryError>;
 
-    async fn get_payments_by_group(
+    async fn get_recurring_payments_by_group(
         &self,
         group: &GroupID,
-    ) -> Result<Vec<Payment>, Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<Vec<RecurringPayment>, RepositoryError>;
+
+    /// Templates that are not paused and whose next occurrence is at or before `now`.
+    async fn get_due_recurring_payments(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> Result<Vec<RecurringPayment>, RepositoryError>;
 }
 
 #[async_trait]
 #[cfg_attr(test, automock)]
 pub trait UserRepository: Send + Sync {
-    async fn create_user(
-        &self,
-        user: User,
-    ) -> Result<User, Box<dyn std::error::Error + Send + Sync>>;
+    async fn create_user(&self, user: User) -> Result<User, RepositoryError>;
 
-    async fn update_user(
-        &self,
-        user: User,
-    ) -> Result<User, Box<dyn std::error::Error + Send + Sync>>;
+    async fn update_user(&self, user: User) -> Result<User, RepositoryError>;
 
-    async fn delete_user(
-        &self,
-        id: &UserID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    async fn delete_user(&self, id: &UserID) -> Result<(), RepositoryError>;
 
-    async fn get_user(
-        &self,
-        id: &UserID,
-    ) -> Result<Option<User>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_user(&self, id: &UserID) -> Result<Option<User>, RepositoryError>;
+
+    async fn get_users_by_ids(&self, ids: &[UserID]) -> Result<Vec<User>, RepositoryError>;
+
+    async fn count_users(&self) -> Result<u64, RepositoryError>;
 }
 
 #[cfg(test)]
 mock! {
     pub Repository {}
 
+    #[async_trait]
+    impl AccessTokenRepository for Repository {
+        async fn create_access_token(
+            &self,
+            access_token: AccessToken,
+        ) -> Result<AccessToken, RepositoryError>;
+
+        async fn delete_access_token(
+            &self,
+            id: &AccessTokenID,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_access_token(
+            &self,
+            id: &AccessTokenID,
+        ) -> Result<Option<AccessToken>, RepositoryError>;
+
+        async fn get_access_token_by_hash(
+            &self,
+            hash: &str,
+        ) -> Result<Option<AccessToken>, RepositoryError>;
+
+        async fn get_access_tokens_by_user(
+            &self,
+            user: &UserID,
+        ) -> Result<Vec<AccessToken>, RepositoryError>;
+    }
+
+    #[async_trait]
+    impl AttachmentRepository for Repository {
+        async fn create_attachment(
+            &self,
+            attachment: Attachment,
+        ) -> Result<Attachment, RepositoryError>;
+
+        async fn delete_attachment(
+            &self,
+            id: &AttachmentID,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_attachment(
+            &self,
+            id: &AttachmentID,
+        ) -> Result<Option<Attachment>, RepositoryError>;
+
+        async fn get_attachments_by_payment(
+            &self,
+            payment: &PaymentID,
+        ) -> Result<Vec<Attachment>, RepositoryError>;
+
+        async fn get_attachments_by_payments(
+            &self,
+            payments: &[PaymentID],
+        ) -> Result<Vec<Attachment>, RepositoryError>;
+
+        async fn get_attachments_by_group(
+            &self,
+            group: &GroupID,
+        ) -> Result<Vec<Attachment>, RepositoryError>;
+    }
+
+    #[async_trait]
+    impl CommentRepository for Repository {
+        async fn create_comment(
+            &self,
+            comment: Comment,
+        ) -> Result<Comment, RepositoryError>;
+
+        async fn update_comment(
+            &self,
+            comment: Comment,
+        ) -> Result<Comment, RepositoryError>;
+
+        async fn delete_comment(
+            &self,
+            id: &CommentID,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_comment(
+            &self,
+            id: &CommentID,
+        ) -> Result<Option<Comment>, RepositoryError>;
+
+        async fn get_comments_by_payment(
+            &self,
+            payment: &PaymentID,
+        ) -> Result<Vec<Comment>, RepositoryError>;
+
+        async fn get_comments_by_payments(
+            &self,
+            payments: &[PaymentID],
+        ) -> Result<Vec<Comment>, RepositoryError>;
+
+        async fn get_comments_by_group(
+            &self,
+            group: &GroupID,
+        ) -> Result<Vec<Comment>, RepositoryError>;
+    }
+
     #[async_trait]
     impl GroupRepository for Repository {
         async fn create_group(
             &self,
             group: Group,
-        ) -> Result<Group, Box<dyn std::error::Error + Send + Sync>>;
+        ) -> Result<Group, RepositoryError>;
 
         async fn update_group(
             &self,
             group: Group,
-        ) -> Result<Group, Box<dyn std::error::Error + Send + Sync>>;
+        ) -> Result<Group, RepositoryError>;
 
         async fn delete_group(
             &self,
             id: &GroupID,
-        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+        ) -> Result<(), RepositoryError>;
 
         async fn get_group(
             &self,
             id: &GroupID,
-        ) -> Result<Option<Group>, Box<dyn std::error::Error + Send + Sync>>;
+        ) -> Result<Option<Group>, RepositoryError>;
 
         async fn get_groups_by_user(
             &self,
             id: &UserID,
-        ) -> Result<Vec<Group>, Box<dyn std::error::Error + Send + Sync>>;
+        ) -> Result<Vec<Group>, RepositoryError>;
+
+        async fn get_groups_page_by_user(
+            &self,
+            id: &UserID,
+            page: &PageRequest,
+        ) -> Result<Page<Group>, RepositoryError>;
+
+        async fn get_groups_by_ids(
+            &self,
+            ids: &[GroupID],
+        ) -> Result<Vec<Group>, RepositoryError>;
+
+        async fn count_groups(&self) -> Result<u64, RepositoryError>;
+    }
+
+    #[async_trait]
+    impl HealthRepository for Repository {
+        async fn ping(&self) -> Result<(), RepositoryError>;
+    }
+
+    #[async_trait]
+    impl IdempotencyRepository for Repository {
+        async fn create_idempotency_key(
+            &self,
+            key: IdempotencyKey,
+        ) -> Result<IdempotencyKey, RepositoryError>;
+
+        async fn delete_idempotency_key(
+            &self,
+            user: &UserID,
+            key: &str,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_idempotency_key(
+            &self,
+            user: &UserID,
+            key: &str,
+        ) -> Result<Option<IdempotencyKey>, RepositoryError>;
     }
 
     #[async_trait]
@@ -170,73 +506,450 @@ mock! {
         async fn create_notification(
             &self,
             notification: Notification,
-        ) -> Result<Notification, Box<dyn std::error::Error + Send + Sync>>;
+        ) -> Result<Notification, RepositoryError>;
+
+        async fn delete_notification(
+            &self,
+            id: &NotificationID,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_notification(
+            &self,
+            id: &NotificationID,
+        ) -> Result<Option<Notification>, RepositoryError>;
+
+        async fn get_notifications_by_group(
+            &self,
+            group: &GroupID,
+        ) -> Result<Vec<Notification>, RepositoryError>;
+
+        async fn get_notifications_page_by_group(
+            &self,
+            group: &GroupID,
+            recipient: &UserID,
+            page: &PageRequest,
+        ) -> Result<Page<Notification>, RepositoryError>;
+    }
+
+    #[async_trait]
+    impl PaymentRepository for Repository {
+        async fn create_payment(
+            &self,
+            payment: Payment,
+        ) -> Result<Payment, RepositoryError>;
+
+        async fn update_payment(
+            &self,
+            payment: Payment,
+        ) -> Result<Payment, RepositoryError>;
+
+        async fn delete_payment(
+            &self,
+            id: &PaymentID,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_payment(
+            &self,
+            id: &PaymentID,
+        ) -> Result<Option<Payment>, RepositoryError>;
+
+        async fn get_payments_by_group(
+            &self,
+            group: &GroupID,
+        ) -> Result<Vec<Payment>, RepositoryError>;
+
+        async fn get_payments_page_by_group(
+            &self,
+            group: &GroupID,
+            page: &PageRequest,
+        ) -> Result<Page<Payment>, RepositoryError>;
+
+        async fn get_payments_by_ids(
+            &self,
+            ids: &[PaymentID],
+        ) -> Result<Vec<Payment>, RepositoryError>;
+
+        async fn count_payments(&self) -> Result<u64, RepositoryError>;
+
+        async fn find_payments(
+            &self,
+            filter: &PaymentFilter,
+        ) -> Result<Vec<Payment>, RepositoryError>;
+    }
+
+    #[async_trait]
+    impl RecurringPaymentRepository for Repository {
+        async fn create_recurring_payment(
+            &self,
+            recurring_payment: RecurringPayment,
+        ) -> Result<RecurringPayment, RepositoryError>;
+
+        async fn update_recurring_payment(
+            &self,
+            recurring_payment: RecurringPayment,
+        ) -> Result<RecurringPayment, RepositoryError>;
+
+        async fn delete_recurring_payment(
+            &self,
+            id: &RecurringPaymentID,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_recurring_payment(
+            &self,
+            id: &RecurringPaymentID,
+        ) -> Result<Option<RecurringPayment>, RepositoryError>;
+
+        async fn get_recurring_payments_by_group(
+            &self,
+            group: &GroupID,
+        ) -> Result<Vec<RecurringPayment>, RepositoryError>;
+
+        async fn get_due_recurring_payments(
+            &self,
+            now: &DateTime<Utc>,
+        ) -> Result<Vec<RecurringPayment>, RepositoryError>;
+    }
+
+    #[async_trait]
+    impl UserRepository for Repository {
+        async fn create_user(
+            &self,
+            user: User,
+        ) -> Result<User, RepositoryError>;
+
+        async fn update_user(
+            &self,
+            user: User,
+        ) -> Result<User, RepositoryError>;
+
+        async fn delete_user(
+            &self,
+            id: &UserID,
+        ) -> Result<(), RepositoryError>;
+
+        async fn get_user(
+            &self,
+            id: &UserID,
+        ) -> Result<Option<User>, RepositoryError>;
+
+        async fn get_users_by_ids(
+            &self,
+            ids: &[UserID],
+        ) -> Result<Vec<User>, RepositoryError>;
+
+        async fn count_users(&self) -> Result<u64, RepositoryError>;
+    }
+}
+
+#[cfg(test)]
+pub struct AccessTokenRepositoryTester<R: AccessTokenRepository> {
+    pub repository: R,
+}
+
+#[cfg(test)]
+impl<R: AccessTokenRepository> AccessTokenRepositoryTester<R> {
+    pub async fn test(repository: R) {
+        let tester = Self { repository };
+        tester.create_access_token().await;
+        tester.delete_access_token().await;
+        tester.get_access_token_by_hash().await;
+        tester.get_access_tokens_by_user().await;
+    }
+
+    async fn create_access_token(&self) {
+        let access_token: AccessToken = Faker.fake();
+
+        let create = self
+            .repository
+            .create_access_token(access_token)
+            .await
+            .unwrap();
+        let get = self.repository.get_access_token(&create.id).await.unwrap();
+
+        assert_eq!(Some(create), get);
+    }
+
+    async fn delete_access_token(&self) {
+        let access_token: AccessToken = Faker.fake();
+
+        let create = self
+            .repository
+            .create_access_token(access_token)
+            .await
+            .unwrap();
+        self.repository
+            .delete_access_token(&create.id)
+            .await
+            .unwrap();
+        let delete = self.repository.get_access_token(&create.id).await.unwrap();
+
+        assert_eq!(delete, None);
+    }
+
+    async fn get_access_token_by_hash(&self) {
+        let access_token: AccessToken = Faker.fake();
+
+        let create = self
+            .repository
+            .create_access_token(access_token)
+            .await
+            .unwrap();
+        let get = self
+            .repository
+            .get_access_token_by_hash(&create.hash)
+            .await
+            .unwrap();
+
+        assert_eq!(Some(create), get);
+    }
+
+    async fn get_access_tokens_by_user(&self) {
+        let mut access_token1: AccessToken = Faker.fake();
+        let mut access_token2: AccessToken = Faker.fake();
+        let access_token3: AccessToken = Faker.fake();
+
+        let user: UserID = Faker.fake();
+        access_token1.user = user.clone();
+        access_token2.user = user.clone();
+
+        self.repository
+            .create_access_token(access_token1.clone())
+            .await
+            .unwrap();
+        self.repository
+            .create_access_token(access_token2.clone())
+            .await
+            .unwrap();
+        self.repository
+            .create_access_token(access_token3.clone())
+            .await
+            .unwrap();
+
+        let get = self
+            .repository
+            .get_access_tokens_by_user(&user)
+            .await
+            .unwrap();
+
+        assert_eq!(vec![access_token1, access_token2], get);
+    }
+}
+
+#[cfg(test)]
+pub struct AttachmentRepositoryTester<R: AttachmentRepository> {
+    pub repository: R,
+}
+
+#[cfg(test)]
+impl<R: AttachmentRepository> AttachmentRepositoryTester<R> {
+    pub async fn test(repository: R) {
+        let tester = Self { repository };
+        tester.create_attachment().await;
+        tester.delete_attachment().await;
+        tester.get_attachments_by_payment().await;
+        tester.get_attachments_by_payments().await;
+        tester.get_attachments_by_group().await;
+    }
+
+    async fn create_attachment(&self) {
+        let attachment: Attachment = Faker.fake();
+
+        let create = self.repository.create_attachment(attachment).await.unwrap();
+        let get = self.repository.get_attachment(&create.id).await.unwrap();
+
+        assert_eq!(Some(create), get);
+    }
+
+    async fn delete_attachment(&self) {
+        let attachment: Attachment = Faker.fake();
+
+        let create = self.repository.create_attachment(attachment).await.unwrap();
+        self.repository.delete_attachment(&create.id).await.unwrap();
+        let delete = self.repository.get_attachment(&create.id).await.unwrap();
+        let again = self.repository.delete_attachment(&create.id).await;
+
+        assert_eq!(delete, None);
+        assert_eq!(again, Err(RepositoryError::NotFound));
+    }
+
+    async fn get_attachments_by_payment(&self) {
+        let mut attachment1: Attachment = Faker.fake();
+        let mut attachment2: Attachment = Faker.fake();
+        let attachment3: Attachment = Faker.fake();
+
+        let payment: PaymentID = Faker.fake();
+        attachment1.payment = payment.clone();
+        attachment2.payment = payment.clone();
+
+        for attachment in [attachment1.clone(), attachment2.clone(), attachment3] {
+            self.repository.create_attachment(attachment).await.unwrap();
+        }
+
+        let get = self
+            .repository
+            .get_attachments_by_payment(&payment)
+            .await
+            .unwrap();
+
+        assert_eq!(vec![attachment1, attachment2], get);
+    }
+
+    async fn get_attachments_by_payments(&self) {
+        let attachment1: Attachment = Faker.fake();
+        let attachment2: Attachment = Faker.fake();
+        let attachment3: Attachment = Faker.fake();
+
+        for attachment in [attachment1.clone(), attachment2.clone(), attachment3] {
+            self.repository.create_attachment(attachment).await.unwrap();
+        }
+
+        let payments = vec![attachment1.payment.clone(), attachment2.payment.clone()];
+        let get = self
+            .repository
+            .get_attachments_by_payments(&payments)
+            .await
+            .unwrap();
+
+        assert_eq!(vec![attachment1, attachment2], get);
+    }
+
+    async fn get_attachments_by_group(&self) {
+        let mut attachment1: Attachment = Faker.fake();
+        let mut attachment2: Attachment = Faker.fake();
+        let attachment3: Attachment = Faker.fake();
+
+        let group: GroupID = Faker.fake();
+        attachment1.group = group.clone();
+        attachment2.group = group.clone();
+
+        for attachment in [attachment1.clone(), attachment2.clone(), attachment3] {
+            self.repository.create_attachment(attachment).await.unwrap();
+        }
+
+        let get = self
+            .repository
+            .get_attachments_by_group(&group)
+            .await
+            .unwrap();
+
+        assert_eq!(vec![attachment1, attachment2], get);
+    }
+}
+
+#[cfg(test)]
+pub struct CommentRepositoryTester<R: CommentRepository> {
+    pub repository: R,
+}
+
+#[cfg(test)]
+impl<R: CommentRepository> CommentRepositoryTester<R> {
+    pub async fn test(repository: R) {
+        let tester = Self { repository };
+        tester.create_comment().await;
+        tester.update_comment().await;
+        tester.delete_comment().await;
+        tester.get_comments_by_payment().await;
+        tester.get_comments_by_payments().await;
+        tester.get_comments_by_group().await;
+    }
+
+    async fn create_comment(&self) {
+        let comment: Comment = Faker.fake();
+
+        let create = self.repository.create_comment(comment).await.unwrap();
+        let get = self.repository.get_comment(&create.id).await.unwrap();
+
+        assert_eq!(Some(create), get);
+    }
+
+    async fn update_comment(&self) {
+        let comment1: Comment = Faker.fake();
+        let mut comment2: Comment = Faker.fake();
+        comment2.id = comment1.id.clone();
+
+        let create = self.repository.create_comment(comment1).await.unwrap();
+        let update = self.repository.update_comment(comment2).await.unwrap();
+        let get = self.repository.get_comment(&create.id).await.unwrap();
+        let missing = self.repository.update_comment(Faker.fake()).await;
+
+        assert_eq!(Some(update), get);
+        assert_eq!(missing, Err(RepositoryError::NotFound));
+    }
+
+    async fn delete_comment(&self) {
+        let comment: Comment = Faker.fake();
+
+        let create = self.repository.create_comment(comment).await.unwrap();
+        self.repository.delete_comment(&create.id).await.unwrap();
+        let delete = self.repository.get_comment(&create.id).await.unwrap();
+        let again = self.repository.delete_comment(&create.id).await;
+
+        assert_eq!(delete, None);
+        assert_eq!(again, Err(RepositoryError::NotFound));
+    }
 
-        async fn delete_notification(
-            &self,
-            id: &NotificationID,
-        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_comments_by_payment(&self) {
+        let mut comment1: Comment = Faker.fake();
+        let mut comment2: Comment = Faker.fake();
+        let comment3: Comment = Faker.fake();
 
-        async fn get_notification(
-            &self,
-            id: &NotificationID,
-        ) -> Result<Option<Notification>, Box<dyn std::error::Error + Send + Sync>>;
+        let payment: PaymentID = Faker.fake();
+        comment1.payment = payment.clone();
+        comment2.payment = payment.clone();
+        comment1.created_at = Utc::now().trunc_subsecs(3);
+        comment2.created_at = comment1.created_at - Duration::minutes(1);
 
-        async fn get_notifications_by_group(
-            &self,
-            group: &GroupID,
-        ) -> Result<Vec<Notification>, Box<dyn std::error::Error + Send + Sync>>;
+        for comment in [comment1.clone(), comment2.clone(), comment3] {
+            self.repository.create_comment(comment).await.unwrap();
+        }
+
+        let get = self
+            .repository
+            .get_comments_by_payment(&payment)
+            .await
+            .unwrap();
+
+        assert_eq!(vec![comment2, comment1], get);
     }
 
-    #[async_trait]
-    impl PaymentRepository for Repository {
-        async fn create_payment(
-            &self,
-            payment: Payment,
-        ) -> Result<Payment, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_comments_by_payments(&self) {
+        let mut comment1: Comment = Faker.fake();
+        let mut comment2: Comment = Faker.fake();
+        let comment3: Comment = Faker.fake();
 
-        async fn update_payment(
-            &self,
-            payment: Payment,
-        ) -> Result<Payment, Box<dyn std::error::Error + Send + Sync>>;
+        comment1.created_at = Utc::now().trunc_subsecs(3);
+        comment2.created_at = comment1.created_at - Duration::minutes(1);
 
-        async fn delete_payment(
-            &self,
-            id: &PaymentID,
-        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+        for comment in [comment1.clone(), comment2.clone(), comment3] {
+            self.repository.create_comment(comment).await.unwrap();
+        }
 
-        async fn get_payment(
-            &self,
-            id: &PaymentID,
-        ) -> Result<Option<Payment>, Box<dyn std::error::Error + Send + Sync>>;
+        let payments = vec![comment1.payment.clone(), comment2.payment.clone()];
+        let get = self
+            .repository
+            .get_comments_by_payments(&payments)
+            .await
+            .unwrap();
 
-        async fn get_payments_by_group(
-            &self,
-            group: &GroupID,
-        ) -> Result<Vec<Payment>, Box<dyn std::error::Error + Send + Sync>>;
+        assert_eq!(vec![comment2, comment1], get);
     }
 
-    #[async_trait]
-    impl UserRepository for Repository {
-        async fn create_user(
-            &self,
-            user: User,
-        ) -> Result<User, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_comments_by_group(&self) {
+        let mut comment1: Comment = Faker.fake();
+        let mut comment2: Comment = Faker.fake();
+        let comment3: Comment = Faker.fake();
 
-        async fn update_user(
-            &self,
-            user: User,
-        ) -> Result<User, Box<dyn std::error::Error + Send + Sync>>;
+        let group: GroupID = Faker.fake();
+        comment1.group = group.clone();
+        comment2.group = group.clone();
 
-        async fn delete_user(
-            &self,
-            id: &UserID,
-        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+        for comment in [comment1.clone(), comment2.clone(), comment3] {
+            self.repository.create_comment(comment).await.unwrap();
+        }
 
-        async fn get_user(
-            &self,
-            id: &UserID,
-        ) -> Result<Option<User>, Box<dyn std::error::Error + Send + Sync>>;
+        let get = self.repository.get_comments_by_group(&group).await.unwrap();
+
+        assert_eq!(vec![comment1, comment2], get);
     }
 }
 
@@ -253,6 +966,9 @@ impl<R: GroupRepository> GroupRepositoryTester<R> {
         tester.update_group().await;
         tester.delete_group().await;
         tester.get_groups_by_user().await;
+        tester.get_groups_by_ids().await;
+        tester.count_groups().await;
+        tester.get_groups_page_by_user().await;
     }
 
     async fn create_group(&self) {
@@ -268,12 +984,16 @@ impl<R: GroupRepository> GroupRepositoryTester<R> {
         let group1: Group = Faker.fake();
         let mut group2: Group = Faker.fake();
         group2.id = group1.id.clone();
+        group2.version = group1.version;
 
         let create = self.repository.create_group(group1).await.unwrap();
-        let update = self.repository.update_group(group2).await.unwrap();
+        let update = self.repository.update_group(group2.clone()).await.unwrap();
         let get = self.repository.get_group(&create.id).await.unwrap();
+        let stale = self.repository.update_group(group2).await;
 
+        assert_eq!(update.version, create.version + 1);
         assert_eq!(Some(update), get);
+        assert!(matches!(stale, Err(RepositoryError::Conflict(_))));
     }
 
     async fn delete_group(&self) {
@@ -304,6 +1024,151 @@ impl<R: GroupRepository> GroupRepositoryTester<R> {
 
         assert_eq!(groups, vec![group1, group2]);
     }
+
+    async fn count_groups(&self) {
+        let before = self.repository.count_groups().await.unwrap();
+        let _ = self.repository.create_group(Faker.fake()).await.unwrap();
+        let after = self.repository.count_groups().await.unwrap();
+
+        assert_eq!(after, before + 1);
+    }
+
+    async fn get_groups_by_ids(&self) {
+        let group1: Group = Faker.fake();
+        let group2: Group = Faker.fake();
+        let group3: Group = Faker.fake();
+
+        let _ = self.repository.create_group(group1.clone()).await.unwrap();
+        let _ = self.repository.create_group(group2.clone()).await.unwrap();
+        let _ = self.repository.create_group(group3).await.unwrap();
+
+        let ids = vec![group1.id.clone(), group2.id.clone()];
+        let groups = self.repository.get_groups_by_ids(&ids).await.unwrap();
+
+        assert_eq!(groups, vec![group1, group2]);
+    }
+
+    async fn get_groups_page_by_user(&self) {
+        let key: UserID = Faker.fake();
+        let now = chrono::Utc::now().trunc_subsecs(3);
+        let mut items: Vec<Group> = Vec::new();
+        for i in 0..3 {
+            let mut item: Group = Faker.fake();
+            item.created_at = now + Duration::seconds(i);
+            item.participants.push(key.clone());
+            let _ = self.repository.create_group(item.clone()).await.unwrap();
+            items.push(item);
+        }
+
+        let first = PageRequest {
+            first: Some(2),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_groups_page_by_user(&key, &first)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[..2]);
+        assert!(!page.has_previous_page);
+        assert!(page.has_next_page);
+
+        let next = PageRequest {
+            after: Some(items[1].cursor()),
+            first: Some(2),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_groups_page_by_user(&key, &next)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[2..]);
+        assert!(page.has_previous_page);
+        assert!(!page.has_next_page);
+
+        let last = PageRequest {
+            before: Some(items[2].cursor()),
+            last: Some(1),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_groups_page_by_user(&key, &last)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[1..2]);
+        assert!(page.has_previous_page);
+        assert!(page.has_next_page);
+    }
+}
+
+#[cfg(test)]
+pub struct HealthRepositoryTester<R: HealthRepository> {
+    pub repository: R,
+}
+
+#[cfg(test)]
+impl<R: HealthRepository> HealthRepositoryTester<R> {
+    pub async fn test(repository: R) {
+        let tester = Self { repository };
+        tester.ping().await;
+    }
+
+    async fn ping(&self) {
+        self.repository.ping().await.unwrap();
+    }
+}
+
+#[cfg(test)]
+pub struct IdempotencyRepositoryTester<R: IdempotencyRepository> {
+    pub repository: R,
+}
+
+#[cfg(test)]
+impl<R: IdempotencyRepository> IdempotencyRepositoryTester<R> {
+    pub async fn test(repository: R) {
+        let tester = Self { repository };
+        tester.create_idempotency_key().await;
+        tester.delete_idempotency_key().await;
+    }
+
+    async fn create_idempotency_key(&self) {
+        let key: IdempotencyKey = Faker.fake();
+        let mut other: IdempotencyKey = Faker.fake();
+        other.key = key.key.clone();
+
+        let create = self.repository.create_idempotency_key(key.clone()).await;
+        let get = self
+            .repository
+            .get_idempotency_key(&key.user, &key.key)
+            .await
+            .unwrap();
+        let duplicate = self.repository.create_idempotency_key(key.clone()).await;
+        let other = self.repository.create_idempotency_key(other).await;
+
+        assert_eq!(create, Ok(key.clone()));
+        assert_eq!(get, Some(key));
+        assert!(matches!(duplicate, Err(RepositoryError::Conflict(_))));
+        assert!(other.is_ok());
+    }
+
+    async fn delete_idempotency_key(&self) {
+        let key: IdempotencyKey = Faker.fake();
+
+        let create = self.repository.create_idempotency_key(key).await.unwrap();
+        self.repository
+            .delete_idempotency_key(&create.user, &create.key)
+            .await
+            .unwrap();
+        let delete = self
+            .repository
+            .get_idempotency_key(&create.user, &create.key)
+            .await
+            .unwrap();
+
+        assert_eq!(delete, None);
+    }
 }
 
 #[cfg(test)]
@@ -318,6 +1183,7 @@ impl<R: NotificationRepository> NotificationRepositoryTester<R> {
         tester.create_notification().await;
         tester.delete_notification().await;
         tester.get_notifications_by_group().await;
+        tester.get_notifications_page_by_group().await;
     }
 
     async fn create_notification(&self) {
@@ -380,6 +1246,71 @@ impl<R: NotificationRepository> NotificationRepositoryTester<R> {
 
         assert_eq!(vec![notification1, notification2], get);
     }
+
+    async fn get_notifications_page_by_group(&self) {
+        let key: GroupID = Faker.fake();
+        let recipient: UserID = Faker.fake();
+        let now = chrono::Utc::now().trunc_subsecs(3);
+        let mut items: Vec<Notification> = Vec::new();
+        for i in 0..4 {
+            let mut item: Notification = Faker.fake();
+            item.created_at = now + Duration::seconds(i);
+            item.group = key.clone();
+            item.recipients = match i {
+                0 => vec![recipient.clone()],
+                1 => vec![Faker.fake()],
+                _ => vec![],
+            };
+            self.repository
+                .create_notification(item.clone())
+                .await
+                .unwrap();
+            if i != 1 {
+                items.push(item);
+            }
+        }
+
+        let first = PageRequest {
+            first: Some(2),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_notifications_page_by_group(&key, &recipient, &first)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[..2]);
+        assert!(!page.has_previous_page);
+        assert!(page.has_next_page);
+
+        let next = PageRequest {
+            after: Some(items[1].cursor()),
+            first: Some(2),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_notifications_page_by_group(&key, &recipient, &next)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[2..]);
+        assert!(page.has_previous_page);
+        assert!(!page.has_next_page);
+
+        let last = PageRequest {
+            before: Some(items[2].cursor()),
+            last: Some(1),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_notifications_page_by_group(&key, &recipient, &last)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[1..2]);
+        assert!(page.has_previous_page);
+        assert!(page.has_next_page);
+    }
 }
 
 #[cfg(test)]
@@ -395,6 +1326,10 @@ impl<R: PaymentRepository> PaymentRepositoryTester<R> {
         tester.update_payment().await;
         tester.delete_payment().await;
         tester.get_payments_by_group().await;
+        tester.get_payments_by_ids().await;
+        tester.count_payments().await;
+        tester.get_payments_page_by_group().await;
+        tester.find_payments().await;
     }
 
     async fn create_payment(&self) {
@@ -410,12 +1345,20 @@ impl<R: PaymentRepository> PaymentRepositoryTester<R> {
         let payment1: Payment = Faker.fake();
         let mut payment2: Payment = Faker.fake();
         payment2.id = payment1.id.clone();
+        payment2.version = payment1.version;
 
         let create = self.repository.create_payment(payment1).await.unwrap();
-        let update = self.repository.update_payment(payment2).await.unwrap();
+        let update = self
+            .repository
+            .update_payment(payment2.clone())
+            .await
+            .unwrap();
         let get = self.repository.get_payment(&create.id).await.unwrap();
+        let stale = self.repository.update_payment(payment2).await;
 
+        assert_eq!(update.version, create.version + 1);
         assert_eq!(Some(update), get);
+        assert!(matches!(stale, Err(RepositoryError::Conflict(_))));
     }
 
     async fn delete_payment(&self) {
@@ -454,6 +1397,296 @@ impl<R: PaymentRepository> PaymentRepositoryTester<R> {
 
         assert_eq!(vec![payment1, payment2], get);
     }
+
+    async fn count_payments(&self) {
+        let before = self.repository.count_payments().await.unwrap();
+        let _ = self.repository.create_payment(Faker.fake()).await.unwrap();
+        let after = self.repository.count_payments().await.unwrap();
+
+        assert_eq!(after, before + 1);
+    }
+
+    async fn get_payments_by_ids(&self) {
+        let payment1: Payment = Faker.fake();
+        let payment2: Payment = Faker.fake();
+        let payment3: Payment = Faker.fake();
+
+        self.repository
+            .create_payment(payment1.clone())
+            .await
+            .unwrap();
+        self.repository
+            .create_payment(payment2.clone())
+            .await
+            .unwrap();
+        self.repository
+            .create_payment(payment3.clone())
+            .await
+            .unwrap();
+
+        let ids = vec![payment1.id.clone(), payment2.id.clone()];
+        let get = self.repository.get_payments_by_ids(&ids).await.unwrap();
+
+        assert_eq!(vec![payment1, payment2], get);
+    }
+
+    async fn get_payments_page_by_group(&self) {
+        let key: GroupID = Faker.fake();
+        let now = chrono::Utc::now().trunc_subsecs(3);
+        let mut items: Vec<Payment> = Vec::new();
+        for i in 0..3 {
+            let mut item: Payment = Faker.fake();
+            item.created_at = now + Duration::seconds(i);
+            item.group = key.clone();
+            self.repository.create_payment(item.clone()).await.unwrap();
+            items.push(item);
+        }
+
+        let first = PageRequest {
+            first: Some(2),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_payments_page_by_group(&key, &first)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[..2]);
+        assert!(!page.has_previous_page);
+        assert!(page.has_next_page);
+
+        let next = PageRequest {
+            after: Some(items[1].cursor()),
+            first: Some(2),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_payments_page_by_group(&key, &next)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[2..]);
+        assert!(page.has_previous_page);
+        assert!(!page.has_next_page);
+
+        let last = PageRequest {
+            before: Some(items[2].cursor()),
+            last: Some(1),
+            ..Default::default()
+        };
+        let page = self
+            .repository
+            .get_payments_page_by_group(&key, &last)
+            .await
+            .unwrap();
+        assert_eq!(page.items, items[1..2]);
+        assert!(page.has_previous_page);
+        assert!(page.has_next_page);
+    }
+
+    async fn find_payments(&self) {
+        let group: GroupID = Faker.fake();
+        let creditor: UserID = Faker.fake();
+
+        let mut payment1: Payment = Faker.fake();
+        payment1.group = group.clone();
+        payment1.title = "Dinner at Sushi Zen".to_string();
+        payment1.creditors = vec![Amount {
+            user: creditor.clone(),
+            amount: 6000,
+        }];
+        payment1.tags = vec!["food".to_string(), "trip".to_string()];
+        payment1.category = Some("food".to_string());
+        payment1.occurred_at = Some(payment1.created_at - Duration::days(3));
+
+        let mut payment2 = payment1.clone();
+        payment2.id = Faker.fake();
+        payment2.creditors[0].amount = 3000;
+
+        let mut payment3 = payment1.clone();
+        payment3.id = Faker.fake();
+        payment3.title = "Taxi".to_string();
+
+        let mut payment4 = payment1.clone();
+        payment4.id = Faker.fake();
+        payment4.category = Some("lodging".to_string());
+
+        let mut payment5 = payment1.clone();
+        payment5.id = Faker.fake();
+        payment5.occurred_at = None;
+
+        for payment in [&payment1, &payment2, &payment3, &payment4, &payment5] {
+            self.repository
+                .create_payment(payment.clone())
+                .await
+                .unwrap();
+        }
+
+        let filter = PaymentFilter {
+            groups: vec![group],
+            title: Some("sushi".to_string()),
+            creditor: Some(creditor),
+            min_amount: Some(5000),
+            tags: vec!["food".to_string()],
+            category: Some("food".to_string()),
+            occurred_before: Some(payment1.created_at - Duration::days(1)),
+            ..Default::default()
+        };
+        let get = self.repository.find_payments(&filter).await.unwrap();
+
+        assert_eq!(vec![payment1], get);
+    }
+}
+
+#[cfg(test)]
+pub struct RecurringPaymentRepositoryTester<R: RecurringPaymentRepository> {
+    pub repository: R,
+}
+
+#[cfg(test)]
+impl<R: RecurringPaymentRepository> RecurringPaymentRepositoryTester<R> {
+    pub async fn test(repository: R) {
+        let tester = Self { repository };
+        tester.create_recurring_payment().await;
+        tester.update_recurring_payment().await;
+        tester.delete_recurring_payment().await;
+        tester.get_recurring_payments_by_group().await;
+        tester.get_due_recurring_payments().await;
+    }
+
+    async fn create_recurring_payment(&self) {
+        let recurring_payment: RecurringPayment = Faker.fake();
+
+        let create = self
+            .repository
+            .create_recurring_payment(recurring_payment)
+            .await
+            .unwrap();
+        let get = self
+            .repository
+            .get_recurring_payment(&create.id)
+            .await
+            .unwrap();
+
+        assert_eq!(Some(create), get);
+    }
+
+    async fn update_recurring_payment(&self) {
+        let recurring_payment1: RecurringPayment = Faker.fake();
+        let mut recurring_payment2: RecurringPayment = Faker.fake();
+        recurring_payment2.id = recurring_payment1.id.clone();
+        recurring_payment2.version = recurring_payment1.version;
+
+        let create = self
+            .repository
+            .create_recurring_payment(recurring_payment1)
+            .await
+            .unwrap();
+        let update = self
+            .repository
+            .update_recurring_payment(recurring_payment2.clone())
+            .await
+            .unwrap();
+        let get = self
+            .repository
+            .get_recurring_payment(&create.id)
+            .await
+            .unwrap();
+        let stale = self
+            .repository
+            .update_recurring_payment(recurring_payment2)
+            .await;
+
+        assert_eq!(update.version, create.version + 1);
+        assert_eq!(Some(update), get);
+        assert!(matches!(stale, Err(RepositoryError::Conflict(_))));
+    }
+
+    async fn delete_recurring_payment(&self) {
+        let recurring_payment: RecurringPayment = Faker.fake();
+
+        let create = self
+            .repository
+            .create_recurring_payment(recurring_payment)
+            .await
+            .unwrap();
+        self.repository
+            .delete_recurring_payment(&create.id)
+            .await
+            .unwrap();
+        let delete = self
+            .repository
+            .get_recurring_payment(&create.id)
+            .await
+            .unwrap();
+        let again = self.repository.delete_recurring_payment(&create.id).await;
+
+        assert_eq!(delete, None);
+        assert_eq!(again, Err(RepositoryError::NotFound));
+    }
+
+    async fn get_recurring_payments_by_group(&self) {
+        let mut recurring_payment1: RecurringPayment = Faker.fake();
+        let mut recurring_payment2: RecurringPayment = Faker.fake();
+        let recurring_payment3: RecurringPayment = Faker.fake();
+
+        let group: GroupID = Faker.fake();
+        recurring_payment1.group = group.clone();
+        recurring_payment2.group = group.clone();
+
+        for recurring_payment in [
+            recurring_payment1.clone(),
+            recurring_payment2.clone(),
+            recurring_payment3,
+        ] {
+            self.repository
+                .create_recurring_payment(recurring_payment)
+                .await
+                .unwrap();
+        }
+
+        let get = self
+            .repository
+            .get_recurring_payments_by_group(&group)
+            .await
+            .unwrap();
+
+        assert_eq!(vec![recurring_payment1, recurring_payment2], get);
+    }
+
+    async fn get_due_recurring_payments(&self) {
+        let now = Utc::now().trunc_subsecs(3);
+        let group: GroupID = Faker.fake();
+        let mut due: RecurringPayment = Faker.fake();
+        due.group = group.clone();
+        due.paused = false;
+        due.next_at = Some(now - Duration::days(1));
+        let mut paused = due.clone();
+        paused.id = Faker.fake();
+        paused.paused = true;
+        let mut later = due.clone();
+        later.id = Faker.fake();
+        later.next_at = Some(now + Duration::days(1));
+        let mut ended = due.clone();
+        ended.id = Faker.fake();
+        ended.next_at = None;
+
+        for recurring_payment in [due.clone(), paused, later, ended] {
+            self.repository
+                .create_recurring_payment(recurring_payment)
+                .await
+                .unwrap();
+        }
+
+        let get = self
+            .repository
+            .get_due_recurring_payments(&now)
+            .await
+            .unwrap();
+
+        assert!(get.contains(&due));
+        assert!(get.iter().all(|r| r.group != group || r.id == due.id));
+    }
 }
 
 #[cfg(test)]
@@ -468,6 +1701,8 @@ impl<R: UserRepository> UserRepositoryTester<R> {
         tester.create_user().await;
         tester.update_user().await;
         tester.delete_user().await;
+        tester.get_users_by_ids().await;
+        tester.count_users().await;
     }
 
     async fn create_user(&self) {
@@ -475,8 +1710,10 @@ impl<R: UserRepository> UserRepositoryTester<R> {
 
         let create = self.repository.create_user(user).await.unwrap();
         let get = self.repository.get_user(&create.id).await.unwrap();
+        let duplicate = self.repository.create_user(create.clone()).await;
 
         assert_eq!(Some(create), get);
+        assert!(matches!(duplicate, Err(RepositoryError::Conflict(_))));
     }
 
     async fn update_user(&self) {
@@ -497,7 +1734,32 @@ impl<R: UserRepository> UserRepositoryTester<R> {
         let create = self.repository.create_user(user).await.unwrap();
         self.repository.delete_user(&create.id).await.unwrap();
         let delete = self.repository.get_user(&create.id).await.unwrap();
+        let again = self.repository.delete_user(&create.id).await;
 
         assert_eq!(delete, None);
+        assert_eq!(again, Err(RepositoryError::NotFound));
+    }
+
+    async fn count_users(&self) {
+        let before = self.repository.count_users().await.unwrap();
+        let _ = self.repository.create_user(Faker.fake()).await.unwrap();
+        let after = self.repository.count_users().await.unwrap();
+
+        assert_eq!(after, before + 1);
+    }
+
+    async fn get_users_by_ids(&self) {
+        let user1: User = Faker.fake();
+        let user2: User = Faker.fake();
+        let user3: User = Faker.fake();
+
+        let _ = self.repository.create_user(user1.clone()).await.unwrap();
+        let _ = self.repository.create_user(user2.clone()).await.unwrap();
+        let _ = self.repository.create_user(user3).await.unwrap();
+
+        let ids = vec![user1.id.clone(), user2.id.clone()];
+        let get = self.repository.get_users_by_ids(&ids).await.unwrap();
+
+        assert_eq!(vec![user1, user2], get);
     }
 }
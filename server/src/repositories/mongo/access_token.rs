@@ -0,0 +1,127 @@
+use crate::{
+    entities::{AccessToken, AccessTokenID, UserID},
+    repositories::{
+        AccessTokenRepository, MongoRepository, MongoRepositoryError, RepositoryError,
+        MONGO_COLLECTION_ACCESS_TOKENS,
+    },
+};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson},
+    options::IndexOptions,
+    Collection, IndexModel,
+};
+use tracing::instrument;
+
+impl From<AccessTokenID> for Bson {
+    fn from(value: AccessTokenID) -> Self {
+        Bson::String(value.0.to_string())
+    }
+}
+
+impl MongoRepository {
+    pub async fn create_access_token_index(&self) -> Result<(), MongoRepositoryError> {
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build();
+
+            self.database
+                .collection::<AccessToken>(MONGO_COLLECTION_ACCESS_TOKENS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"hash": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build();
+
+            self.database
+                .collection::<AccessToken>(MONGO_COLLECTION_ACCESS_TOKENS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder().keys(doc! {"user": 1}).build();
+
+            self.database
+                .collection::<AccessToken>(MONGO_COLLECTION_ACCESS_TOKENS)
+                .create_index(model, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AccessTokenRepository for MongoRepository {
+    #[instrument(skip(self, access_token), fields(id = ?access_token.id), err(level = "debug"))]
+    async fn create_access_token(
+        &self,
+        access_token: AccessToken,
+    ) -> Result<AccessToken, RepositoryError> {
+        let access_tokens: Collection<AccessToken> =
+            self.database.collection(MONGO_COLLECTION_ACCESS_TOKENS);
+        let _ = access_tokens.insert_one(&access_token, None).await?;
+        Ok(access_token)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_access_token(&self, id: &AccessTokenID) -> Result<(), RepositoryError> {
+        let access_tokens: Collection<AccessToken> =
+            self.database.collection(MONGO_COLLECTION_ACCESS_TOKENS);
+        let filter = doc! { "id": id };
+        let _ = access_tokens.delete_one(filter, None).await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_access_token(
+        &self,
+        id: &AccessTokenID,
+    ) -> Result<Option<AccessToken>, RepositoryError> {
+        let access_tokens: Collection<AccessToken> =
+            self.database.collection(MONGO_COLLECTION_ACCESS_TOKENS);
+
+        let filter = doc! { "id": id };
+        let result = access_tokens.find_one(filter, None).await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self, hash), err(level = "debug"))]
+    async fn get_access_token_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Option<AccessToken>, RepositoryError> {
+        let access_tokens: Collection<AccessToken> =
+            self.database.collection(MONGO_COLLECTION_ACCESS_TOKENS);
+
+        let filter = doc! { "hash": hash };
+        let result = access_tokens.find_one(filter, None).await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_access_tokens_by_user(
+        &self,
+        user: &UserID,
+    ) -> Result<Vec<AccessToken>, RepositoryError> {
+        let access_tokens: Collection<AccessToken> =
+            self.database.collection(MONGO_COLLECTION_ACCESS_TOKENS);
+
+        let filter = doc! { "user": user };
+        let result = access_tokens
+            .find(filter, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(result)
+    }
+}
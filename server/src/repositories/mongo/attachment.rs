@@ -0,0 +1,139 @@
+use crate::{
+    entities::{Attachment, AttachmentID, GroupID, PaymentID},
+    repositories::{
+        AttachmentRepository, MongoRepository, MongoRepositoryError, RepositoryError,
+        MONGO_COLLECTION_ATTACHMENTS,
+    },
+};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson},
+    options::IndexOptions,
+    Collection, IndexModel,
+};
+use tracing::instrument;
+
+impl From<AttachmentID> for Bson {
+    fn from(value: AttachmentID) -> Self {
+        Bson::String(value.0.to_string())
+    }
+}
+
+impl MongoRepository {
+    pub async fn create_attachment_index(&self) -> Result<(), MongoRepositoryError> {
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build();
+
+            self.database
+                .collection::<Attachment>(MONGO_COLLECTION_ATTACHMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder().keys(doc! {"payment": 1}).build();
+
+            self.database
+                .collection::<Attachment>(MONGO_COLLECTION_ATTACHMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder().keys(doc! {"group": 1}).build();
+
+            self.database
+                .collection::<Attachment>(MONGO_COLLECTION_ATTACHMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for MongoRepository {
+    #[instrument(skip(self, attachment), fields(id = ?attachment.id), err(level = "debug"))]
+    async fn create_attachment(
+        &self,
+        attachment: Attachment,
+    ) -> Result<Attachment, RepositoryError> {
+        let attachments: Collection<Attachment> =
+            self.database.collection(MONGO_COLLECTION_ATTACHMENTS);
+        let _ = attachments.insert_one(&attachment, None).await?;
+        Ok(attachment)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_attachment(&self, id: &AttachmentID) -> Result<(), RepositoryError> {
+        let attachments: Collection<Attachment> =
+            self.database.collection(MONGO_COLLECTION_ATTACHMENTS);
+
+        let filter = doc! { "id": id };
+        let result = attachments.delete_one(filter, None).await?;
+
+        if result.deleted_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_attachment(
+        &self,
+        id: &AttachmentID,
+    ) -> Result<Option<Attachment>, RepositoryError> {
+        let attachments: Collection<Attachment> =
+            self.database.collection(MONGO_COLLECTION_ATTACHMENTS);
+
+        let filter = doc! { "id": id };
+        let result = attachments.find_one(filter, None).await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_attachments_by_payment(
+        &self,
+        payment: &PaymentID,
+    ) -> Result<Vec<Attachment>, RepositoryError> {
+        let attachments: Collection<Attachment> =
+            self.database.collection(MONGO_COLLECTION_ATTACHMENTS);
+
+        let filter = doc! { "payment": payment };
+        let result = attachments.find(filter, None).await?.try_collect().await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_attachments_by_payments(
+        &self,
+        payments: &[PaymentID],
+    ) -> Result<Vec<Attachment>, RepositoryError> {
+        let attachments: Collection<Attachment> =
+            self.database.collection(MONGO_COLLECTION_ATTACHMENTS);
+
+        let filter = doc! { "payment": { "$in": payments.to_vec() } };
+        let result = attachments.find(filter, None).await?.try_collect().await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_attachments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<Attachment>, RepositoryError> {
+        let attachments: Collection<Attachment> =
+            self.database.collection(MONGO_COLLECTION_ATTACHMENTS);
+
+        let filter = doc! { "group": group };
+        let result = attachments.find(filter, None).await?.try_collect().await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    entities::{Comment, CommentID, GroupID, PaymentID},
+    repositories::{
+        CommentRepository, MongoRepository, MongoRepositoryError, RepositoryError,
+        MONGO_COLLECTION_COMMENTS,
+    },
+};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson},
+    options::{FindOptions, IndexOptions},
+    Collection, IndexModel,
+};
+use tracing::instrument;
+
+impl From<CommentID> for Bson {
+    fn from(value: CommentID) -> Self {
+        Bson::String(value.0.to_string())
+    }
+}
+
+impl MongoRepository {
+    pub async fn create_comment_index(&self) -> Result<(), MongoRepositoryError> {
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build();
+
+            self.database
+                .collection::<Comment>(MONGO_COLLECTION_COMMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"payment": 1, "created_at": 1, "id": 1})
+                .build();
+
+            self.database
+                .collection::<Comment>(MONGO_COLLECTION_COMMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder().keys(doc! {"group": 1}).build();
+
+            self.database
+                .collection::<Comment>(MONGO_COLLECTION_COMMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommentRepository for MongoRepository {
+    #[instrument(skip(self, comment), fields(id = ?comment.id), err(level = "debug"))]
+    async fn create_comment(&self, comment: Comment) -> Result<Comment, RepositoryError> {
+        let comments: Collection<Comment> = self.database.collection(MONGO_COLLECTION_COMMENTS);
+        let _ = comments.insert_one(&comment, None).await?;
+        Ok(comment)
+    }
+
+    #[instrument(skip(self, comment), fields(id = ?comment.id), err(level = "debug"))]
+    async fn update_comment(&self, comment: Comment) -> Result<Comment, RepositoryError> {
+        let comments: Collection<Comment> = self.database.collection(MONGO_COLLECTION_COMMENTS);
+        let filter = doc! { "id": &comment.id };
+        let result = comments.replace_one(filter, &comment, None).await?;
+        if result.matched_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(comment)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_comment(&self, id: &CommentID) -> Result<(), RepositoryError> {
+        let comments: Collection<Comment> = self.database.collection(MONGO_COLLECTION_COMMENTS);
+
+        let filter = doc! { "id": id };
+        let result = comments.delete_one(filter, None).await?;
+
+        if result.deleted_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_comment(&self, id: &CommentID) -> Result<Option<Comment>, RepositoryError> {
+        let comments: Collection<Comment> = self.database.collection(MONGO_COLLECTION_COMMENTS);
+
+        let filter = doc! { "id": id };
+        let result = comments.find_one(filter, None).await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_comments_by_payment(
+        &self,
+        payment: &PaymentID,
+    ) -> Result<Vec<Comment>, RepositoryError> {
+        let comments: Collection<Comment> = self.database.collection(MONGO_COLLECTION_COMMENTS);
+
+        let filter = doc! { "payment": payment };
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": 1, "id": 1 })
+            .build();
+        let result = comments.find(filter, options).await?.try_collect().await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_comments_by_payments(
+        &self,
+        payments: &[PaymentID],
+    ) -> Result<Vec<Comment>, RepositoryError> {
+        let comments: Collection<Comment> = self.database.collection(MONGO_COLLECTION_COMMENTS);
+
+        let filter = doc! { "payment": { "$in": payments.to_vec() } };
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": 1, "id": 1 })
+            .build();
+        let result = comments.find(filter, options).await?.try_collect().await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_comments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<Comment>, RepositoryError> {
+        let comments: Collection<Comment> = self.database.collection(MONGO_COLLECTION_COMMENTS);
+
+        let filter = doc! { "group": group };
+        let result = comments.find(filter, None).await?.try_collect().await?;
+
+        Ok(result)
+    }
+}
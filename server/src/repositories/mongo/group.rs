@@ -1,7 +1,8 @@
 use crate::{
-    entities::{Group, GroupID, UserID},
+    entities::{Group, GroupID, Page, PageRequest, UserID},
     repositories::{
-        GroupRepository, MongoRepository, MongoRepositoryError, MONGO_COLLECTION_GROUPS,
+        GroupRepository, MongoRepository, MongoRepositoryError, RepositoryError,
+        MONGO_COLLECTION_GROUPS,
     },
 };
 use async_trait::async_trait;
@@ -11,6 +12,7 @@ use mongodb::{
     options::IndexOptions,
     Collection, IndexModel,
 };
+use tracing::instrument;
 
 impl From<GroupID> for Bson {
     fn from(value: GroupID) -> Self {
@@ -30,50 +32,58 @@ impl MongoRepository {
                 .collection::<Group>(MONGO_COLLECTION_GROUPS)
                 .create_index(model, None)
                 .await?;
+        }
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"participants": 1, "created_at": 1, "id": 1})
+                .build();
 
-            Ok(())
+            self.database
+                .collection::<Group>(MONGO_COLLECTION_GROUPS)
+                .create_index(model, None)
+                .await?;
         }
+
+        Ok(())
     }
 }
 
 #[async_trait]
 impl GroupRepository for MongoRepository {
-    async fn create_group(
-        &self,
-        group: Group,
-    ) -> Result<Group, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self, group), fields(id = ?group.id), err(level = "debug"))]
+    async fn create_group(&self, group: Group) -> Result<Group, RepositoryError> {
         let groups: Collection<Group> = self.database.collection(MONGO_COLLECTION_GROUPS);
         let _ = groups.insert_one(&group, None).await?;
         Ok(group)
     }
 
-    async fn update_group(
-        &self,
-        group: Group,
-    ) -> Result<Group, Box<dyn std::error::Error + Send + Sync>> {
-        let groups: Collection<Group> = self.database.collection(MONGO_COLLECTION_GROUPS);
-        let filter = doc! { "id": &group.id };
-        let _ = groups.replace_one(filter, &group, None).await?;
+    #[instrument(skip(self, group), fields(id = ?group.id), err(level = "debug"))]
+    async fn update_group(&self, group: Group) -> Result<Group, RepositoryError> {
+        let expected = group.version;
+        let group = Group {
+            version: expected + 1,
+            ..group
+        };
+        self.replace_versioned(MONGO_COLLECTION_GROUPS, group.id.clone(), expected, &group)
+            .await?;
         Ok(group)
     }
 
-    async fn delete_group(
-        &self,
-        id: &GroupID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_group(&self, id: &GroupID) -> Result<(), RepositoryError> {
         let groups: Collection<Group> = self.database.collection(MONGO_COLLECTION_GROUPS);
 
         let filter = doc! { "id": id };
         let result = groups.delete_one(filter, None).await?;
 
-        assert!(result.deleted_count == 1);
+        if result.deleted_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
         Ok(())
     }
 
-    async fn get_group(
-        &self,
-        id: &GroupID,
-    ) -> Result<Option<Group>, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_group(&self, id: &GroupID) -> Result<Option<Group>, RepositoryError> {
         let groups: Collection<Group> = self.database.collection(MONGO_COLLECTION_GROUPS);
 
         let filter = doc! { "id": id };
@@ -82,10 +92,8 @@ impl GroupRepository for MongoRepository {
         Ok(result)
     }
 
-    async fn get_groups_by_user(
-        &self,
-        id: &UserID,
-    ) -> Result<Vec<Group>, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_groups_by_user(&self, id: &UserID) -> Result<Vec<Group>, RepositoryError> {
         let groups: Collection<Group> = self.database.collection(MONGO_COLLECTION_GROUPS);
 
         let filter = doc! { "participants": id };
@@ -93,4 +101,31 @@ impl GroupRepository for MongoRepository {
 
         Ok(result)
     }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_groups_by_ids(&self, ids: &[GroupID]) -> Result<Vec<Group>, RepositoryError> {
+        let groups: Collection<Group> = self.database.collection(MONGO_COLLECTION_GROUPS);
+
+        let filter = doc! { "id": { "$in": ids.to_vec() } };
+        let result = groups.find(filter, None).await?.try_collect().await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn count_groups(&self) -> Result<u64, RepositoryError> {
+        let groups: Collection<Group> = self.database.collection(MONGO_COLLECTION_GROUPS);
+        let count = groups.count_documents(None, None).await?;
+        Ok(count)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_groups_page_by_user(
+        &self,
+        id: &UserID,
+        page: &PageRequest,
+    ) -> Result<Page<Group>, RepositoryError> {
+        let filter = doc! { "participants": id };
+        self.find_page(MONGO_COLLECTION_GROUPS, filter, page).await
+    }
 }
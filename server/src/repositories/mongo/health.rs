@@ -0,0 +1,13 @@
+use crate::repositories::{HealthRepository, MongoRepository, RepositoryError};
+use async_trait::async_trait;
+use mongodb::bson::doc;
+use tracing::instrument;
+
+#[async_trait]
+impl HealthRepository for MongoRepository {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn ping(&self) -> Result<(), RepositoryError> {
+        let _ = self.database.run_command(doc! { "ping": 1 }, None).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    entities::{IdempotencyKey, UserID},
+    repositories::{
+        IdempotencyRepository, MongoRepository, MongoRepositoryError, RepositoryError,
+        MONGO_COLLECTION_IDEMPOTENCY_KEYS,
+    },
+};
+use async_trait::async_trait;
+use mongodb::{
+    bson::{doc, DateTime},
+    options::IndexOptions,
+    Collection, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::instrument;
+
+/// Stored form of [`IdempotencyKey`]. The expiry is repeated as a BSON date
+/// so that a TTL index can remove keys once their window has passed.
+#[derive(Debug, Serialize, Deserialize)]
+struct IdempotencyKeyDocument {
+    #[serde(flatten)]
+    key: IdempotencyKey,
+    expire_at: DateTime,
+}
+
+impl From<IdempotencyKey> for IdempotencyKeyDocument {
+    fn from(key: IdempotencyKey) -> Self {
+        let expire_at = DateTime::from_millis(key.expires_at.timestamp_millis());
+        Self { key, expire_at }
+    }
+}
+
+impl MongoRepository {
+    pub async fn create_idempotency_key_index(&self) -> Result<(), MongoRepositoryError> {
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"user": 1, "key": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build();
+
+            self.database
+                .collection::<IdempotencyKeyDocument>(MONGO_COLLECTION_IDEMPOTENCY_KEYS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"expire_at": 1})
+                .options(IndexOptions::builder().expire_after(Duration::ZERO).build())
+                .build();
+
+            self.database
+                .collection::<IdempotencyKeyDocument>(MONGO_COLLECTION_IDEMPOTENCY_KEYS)
+                .create_index(model, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for MongoRepository {
+    #[instrument(skip(self, key), fields(user = ?key.user), err(level = "debug"))]
+    async fn create_idempotency_key(
+        &self,
+        key: IdempotencyKey,
+    ) -> Result<IdempotencyKey, RepositoryError> {
+        let keys: Collection<IdempotencyKeyDocument> =
+            self.database.collection(MONGO_COLLECTION_IDEMPOTENCY_KEYS);
+        let document = IdempotencyKeyDocument::from(key);
+        let _ = keys.insert_one(&document, None).await?;
+        Ok(document.key)
+    }
+
+    #[instrument(skip(self, key), err(level = "debug"))]
+    async fn delete_idempotency_key(
+        &self,
+        user: &UserID,
+        key: &str,
+    ) -> Result<(), RepositoryError> {
+        let keys: Collection<IdempotencyKeyDocument> =
+            self.database.collection(MONGO_COLLECTION_IDEMPOTENCY_KEYS);
+
+        let filter = doc! { "user": user, "key": key };
+        let result = keys.delete_one(filter, None).await?;
+
+        if result.deleted_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self, key), err(level = "debug"))]
+    async fn get_idempotency_key(
+        &self,
+        user: &UserID,
+        key: &str,
+    ) -> Result<Option<IdempotencyKey>, RepositoryError> {
+        let keys: Collection<IdempotencyKeyDocument> =
+            self.database.collection(MONGO_COLLECTION_IDEMPOTENCY_KEYS);
+
+        let filter = doc! { "user": user, "key": key };
+        let result = keys.find_one(filter, None).await?;
+
+        Ok(result.map(|document| document.key))
+    }
+}
@@ -1,14 +1,36 @@
+mod access_token;
+mod attachment;
+mod comment;
 mod group;
+mod health;
+mod idempotency;
 mod notification;
 mod payment;
+mod recurring_payment;
 mod user;
 
-use mongodb::{Client, Database};
+use crate::{
+    entities::{Cursor, Page, PageRequest},
+    repositories::RepositoryError,
+};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{self, doc, Bson, Document},
+    error::{ErrorKind, WriteFailure},
+    options::FindOptions,
+    Client, Database,
+};
+use serde::{de::DeserializeOwned, Serialize};
 use thiserror::Error;
 
+pub const MONGO_COLLECTION_ACCESS_TOKENS: &str = "access_tokens";
+pub const MONGO_COLLECTION_ATTACHMENTS: &str = "attachments";
+pub const MONGO_COLLECTION_COMMENTS: &str = "comments";
 pub const MONGO_COLLECTION_GROUPS: &str = "groups";
+pub const MONGO_COLLECTION_IDEMPOTENCY_KEYS: &str = "idempotency_keys";
 pub const MONGO_COLLECTION_NOTIFICATIONS: &str = "notifications";
 pub const MONGO_COLLECTION_PAYMENTS: &str = "payments";
+pub const MONGO_COLLECTION_RECURRING_PAYMENTS: &str = "recurring_payments";
 pub const MONGO_COLLECTION_USERS: &str = "users";
 
 #[derive(Debug)]
@@ -22,6 +44,47 @@ pub enum MongoRepositoryError {
     Mongo(#[from] mongodb::error::Error),
 }
 
+/// Server error code for a unique index violation.
+const DUPLICATE_KEY: i32 = 11000;
+
+impl From<mongodb::error::Error> for RepositoryError {
+    fn from(err: mongodb::error::Error) -> Self {
+        let message = err.to_string();
+        match err.kind.as_ref() {
+            ErrorKind::Write(WriteFailure::WriteError(e)) if e.code == DUPLICATE_KEY => {
+                RepositoryError::Conflict(message)
+            }
+            ErrorKind::Command(e) if e.code == DUPLICATE_KEY => RepositoryError::Conflict(message),
+            ErrorKind::BulkWrite(e)
+                if e.write_errors
+                    .iter()
+                    .flatten()
+                    .any(|e| e.code == DUPLICATE_KEY) =>
+            {
+                RepositoryError::Conflict(message)
+            }
+            ErrorKind::Io(_)
+            | ErrorKind::DnsResolve { .. }
+            | ErrorKind::ConnectionPoolCleared { .. }
+            | ErrorKind::ServerSelection { .. }
+            | ErrorKind::Shutdown => RepositoryError::Unavailable(message),
+            ErrorKind::BsonDeserialization(_) | ErrorKind::BsonSerialization(_) => {
+                RepositoryError::Serialization(message)
+            }
+            _ if err.contains_label(mongodb::error::RETRYABLE_WRITE_ERROR) => {
+                RepositoryError::Unavailable(message)
+            }
+            _ => RepositoryError::Other(message),
+        }
+    }
+}
+
+impl From<bson::ser::Error> for RepositoryError {
+    fn from(err: bson::ser::Error) -> Self {
+        RepositoryError::Serialization(err.to_string())
+    }
+}
+
 #[derive(Debug)]
 pub struct MongoRepositoryConfig<'a> {
     pub uri: &'a str,
@@ -34,27 +97,187 @@ impl MongoRepository {
         let database = client.database(config.database);
         let mongo = MongoRepository { database };
         mongo.create_index().await?;
+        mongo.migrate_dates().await?;
         Ok(mongo)
     }
 
+    /// Converts dates stored as RFC 3339 strings by earlier versions into
+    /// BSON datetimes, which compare in time order. Values that do not parse
+    /// are left as they are.
+    pub async fn migrate_dates(&self) -> Result<(), MongoRepositoryError> {
+        for (collection, field) in [
+            (MONGO_COLLECTION_COMMENTS, "created_at"),
+            (MONGO_COLLECTION_GROUPS, "created_at"),
+            (MONGO_COLLECTION_NOTIFICATIONS, "created_at"),
+            (MONGO_COLLECTION_PAYMENTS, "created_at"),
+            (MONGO_COLLECTION_PAYMENTS, "occurred_at"),
+            (MONGO_COLLECTION_RECURRING_PAYMENTS, "next_at"),
+        ] {
+            let value = format!("${field}");
+            self.database
+                .collection::<Document>(collection)
+                .update_many(
+                    doc! { field: { "$type": "string" } },
+                    vec![doc! { "$set": { field: {
+                        "$convert": { "input": &value, "to": "date", "onError": &value }
+                    } } }],
+                    None,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn create_index(&self) -> Result<(), MongoRepositoryError> {
+        self.create_access_token_index().await?;
+        self.create_attachment_index().await?;
+        self.create_comment_index().await?;
         self.create_group_index().await?;
+        self.create_idempotency_key_index().await?;
         self.create_notification_index().await?;
         self.create_payment_index().await?;
+        self.create_recurring_payment_index().await?;
         self.create_user_index().await?;
 
         Ok(())
     }
+
+    /// Reads one page of `collection` in `created_at` + id order. Only one
+    /// entity past the requested size is fetched to detect further pages.
+    pub async fn find_page<T>(
+        &self,
+        collection: &str,
+        filter: Document,
+        page: &PageRequest,
+    ) -> Result<Page<T>, RepositoryError>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let mut conditions = vec![filter];
+        if let Some(after) = &page.after {
+            conditions.push(cursor_condition(after, "$gt"));
+        }
+        if let Some(before) = &page.before {
+            conditions.push(cursor_condition(before, "$lt"));
+        }
+
+        let backward = page.first.is_none();
+        let direction = if backward { -1 } else { 1 };
+        let limit = page.first.or(page.last).map(|size| size as i64 + 1);
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": direction, "id": direction })
+            .limit(limit)
+            .build();
+
+        let mut items: Vec<T> = self
+            .database
+            .collection::<T>(collection)
+            .find(doc! { "$and": conditions }, options)
+            .await?
+            .try_collect()
+            .await?;
+        if backward {
+            items.reverse();
+        }
+
+        Ok(Page::from_window(items, page))
+    }
+
+    /// Replaces the document with `id` by `entity` only if it is still at
+    /// version `expected`. Documents written before versioning count as 0.
+    pub async fn replace_versioned<T>(
+        &self,
+        collection: &str,
+        id: impl Into<Bson>,
+        expected: i32,
+        entity: &T,
+    ) -> Result<(), RepositoryError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        let collection = self.database.collection::<T>(collection);
+        let id = id.into();
+        let version = if expected == 0 {
+            doc! { "$in": [0, Bson::Null] }
+        } else {
+            doc! { "$eq": expected }
+        };
+        let filter = doc! { "id": id.clone(), "version": version };
+        let result = collection.replace_one(filter, entity, None).await?;
+        if result.matched_count > 0 {
+            return Ok(());
+        }
+
+        if collection.count_documents(doc! { "id": id }, None).await? > 0 {
+            Err(RepositoryError::Conflict(format!(
+                "version {expected} is no longer current"
+            )))
+        } else {
+            Err(RepositoryError::NotFound)
+        }
+    }
+}
+
+fn cursor_condition(cursor: &Cursor, operator: &str) -> Document {
+    let created_at = bson::DateTime::from_chrono(cursor.created_at);
+    doc! {
+        "$or": [
+            { "created_at": { operator: created_at } },
+            { "created_at": created_at, "id": { operator: &cursor.id } },
+        ]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::repositories::{
-        GroupRepositoryTester, NotificationRepositoryTester, PaymentRepositoryTester,
+        AccessTokenRepositoryTester, AttachmentRepositoryTester, CommentRepositoryTester,
+        GroupRepositoryTester, HealthRepositoryTester, IdempotencyRepositoryTester,
+        NotificationRepositoryTester, PaymentRepositoryTester, RecurringPaymentRepositoryTester,
         UserRepositoryTester,
     };
 
+    #[tokio::test]
+    async fn test_mongo_access_token_repository() {
+        AccessTokenRepositoryTester::test(
+            MongoRepository::new(MongoRepositoryConfig {
+                uri: "mongodb://localhost:27017",
+                database: "warikan",
+            })
+            .await
+            .unwrap(),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_mongo_attachment_repository() {
+        AttachmentRepositoryTester::test(
+            MongoRepository::new(MongoRepositoryConfig {
+                uri: "mongodb://localhost:27017",
+                database: "warikan",
+            })
+            .await
+            .unwrap(),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_mongo_comment_repository() {
+        CommentRepositoryTester::test(
+            MongoRepository::new(MongoRepositoryConfig {
+                uri: "mongodb://localhost:27017",
+                database: "warikan",
+            })
+            .await
+            .unwrap(),
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn test_mongo_group_repository() {
         GroupRepositoryTester::test(
@@ -68,6 +291,32 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_mongo_health_repository() {
+        HealthRepositoryTester::test(
+            MongoRepository::new(MongoRepositoryConfig {
+                uri: "mongodb://localhost:27017",
+                database: "warikan",
+            })
+            .await
+            .unwrap(),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_mongo_idempotency_repository() {
+        IdempotencyRepositoryTester::test(
+            MongoRepository::new(MongoRepositoryConfig {
+                uri: "mongodb://localhost:27017",
+                database: "warikan",
+            })
+            .await
+            .unwrap(),
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn test_mongo_notification_repository() {
         NotificationRepositoryTester::test(
@@ -94,6 +343,19 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_mongo_recurring_payment_repository() {
+        RecurringPaymentRepositoryTester::test(
+            MongoRepository::new(MongoRepositoryConfig {
+                uri: "mongodb://localhost:27017",
+                database: "warikan",
+            })
+            .await
+            .unwrap(),
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn test_mongo_user_repository() {
         UserRepositoryTester::test(
@@ -1,7 +1,7 @@
 use crate::{
-    entities::{GroupID, Notification, NotificationID},
+    entities::{GroupID, Notification, NotificationID, Page, PageRequest, UserID},
     repositories::{
-        MongoRepository, MongoRepositoryError, NotificationRepository,
+        MongoRepository, MongoRepositoryError, NotificationRepository, RepositoryError,
         MONGO_COLLECTION_NOTIFICATIONS,
     },
 };
@@ -12,6 +12,7 @@ use mongodb::{
     options::IndexOptions,
     Collection, IndexModel,
 };
+use tracing::instrument;
 
 impl From<NotificationID> for Bson {
     fn from(value: NotificationID) -> Self {
@@ -31,28 +32,37 @@ impl MongoRepository {
                 .collection::<Notification>(MONGO_COLLECTION_NOTIFICATIONS)
                 .create_index(model, None)
                 .await?;
+        }
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"group": 1, "created_at": 1, "id": 1})
+                .build();
 
-            Ok(())
+            self.database
+                .collection::<Notification>(MONGO_COLLECTION_NOTIFICATIONS)
+                .create_index(model, None)
+                .await?;
         }
+
+        Ok(())
     }
 }
 
 #[async_trait]
 impl NotificationRepository for MongoRepository {
+    #[instrument(skip(self, notification), fields(id = ?notification.id), err(level = "debug"))]
     async fn create_notification(
         &self,
         notification: Notification,
-    ) -> Result<Notification, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Notification, RepositoryError> {
         let notifications: Collection<Notification> =
             self.database.collection(MONGO_COLLECTION_NOTIFICATIONS);
         let _ = notifications.insert_one(&notification, None).await?;
         Ok(notification)
     }
 
-    async fn delete_notification(
-        &self,
-        id: &NotificationID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_notification(&self, id: &NotificationID) -> Result<(), RepositoryError> {
         let notifications: Collection<Notification> =
             self.database.collection(MONGO_COLLECTION_NOTIFICATIONS);
         let filter = doc! { "id": id };
@@ -60,10 +70,11 @@ impl NotificationRepository for MongoRepository {
         Ok(())
     }
 
+    #[instrument(skip(self), err(level = "debug"))]
     async fn get_notification(
         &self,
         id: &NotificationID,
-    ) -> Result<Option<Notification>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<Notification>, RepositoryError> {
         let notifications: Collection<Notification> =
             self.database.collection(MONGO_COLLECTION_NOTIFICATIONS);
 
@@ -73,10 +84,11 @@ impl NotificationRepository for MongoRepository {
         Ok(result)
     }
 
+    #[instrument(skip(self), err(level = "debug"))]
     async fn get_notifications_by_group(
         &self,
         group: &GroupID,
-    ) -> Result<Vec<Notification>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Vec<Notification>, RepositoryError> {
         let notifications: Collection<Notification> =
             self.database.collection(MONGO_COLLECTION_NOTIFICATIONS);
 
@@ -89,4 +101,23 @@ impl NotificationRepository for MongoRepository {
 
         Ok(result)
     }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_notifications_page_by_group(
+        &self,
+        group: &GroupID,
+        recipient: &UserID,
+        page: &PageRequest,
+    ) -> Result<Page<Notification>, RepositoryError> {
+        let filter = doc! {
+            "group": group,
+            "$or": [
+                { "recipients": recipient },
+                { "recipients": { "$size": 0 } },
+                { "recipients": { "$exists": false } },
+            ],
+        };
+        self.find_page(MONGO_COLLECTION_NOTIFICATIONS, filter, page)
+            .await
+    }
 }
@@ -1,16 +1,18 @@
 use crate::{
-    entities::{GroupID, Payment, PaymentID},
+    entities::{GroupID, Page, PageRequest, Payment, PaymentFilter, PaymentID},
     repositories::{
-        MongoRepository, MongoRepositoryError, PaymentRepository, MONGO_COLLECTION_PAYMENTS,
+        MongoRepository, MongoRepositoryError, PaymentRepository, RepositoryError,
+        MONGO_COLLECTION_PAYMENTS,
     },
 };
 use async_trait::async_trait;
 use futures::TryStreamExt;
 use mongodb::{
-    bson::{doc, Bson},
-    options::IndexOptions,
+    bson::{doc, Bson, DateTime, Document},
+    options::{FindOptions, IndexOptions},
     Collection, IndexModel,
 };
+use tracing::instrument;
 
 impl From<PaymentID> for Bson {
     fn from(value: PaymentID) -> Self {
@@ -30,50 +32,76 @@ impl MongoRepository {
                 .collection::<Payment>(MONGO_COLLECTION_PAYMENTS)
                 .create_index(model, None)
                 .await?;
+        }
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"group": 1, "created_at": 1, "id": 1})
+                .build();
+
+            self.database
+                .collection::<Payment>(MONGO_COLLECTION_PAYMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+        for keys in [
+            doc! {"group": 1, "creditors.user": 1},
+            doc! {"group": 1, "debtors.user": 1},
+            doc! {"group": 1, "tags": 1},
+            doc! {"group": 1, "category": 1},
+        ] {
+            let model = IndexModel::builder().keys(keys).build();
 
-            Ok(())
+            self.database
+                .collection::<Payment>(MONGO_COLLECTION_PAYMENTS)
+                .create_index(model, None)
+                .await?;
         }
+
+        Ok(())
     }
 }
 
 #[async_trait]
 impl PaymentRepository for MongoRepository {
-    async fn create_payment(
-        &self,
-        payment: Payment,
-    ) -> Result<Payment, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self, payment), fields(id = ?payment.id), err(level = "debug"))]
+    async fn create_payment(&self, payment: Payment) -> Result<Payment, RepositoryError> {
         let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
         let _ = payments.insert_one(&payment, None).await?;
         Ok(payment)
     }
 
-    async fn update_payment(
-        &self,
-        payment: Payment,
-    ) -> Result<Payment, Box<dyn std::error::Error + Send + Sync>> {
-        let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
-        let filter = doc! { "id": &payment.id };
-        let _ = payments.replace_one(filter, &payment, None).await?;
+    #[instrument(skip(self, payment), fields(id = ?payment.id), err(level = "debug"))]
+    async fn update_payment(&self, payment: Payment) -> Result<Payment, RepositoryError> {
+        let expected = payment.version;
+        let payment = Payment {
+            version: expected + 1,
+            ..payment
+        };
+        self.replace_versioned(
+            MONGO_COLLECTION_PAYMENTS,
+            payment.id.clone(),
+            expected,
+            &payment,
+        )
+        .await?;
         Ok(payment)
     }
 
-    async fn delete_payment(
-        &self,
-        id: &PaymentID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_payment(&self, id: &PaymentID) -> Result<(), RepositoryError> {
         let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
 
         let filter = doc! { "id": id };
         let result = payments.delete_one(filter, None).await?;
 
-        assert!(result.deleted_count == 1);
+        if result.deleted_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
         Ok(())
     }
 
-    async fn get_payment(
-        &self,
-        id: &PaymentID,
-    ) -> Result<Option<Payment>, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_payment(&self, id: &PaymentID) -> Result<Option<Payment>, RepositoryError> {
         let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
 
         let filter = doc! { "id": id };
@@ -82,10 +110,11 @@ impl PaymentRepository for MongoRepository {
         Ok(result)
     }
 
+    #[instrument(skip(self), err(level = "debug"))]
     async fn get_payments_by_group(
         &self,
         group: &GroupID,
-    ) -> Result<Vec<Payment>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Vec<Payment>, RepositoryError> {
         let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
 
         let filter = doc! { "group": group };
@@ -93,4 +122,110 @@ impl PaymentRepository for MongoRepository {
 
         Ok(result)
     }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_payments_by_ids(
+        &self,
+        ids: &[PaymentID],
+    ) -> Result<Vec<Payment>, RepositoryError> {
+        let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
+
+        let filter = doc! { "id": { "$in": ids.to_vec() } };
+        let result = payments.find(filter, None).await?.try_collect().await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn count_payments(&self) -> Result<u64, RepositoryError> {
+        let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
+        let count = payments.count_documents(None, None).await?;
+        Ok(count)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_payments_page_by_group(
+        &self,
+        group: &GroupID,
+        page: &PageRequest,
+    ) -> Result<Page<Payment>, RepositoryError> {
+        let filter = doc! { "group": group };
+        self.find_page(MONGO_COLLECTION_PAYMENTS, filter, page)
+            .await
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn find_payments(&self, filter: &PaymentFilter) -> Result<Vec<Payment>, RepositoryError> {
+        let payments: Collection<Payment> = self.database.collection(MONGO_COLLECTION_PAYMENTS);
+
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": 1, "id": 1 })
+            .build();
+        let result = payments
+            .find(payment_filter(filter), options)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(result)
+    }
+}
+
+fn payment_filter(filter: &PaymentFilter) -> Document {
+    let mut conditions = vec![doc! { "group": { "$in": filter.groups.clone() } }];
+    if let Some(title) = &filter.title {
+        conditions.push(doc! { "title": { "$regex": escape_regex(title), "$options": "i" } });
+    }
+    if let Some(after) = &filter.created_after {
+        conditions.push(doc! { "created_at": { "$gte": DateTime::from_chrono(*after) } });
+    }
+    if let Some(before) = &filter.created_before {
+        conditions.push(doc! { "created_at": { "$lte": DateTime::from_chrono(*before) } });
+    }
+    if let Some(after) = &filter.occurred_after {
+        conditions.push(occurred_condition("$gte", DateTime::from_chrono(*after)));
+    }
+    if let Some(before) = &filter.occurred_before {
+        conditions.push(occurred_condition("$lte", DateTime::from_chrono(*before)));
+    }
+    if let Some(category) = &filter.category {
+        conditions.push(doc! { "category": category });
+    }
+    if let Some(creditor) = &filter.creditor {
+        conditions.push(doc! { "creditors.user": creditor });
+    }
+    if let Some(debtor) = &filter.debtor {
+        conditions.push(doc! { "debtors.user": debtor });
+    }
+    if let Some(min) = filter.min_amount {
+        conditions.push(doc! { "$expr": { "$gte": [{ "$sum": "$creditors.amount" }, min] } });
+    }
+    if let Some(max) = filter.max_amount {
+        conditions.push(doc! { "$expr": { "$lte": [{ "$sum": "$creditors.amount" }, max] } });
+    }
+    if !filter.tags.is_empty() {
+        conditions.push(doc! { "tags": { "$all": filter.tags.clone() } });
+    }
+    doc! { "$and": conditions }
+}
+
+/// Payments stored without `occurred_at` are compared by `created_at`.
+fn occurred_condition(operator: &str, date: DateTime) -> Document {
+    doc! {
+        "$or": [
+            { "occurred_at": { operator: date } },
+            { "occurred_at": null, "created_at": { operator: date } },
+        ]
+    }
+}
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
@@ -0,0 +1,167 @@
+use crate::{
+    entities::{GroupID, RecurringPayment, RecurringPaymentID},
+    repositories::{
+        MongoRepository, MongoRepositoryError, RecurringPaymentRepository, RepositoryError,
+        MONGO_COLLECTION_RECURRING_PAYMENTS,
+    },
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{self, doc, Bson},
+    options::IndexOptions,
+    Collection, IndexModel,
+};
+use tracing::instrument;
+
+impl From<RecurringPaymentID> for Bson {
+    fn from(value: RecurringPaymentID) -> Self {
+        Bson::String(value.0.to_string())
+    }
+}
+
+impl MongoRepository {
+    pub async fn create_recurring_payment_index(&self) -> Result<(), MongoRepositoryError> {
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build();
+
+            self.database
+                .collection::<RecurringPayment>(MONGO_COLLECTION_RECURRING_PAYMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder().keys(doc! {"group": 1}).build();
+
+            self.database
+                .collection::<RecurringPayment>(MONGO_COLLECTION_RECURRING_PAYMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+        {
+            let model = IndexModel::builder()
+                .keys(doc! {"paused": 1, "next_at": 1})
+                .build();
+
+            self.database
+                .collection::<RecurringPayment>(MONGO_COLLECTION_RECURRING_PAYMENTS)
+                .create_index(model, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecurringPaymentRepository for MongoRepository {
+    #[instrument(skip(self, recurring_payment), fields(id = ?recurring_payment.id), err(level = "debug"))]
+    async fn create_recurring_payment(
+        &self,
+        recurring_payment: RecurringPayment,
+    ) -> Result<RecurringPayment, RepositoryError> {
+        let recurring_payments: Collection<RecurringPayment> = self
+            .database
+            .collection(MONGO_COLLECTION_RECURRING_PAYMENTS);
+        let _ = recurring_payments
+            .insert_one(&recurring_payment, None)
+            .await?;
+        Ok(recurring_payment)
+    }
+
+    #[instrument(skip(self, recurring_payment), fields(id = ?recurring_payment.id), err(level = "debug"))]
+    async fn update_recurring_payment(
+        &self,
+        recurring_payment: RecurringPayment,
+    ) -> Result<RecurringPayment, RepositoryError> {
+        let expected = recurring_payment.version;
+        let recurring_payment = RecurringPayment {
+            version: expected + 1,
+            ..recurring_payment
+        };
+        self.replace_versioned(
+            MONGO_COLLECTION_RECURRING_PAYMENTS,
+            recurring_payment.id.clone(),
+            expected,
+            &recurring_payment,
+        )
+        .await?;
+        Ok(recurring_payment)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_recurring_payment(
+        &self,
+        id: &RecurringPaymentID,
+    ) -> Result<(), RepositoryError> {
+        let recurring_payments: Collection<RecurringPayment> = self
+            .database
+            .collection(MONGO_COLLECTION_RECURRING_PAYMENTS);
+
+        let filter = doc! { "id": id };
+        let result = recurring_payments.delete_one(filter, None).await?;
+
+        if result.deleted_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_recurring_payment(
+        &self,
+        id: &RecurringPaymentID,
+    ) -> Result<Option<RecurringPayment>, RepositoryError> {
+        let recurring_payments: Collection<RecurringPayment> = self
+            .database
+            .collection(MONGO_COLLECTION_RECURRING_PAYMENTS);
+
+        let filter = doc! { "id": id };
+        let result = recurring_payments.find_one(filter, None).await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_recurring_payments_by_group(
+        &self,
+        group: &GroupID,
+    ) -> Result<Vec<RecurringPayment>, RepositoryError> {
+        let recurring_payments: Collection<RecurringPayment> = self
+            .database
+            .collection(MONGO_COLLECTION_RECURRING_PAYMENTS);
+
+        let filter = doc! { "group": group };
+        let result = recurring_payments
+            .find(filter, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_due_recurring_payments(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> Result<Vec<RecurringPayment>, RepositoryError> {
+        let recurring_payments: Collection<RecurringPayment> = self
+            .database
+            .collection(MONGO_COLLECTION_RECURRING_PAYMENTS);
+
+        let filter =
+            doc! { "paused": false, "next_at": { "$lte": bson::DateTime::from_chrono(*now) } };
+        let result = recurring_payments
+            .find(filter, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(result)
+    }
+}
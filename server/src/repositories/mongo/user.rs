@@ -1,13 +1,18 @@
 use crate::{
     entities::{User, UserID},
-    repositories::{MongoRepository, MongoRepositoryError, UserRepository, MONGO_COLLECTION_USERS},
+    repositories::{
+        MongoRepository, MongoRepositoryError, RepositoryError, UserRepository,
+        MONGO_COLLECTION_USERS,
+    },
 };
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use mongodb::{
     bson::{doc, Bson},
     options::IndexOptions,
     Collection, IndexModel,
 };
+use tracing::instrument;
 
 impl From<UserID> for Bson {
     fn from(value: UserID) -> Self {
@@ -35,42 +40,39 @@ impl MongoRepository {
 
 #[async_trait]
 impl UserRepository for MongoRepository {
-    async fn create_user(
-        &self,
-        user: User,
-    ) -> Result<User, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self, user), fields(id = ?user.id), err(level = "debug"))]
+    async fn create_user(&self, user: User) -> Result<User, RepositoryError> {
         let users: Collection<User> = self.database.collection(MONGO_COLLECTION_USERS);
         let _ = users.insert_one(&user, None).await?;
         Ok(user)
     }
 
-    async fn update_user(
-        &self,
-        user: User,
-    ) -> Result<User, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self, user), fields(id = ?user.id), err(level = "debug"))]
+    async fn update_user(&self, user: User) -> Result<User, RepositoryError> {
         let users: Collection<User> = self.database.collection(MONGO_COLLECTION_USERS);
         let filter = doc! { "id": &user.id };
-        let _ = users.replace_one(filter, &user, None).await?;
+        let result = users.replace_one(filter, &user, None).await?;
+        if result.matched_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
         Ok(user)
     }
 
-    async fn delete_user(
-        &self,
-        id: &UserID,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn delete_user(&self, id: &UserID) -> Result<(), RepositoryError> {
         let users: Collection<User> = self.database.collection(MONGO_COLLECTION_USERS);
 
         let filter = doc! { "id": id };
         let result = users.delete_one(filter, None).await?;
 
-        assert!(result.deleted_count == 1);
+        if result.deleted_count == 0 {
+            return Err(RepositoryError::NotFound);
+        }
         Ok(())
     }
 
-    async fn get_user(
-        &self,
-        id: &UserID,
-    ) -> Result<Option<User>, Box<dyn std::error::Error + Send + Sync>> {
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_user(&self, id: &UserID) -> Result<Option<User>, RepositoryError> {
         let users: Collection<User> = self.database.collection(MONGO_COLLECTION_USERS);
 
         let filter = doc! { "id": id };
@@ -78,4 +80,21 @@ impl UserRepository for MongoRepository {
 
         Ok(result)
     }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn get_users_by_ids(&self, ids: &[UserID]) -> Result<Vec<User>, RepositoryError> {
+        let users: Collection<User> = self.database.collection(MONGO_COLLECTION_USERS);
+
+        let filter = doc! { "id": { "$in": ids.to_vec() } };
+        let result = users.find(filter, None).await?.try_collect().await?;
+
+        Ok(result)
+    }
+
+    #[instrument(skip(self), err(level = "debug"))]
+    async fn count_users(&self) -> Result<u64, RepositoryError> {
+        let users: Collection<User> = self.database.collection(MONGO_COLLECTION_USERS);
+        let count = users.count_documents(None, None).await?;
+        Ok(count)
+    }
 }
@@ -0,0 +1,79 @@
+use crate::storages::{check_key, Storage, StorageError};
+use async_trait::async_trait;
+use std::{io::ErrorKind, path::PathBuf};
+use tokio::fs;
+use tracing::instrument;
+
+/// Stores blobs as files below `root`, one file per key.
+#[derive(Debug)]
+pub struct LocalStorage {
+    pub root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(err: std::io::Error) -> Self {
+        StorageError::Other(err.to_string())
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    #[instrument(skip(self, content), err)]
+    async fn put(
+        &self,
+        key: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        // Write under a temporary name first so readers never see a partial file.
+        let partial = path.with_extension("partial");
+        fs::write(&partial, content).await?;
+        fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match fs::read(self.path(key)?).await {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)?,
+        }
+    }
+
+    #[instrument(skip(self), err)]
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err)?,
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::StorageTester;
+
+    #[tokio::test]
+    async fn test_local_storage() {
+        let root = std::env::temp_dir().join(format!("warikan-{}", nanoid::nanoid!()));
+        StorageTester::test(LocalStorage::new(&root)).await;
+        let _ = fs::remove_dir_all(root).await;
+    }
+}
@@ -0,0 +1,56 @@
+use crate::storages::{check_key, Storage, StorageError};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Mutex};
+
+/// Keeps blobs in process memory. Content is lost on restart, so this only
+/// serves tests and local experiments.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    blobs: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn put(
+        &self,
+        key: &str,
+        content: Vec<u8>,
+        _content_type: &str,
+    ) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.blobs
+            .lock()
+            .map_err(|err| StorageError::Other(err.to_string()))?
+            .insert(key.to_string(), content);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        check_key(key)?;
+        let blobs = self
+            .blobs
+            .lock()
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+        Ok(blobs.get(key).cloned())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.blobs
+            .lock()
+            .map_err(|err| StorageError::Other(err.to_string()))?
+            .remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::StorageTester;
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        StorageTester::test(MemoryStorage::default()).await;
+    }
+}
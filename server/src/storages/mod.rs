@@ -0,0 +1,129 @@
+mod local;
+mod memory;
+#[cfg(feature = "s3")]
+mod s3;
+
+pub use local::*;
+pub use memory::*;
+#[cfg(feature = "s3")]
+pub use s3::*;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[cfg(test)]
+use mockall::*;
+
+/// Failure of a blob storage call.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum StorageError {
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+
+    #[error("storage unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("storage error: {0}")]
+    Other(String),
+}
+
+/// Stores the content of attachments. Keys are `/`-separated paths made of
+/// entity IDs.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(
+        &self,
+        key: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), StorageError>;
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Deleting a key that does not exist succeeds.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Keys are joined onto paths and URLs, so only ID characters and `/`
+/// separators between non-empty segments are allowed.
+pub fn check_key(key: &str) -> Result<(), StorageError> {
+    let valid = key.split('/').all(|segment| {
+        !segment.is_empty()
+            && segment != "."
+            && segment != ".."
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+#[cfg(test)]
+pub struct StorageTester<S: Storage> {
+    pub storage: S,
+}
+
+#[cfg(test)]
+impl<S: Storage> StorageTester<S> {
+    pub async fn test(storage: S) {
+        let tester = Self { storage };
+        tester.put().await;
+        tester.delete().await;
+        tester.invalid_key().await;
+    }
+
+    async fn put(&self) {
+        let key = format!("test/{}", nanoid::nanoid!());
+
+        self.storage
+            .put(&key, b"receipt".to_vec(), "image/png")
+            .await
+            .unwrap();
+        let get = self.storage.get(&key).await.unwrap();
+
+        assert_eq!(get, Some(b"receipt".to_vec()));
+    }
+
+    async fn delete(&self) {
+        let key = format!("test/{}", nanoid::nanoid!());
+
+        self.storage
+            .put(&key, b"receipt".to_vec(), "image/png")
+            .await
+            .unwrap();
+        self.storage.delete(&key).await.unwrap();
+        let get = self.storage.get(&key).await.unwrap();
+        let again = self.storage.delete(&key).await;
+
+        assert_eq!(get, None);
+        assert_eq!(again, Ok(()));
+    }
+
+    async fn invalid_key(&self) {
+        let put = self
+            .storage
+            .put("../escape", b"receipt".to_vec(), "image/png")
+            .await;
+
+        assert!(matches!(put, Err(StorageError::InvalidKey(_))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_key_rejects_traversal() {
+        assert!(check_key("group/payment/attachment_1-a").is_ok());
+        assert!(check_key("../etc/passwd").is_err());
+        assert!(check_key("/absolute").is_err());
+        assert!(check_key("group//attachment").is_err());
+        assert!(check_key("group/a b").is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    entities::{AccessToken, AccessTokenID, AuthState, Scope, UserID, ACCESS_TOKEN_PREFIX},
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use chrono::{DateTime, Utc};
+use nanoid::nanoid;
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct CreateAccessTokenInput {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The plaintext `secret` is only ever returned here; the repository keeps its hash.
+pub struct CreatedAccessToken {
+    pub access_token: AccessToken,
+    pub secret: String,
+}
+
+impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn create_access_token(
+        &self,
+        auth: &AuthState,
+        input: CreateAccessTokenInput,
+    ) -> Result<CreatedAccessToken, UseCaseError> {
+        if let AuthState::Authorized(claims) = auth {
+            if claims.is_access_token() {
+                Err(UseCaseError::Forbidden)?
+            }
+            let now = Utc::now();
+            if input.scopes.is_empty() || input.expires_at <= now {
+                Err(UseCaseError::BadRequest)?
+            }
+            let secret = format!("{}{}", ACCESS_TOKEN_PREFIX, nanoid!(40));
+            let access_token = AccessToken {
+                id: AccessTokenID::new(nanoid!()),
+                created_at: now,
+                updated_at: now,
+                name: input.name,
+                hash: AccessToken::hash(&secret),
+                scopes: input.scopes,
+                expires_at: input.expires_at,
+                user: UserID::new(&claims.sub),
+            };
+            let access_token = self.repository.create_access_token(access_token).await?;
+            Ok(CreatedAccessToken {
+                access_token,
+                secret,
+            })
+        } else {
+            Err(UseCaseError::from(auth))?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{AuthError, Claims, ACCESS_TOKEN_ISSUER},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use chrono::Duration;
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    fn input() -> CreateAccessTokenInput {
+        let mut input: CreateAccessTokenInput = Faker.fake();
+        input.scopes = vec![Scope::Read];
+        input.expires_at = Utc::now() + Duration::days(30);
+        input
+    }
+
+    #[tokio::test]
+    async fn create_access_token_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_access_token().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_access_token(&auth, input()).await.unwrap();
+        assert!(create.secret.starts_with(ACCESS_TOKEN_PREFIX));
+        assert_eq!(create.access_token.hash, AccessToken::hash(&create.secret));
+        assert_eq!(create.access_token.user, user);
+    }
+
+    #[tokio::test]
+    async fn create_access_token_400() {
+        let claims: Claims = Faker.fake();
+        let mut input = input();
+        input.expires_at = Utc::now() - Duration::days(1);
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_access_token(&auth, input).await;
+        assert!(matches!(create, Err(UseCaseError::BadRequest)));
+    }
+
+    #[tokio::test]
+    async fn create_access_token_401() {
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let create = usecase.create_access_token(&auth, input()).await;
+        assert!(matches!(
+            create,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        ));
+    }
+
+    #[tokio::test]
+    async fn create_access_token_403() {
+        let mut claims: Claims = Faker.fake();
+        claims.iss = ACCESS_TOKEN_ISSUER.to_string();
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_access_token(&auth, input()).await;
+        assert!(matches!(create, Err(UseCaseError::Forbidden)));
+    }
+
+    #[tokio::test]
+    async fn create_access_token_500() {
+        let claims: Claims = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_access_token()
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_access_token(&auth, input()).await;
+        assert!(matches!(create, Err(UseCaseError::InternalServerError)));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    entities::{AccessTokenID, AuthState, UserID},
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct DeleteAccessTokenInput {
+    pub id: AccessTokenID,
+}
+
+impl UseCase {
+    /// Revokes a personal access token owned by the caller.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn delete_access_token(
+        &self,
+        auth: &AuthState,
+        input: DeleteAccessTokenInput,
+    ) -> Result<AccessTokenID, UseCaseError> {
+        if let AuthState::Authorized(claims) = auth {
+            if let Some(access_token) = self.repository.get_access_token(&input.id).await? {
+                if access_token.user == UserID::new(&claims.sub) {
+                    self.repository
+                        .delete_access_token(&access_token.id)
+                        .await?;
+                    Ok(access_token.id)
+                } else {
+                    Err(UseCaseError::Forbidden)?
+                }
+            } else {
+                Err(UseCaseError::NotFound)?
+            }
+        } else {
+            Err(UseCaseError::from(auth))?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{AccessToken, Claims},
+        repositories::MockRepository,
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn delete_access_token_200() {
+        let claims: Claims = Faker.fake();
+        let mut access_token: AccessToken = Faker.fake();
+        access_token.user = UserID::new(&claims.sub);
+        let input = DeleteAccessTokenInput {
+            id: access_token.id.clone(),
+        };
+        let id = access_token.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_access_token()
+            .returning(move |_| Ok(Some(access_token.clone())));
+        mock.expect_delete_access_token().returning(|_| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_access_token(&auth, input).await.unwrap();
+        assert_eq!(delete, id);
+    }
+
+    #[tokio::test]
+    async fn delete_access_token_403() {
+        let claims: Claims = Faker.fake();
+        let access_token: AccessToken = Faker.fake();
+        let input = DeleteAccessTokenInput {
+            id: access_token.id.clone(),
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_access_token()
+            .returning(move |_| Ok(Some(access_token.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_access_token(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn delete_access_token_404() {
+        let claims: Claims = Faker.fake();
+        let input: DeleteAccessTokenInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_access_token().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_access_token(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::NotFound));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    entities::{AccessToken, AuthError, AuthState, Claims, UserID},
+    usecases::{UseCase, UseCaseError},
+};
+use chrono::Utc;
+use tracing::instrument;
+
+impl UseCase {
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_access_tokens_by_user(
+        &self,
+        auth: &AuthState,
+        id: &UserID,
+    ) -> Result<Vec<AccessToken>, UseCaseError> {
+        if let AuthState::Authorized(claims) = auth {
+            if id == &UserID::new(&claims.sub) {
+                let access_tokens = self.repository.get_access_tokens_by_user(id).await?;
+                Ok(access_tokens)
+            } else {
+                Err(UseCaseError::Forbidden)?
+            }
+        } else {
+            Err(UseCaseError::from(auth))?
+        }
+    }
+
+    /// Resolves a plaintext personal access token to the identity it was issued for.
+    #[instrument(skip(self, secret), err(level = "debug"))]
+    pub async fn authenticate_access_token(&self, secret: &str) -> Result<Claims, UseCaseError> {
+        let access_token = self
+            .repository
+            .get_access_token_by_hash(&AccessToken::hash(secret))
+            .await?
+            .ok_or(UseCaseError::Unauthorized(AuthError::InvalidToken))?;
+        if access_token.is_expired(Utc::now()) {
+            Err(UseCaseError::Unauthorized(AuthError::TokenExpired))?
+        }
+        Ok(access_token.claims())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::MockRepository;
+    use chrono::Duration;
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn get_access_tokens_by_user_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let access_tokens: Vec<AccessToken> = Faker.fake();
+        let len = access_tokens.len();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_access_tokens_by_user()
+            .returning(move |_| Ok(access_tokens.clone()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .get_access_tokens_by_user(&auth, &user)
+            .await
+            .unwrap();
+        assert_eq!(get.len(), len);
+    }
+
+    #[tokio::test]
+    async fn get_access_tokens_by_user_401() {
+        let user: UserID = Faker.fake();
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let get = usecase.get_access_tokens_by_user(&auth, &user).await;
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_access_tokens_by_user_403() {
+        let claims: Claims = Faker.fake();
+        let user: UserID = Faker.fake();
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_access_tokens_by_user(&auth, &user).await;
+        assert_eq!(get, Err(UseCaseError::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn authenticate_access_token_200() {
+        let mut access_token: AccessToken = Faker.fake();
+        access_token.expires_at = Utc::now() + Duration::days(1);
+        let user = access_token.user.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_access_token_by_hash()
+            .returning(move |_| Ok(Some(access_token.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+
+        let claims = usecase.authenticate_access_token("secret").await.unwrap();
+        assert_eq!(UserID::new(&claims.sub), user);
+        assert!(claims.is_access_token());
+    }
+
+    #[tokio::test]
+    async fn authenticate_access_token_401() {
+        let mut access_token: AccessToken = Faker.fake();
+        access_token.expires_at = Utc::now() - Duration::days(1);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_access_token_by_hash()
+            .returning(move |_| Ok(Some(access_token.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+
+        let claims = usecase.authenticate_access_token("secret").await;
+        assert!(matches!(
+            claims,
+            Err(UseCaseError::Unauthorized(AuthError::TokenExpired))
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    entities::{Attachment, AttachmentID, AuthState, PaymentID, UserID, ATTACHMENT_CONTENT_TYPES},
+    usecases::{FieldError, UseCase, UseCaseError},
+};
+use chrono::Utc;
+use nanoid::nanoid;
+use tracing::{instrument, warn};
+
+#[cfg(test)]
+use fake::Dummy;
+
+/// An uploaded file, read from a GraphQL multipart request.
+#[cfg_attr(test, derive(Dummy))]
+pub struct CreateAttachmentInput {
+    pub payment: PaymentID,
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn create_attachment(
+        &self,
+        auth: &AuthState,
+        input: CreateAttachmentInput,
+    ) -> Result<Attachment, UseCaseError> {
+        if let (AuthState::Authorized(claims), Some(payment)) =
+            (auth, self.get_payment(auth, &input.payment).await?)
+        {
+            let mut errors = Vec::new();
+            if input.filename.trim().is_empty() {
+                errors.push(FieldError::new("file", "must have a filename"));
+            }
+            if !ATTACHMENT_CONTENT_TYPES.contains(&input.content_type.as_str()) {
+                errors.push(FieldError::new(
+                    "file",
+                    format!("content type {} is not supported", input.content_type),
+                ));
+            }
+            if input.content.is_empty() {
+                errors.push(FieldError::new("file", "must not be empty"));
+            } else if input.content.len() > self.max_attachment_size {
+                errors.push(FieldError::new(
+                    "file",
+                    format!("must not exceed {} bytes", self.max_attachment_size),
+                ));
+            }
+            if !errors.is_empty() {
+                Err(UseCaseError::InvalidInput(errors))?
+            }
+
+            let now = Utc::now();
+            let id = AttachmentID::new(nanoid!());
+            let attachment = Attachment {
+                key: Attachment::key_for(&payment.group, &payment.id, &id),
+                id,
+                created_at: now,
+                updated_at: now,
+                filename: input.filename,
+                content_type: input.content_type,
+                size: input.content.len() as i64,
+                uploaded_by: UserID::new(&claims.sub),
+                payment: payment.id,
+                group: payment.group,
+            };
+            self.storage
+                .put(&attachment.key, input.content, &attachment.content_type)
+                .await?;
+            match self.repository.create_attachment(attachment.clone()).await {
+                Ok(attachment) => Ok(attachment),
+                Err(err) => {
+                    if let Err(err) = self.storage.delete(&attachment.key).await {
+                        warn!(%err, key = attachment.key, "failed to remove orphaned blob");
+                    }
+                    Err(err)?
+                }
+            }
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Group, Payment},
+        repositories::{MockRepository, RepositoryError},
+        storages::MockStorage,
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn create_attachment_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let mut input: CreateAttachmentInput = Faker.fake();
+        input.payment = payment.id.clone();
+        input.content_type = "image/png".to_string();
+        input.content = vec![0; 128];
+        let (id, group_id) = (payment.id.clone(), group.id.clone());
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_create_attachment().returning(Ok);
+        let mut storage = MockStorage::new();
+        storage.expect_put().times(1).returning(|_, _, _| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock)).with_storage(Arc::new(storage));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_attachment(&auth, input).await.unwrap();
+        assert_eq!(create.payment, id);
+        assert_eq!(create.size, 128);
+        assert!(create.key.starts_with(&group_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn create_attachment_400() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let mut input: CreateAttachmentInput = Faker.fake();
+        input.payment = payment.id.clone();
+        input.content_type = "text/html".to_string();
+        input.content = vec![0; 2048];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        let mut storage = MockStorage::new();
+        storage.expect_put().never();
+
+        let usecase = UseCase::new(Arc::new(mock))
+            .with_storage(Arc::new(storage))
+            .with_max_attachment_size(1024);
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_attachment(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = create else {
+            panic!("expected invalid input, got {create:?}");
+        };
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn create_attachment_404() {
+        let claims: Claims = Faker.fake();
+        let input: CreateAttachmentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_payment().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_attachment(&auth, input).await;
+        assert_eq!(create, Err(UseCaseError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn create_attachment_500() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let mut input: CreateAttachmentInput = Faker.fake();
+        input.payment = payment.id.clone();
+        input.content_type = "image/png".to_string();
+        input.content = vec![0; 128];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_create_attachment()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+        let mut storage = MockStorage::new();
+        storage.expect_put().returning(|_, _, _| Ok(()));
+        storage.expect_delete().times(1).returning(|_| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock)).with_storage(Arc::new(storage));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_attachment(&auth, input).await;
+        assert_eq!(create, Err(UseCaseError::InternalServerError));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{
+    entities::{Attachment, AttachmentID, AuthState},
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use futures::future::try_join_all;
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct DeleteAttachmentInput {
+    pub id: AttachmentID,
+}
+
+impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn delete_attachment(
+        &self,
+        auth: &AuthState,
+        input: DeleteAttachmentInput,
+    ) -> Result<AttachmentID, UseCaseError> {
+        if let Some(attachment) = self.get_attachment(auth, &input.id).await? {
+            let id = attachment.id.clone();
+            self.remove_attachments(vec![attachment]).await?;
+            Ok(id)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+
+    /// Deletes the content before the metadata, so that a failure leaves
+    /// nothing behind that cannot be found again and retried.
+    pub(crate) async fn remove_attachments(
+        &self,
+        attachments: Vec<Attachment>,
+    ) -> Result<(), UseCaseError> {
+        try_join_all(attachments.iter().map(|attachment| async {
+            self.storage.delete(&attachment.key).await?;
+            self.repository.delete_attachment(&attachment.id).await?;
+            Ok::<_, UseCaseError>(())
+        }))
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Group, Payment, UserID},
+        repositories::MockRepository,
+        storages::{MockStorage, StorageError},
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn delete_attachment_200() {
+        let claims: Claims = Faker.fake();
+        let attachment: Attachment = Faker.fake();
+        let input = DeleteAttachmentInput {
+            id: attachment.id.clone(),
+        };
+        let id = attachment.id.clone();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = attachment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_attachment()
+            .returning(move |_| Ok(Some(attachment.clone())));
+        mock.expect_delete_attachment()
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut storage = MockStorage::new();
+        storage.expect_delete().times(1).returning(|_| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock)).with_storage(Arc::new(storage));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_attachment(&auth, input).await.unwrap();
+        assert_eq!(delete, id);
+    }
+
+    #[tokio::test]
+    async fn delete_attachment_404() {
+        let claims: Claims = Faker.fake();
+        let input: DeleteAttachmentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_attachment().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_attachment(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn delete_attachment_500() {
+        let claims: Claims = Faker.fake();
+        let attachment: Attachment = Faker.fake();
+        let input = DeleteAttachmentInput {
+            id: attachment.id.clone(),
+        };
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = attachment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_attachment()
+            .returning(move |_| Ok(Some(attachment.clone())));
+        mock.expect_delete_attachment().never();
+        let mut storage = MockStorage::new();
+        storage
+            .expect_delete()
+            .returning(|_| Err(StorageError::Unavailable("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock)).with_storage(Arc::new(storage));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_attachment(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::InternalServerError));
+    }
+}
@@ -0,0 +1,6 @@
+mod create;
+mod delete;
+mod read;
+
+pub use create::*;
+pub use delete::*;
@@ -0,0 +1,213 @@
+use crate::{
+    entities::{Attachment, AttachmentID, AuthState, PaymentID},
+    usecases::{UseCase, UseCaseError},
+};
+use tracing::instrument;
+
+impl UseCase {
+    /// Visible to the participants of the group the payment belongs to.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_attachment(
+        &self,
+        auth: &AuthState,
+        id: &AttachmentID,
+    ) -> Result<Option<Attachment>, UseCaseError> {
+        if let Some(attachment) = self.repository.get_attachment(id).await? {
+            let attachment = self
+                .get_payment(auth, &attachment.payment)
+                .await?
+                .and(Some(attachment));
+            Ok(attachment)
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_attachments_by_payment(
+        &self,
+        auth: &AuthState,
+        id: &PaymentID,
+    ) -> Result<Vec<Attachment>, UseCaseError> {
+        if self.get_payment(auth, id).await?.is_some() {
+            let attachments = self.repository.get_attachments_by_payment(id).await?;
+            Ok(attachments)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+
+    /// Batch form of [`UseCase::get_attachments_by_payment`]. Attachments of
+    /// payments the caller cannot see are left out.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_attachments_by_payments(
+        &self,
+        auth: &AuthState,
+        ids: &[PaymentID],
+    ) -> Result<Vec<Attachment>, UseCaseError> {
+        let payments: Vec<PaymentID> = self
+            .get_payments_by_ids(auth, ids)
+            .await?
+            .into_iter()
+            .map(|payment| payment.id)
+            .collect();
+        if payments.is_empty() {
+            return Ok(vec![]);
+        }
+        let attachments = self
+            .repository
+            .get_attachments_by_payments(&payments)
+            .await?;
+        Ok(attachments)
+    }
+
+    /// The attachment together with its content.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn download_attachment(
+        &self,
+        auth: &AuthState,
+        id: &AttachmentID,
+    ) -> Result<(Attachment, Vec<u8>), UseCaseError> {
+        let attachment = self
+            .get_attachment(auth, id)
+            .await?
+            .ok_or(UseCaseError::NotFound)?;
+        let content = self
+            .storage
+            .get(&attachment.key)
+            .await?
+            .ok_or(UseCaseError::NotFound)?;
+        Ok((attachment, content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{AuthError, Claims, Group, Payment, UserID},
+        repositories::MockRepository,
+        storages::MockStorage,
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn get_attachments_by_payments_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let foreign: Payment = Faker.fake();
+        let mut attachment: Attachment = Faker.fake();
+        attachment.payment = payment.id.clone();
+        let ids = vec![payment.id.clone(), foreign.id.clone()];
+        let expected = vec![attachment.clone()];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_payments_by_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![payment.clone(), foreign.clone()]));
+        mock.expect_get_groups_by_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![group.clone()]));
+        mock.expect_get_attachments_by_payments()
+            .withf(|payments| payments.len() == 1)
+            .times(1)
+            .returning(move |_| Ok(vec![attachment.clone()]));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_attachments_by_payments(&auth, &ids).await;
+        assert_eq!(get, Ok(expected));
+    }
+
+    #[tokio::test]
+    async fn download_attachment_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let attachment: Attachment = Faker.fake();
+        let id = attachment.id.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.id = attachment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_attachment()
+            .returning(move |_| Ok(Some(attachment.clone())));
+        let mut storage = MockStorage::new();
+        storage
+            .expect_get()
+            .returning(|_| Ok(Some(b"receipt".to_vec())));
+
+        let usecase = UseCase::new(Arc::new(mock)).with_storage(Arc::new(storage));
+        let auth = AuthState::Authorized(claims);
+
+        let (download, content) = usecase.download_attachment(&auth, &id).await.unwrap();
+        assert_eq!(download.id, id);
+        assert_eq!(content, b"receipt".to_vec());
+    }
+
+    #[tokio::test]
+    async fn download_attachment_401() {
+        let group: Group = Faker.fake();
+        let attachment: Attachment = Faker.fake();
+        let id = attachment.id.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.id = attachment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_attachment()
+            .returning(move |_| Ok(Some(attachment.clone())));
+        let mut storage = MockStorage::new();
+        storage.expect_get().never();
+
+        let usecase = UseCase::new(Arc::new(mock)).with_storage(Arc::new(storage));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let download = usecase.download_attachment(&auth, &id).await;
+        assert_eq!(
+            download,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn download_attachment_403() {
+        let claims: Claims = Faker.fake();
+        let group: Group = Faker.fake();
+        let attachment: Attachment = Faker.fake();
+        let id = attachment.id.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.id = attachment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_attachment()
+            .returning(move |_| Ok(Some(attachment.clone())));
+        let mut storage = MockStorage::new();
+        storage.expect_get().never();
+
+        let usecase = UseCase::new(Arc::new(mock)).with_storage(Arc::new(storage));
+        let auth = AuthState::Authorized(claims);
+
+        let download = usecase.download_attachment(&auth, &id).await;
+        assert_eq!(download, Err(UseCaseError::Forbidden));
+    }
+}
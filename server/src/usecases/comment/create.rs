@@ -0,0 +1,229 @@
+use crate::{
+    entities::{
+        AuthState, Comment, CommentID, Notification, NotificationID, PaymentID, UserID,
+        MAX_COMMENT_LENGTH,
+    },
+    usecases::{FieldError, UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use chrono::Utc;
+use nanoid::nanoid;
+use tracing::{instrument, warn};
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct CreateCommentInput {
+    pub payment: PaymentID,
+    pub body: String,
+}
+
+impl UseCase {
+    /// Adds a comment to the payment and notifies the group about it.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn create_comment(
+        &self,
+        auth: &AuthState,
+        input: CreateCommentInput,
+    ) -> Result<Comment, UseCaseError> {
+        if let (AuthState::Authorized(claims), Some(payment)) =
+            (auth, self.get_payment(auth, &input.payment).await?)
+        {
+            let errors = check_body(&input.body);
+            if !errors.is_empty() {
+                Err(UseCaseError::InvalidInput(errors))?
+            }
+
+            let now = Utc::now();
+            let comment = Comment {
+                id: CommentID::new(nanoid!()),
+                created_at: now,
+                updated_at: now,
+                author: UserID::new(&claims.sub),
+                body: input.body,
+                payment: payment.id,
+                group: payment.group,
+            };
+            let comment = self.repository.create_comment(comment).await?;
+
+            // The comment is already saved, so a failed notification must not
+            // make the client retry and post it twice.
+            let notification = Notification {
+                id: NotificationID::new(nanoid!()),
+                created_at: now,
+                updated_at: now,
+                message: format!("New comment on \"{}\"", payment.title),
+                recipients: Vec::new(),
+                group: comment.group.clone(),
+            };
+            if let Err(err) = self.repository.create_notification(notification).await {
+                warn!(%err, id = ?comment.id, "failed to notify group of comment");
+            }
+            Ok(comment)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+}
+
+/// Comment bodies must be non-blank and at most [`MAX_COMMENT_LENGTH`] characters.
+pub(super) fn check_body(body: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if body.trim().is_empty() {
+        errors.push(FieldError::new("body", "must not be empty"));
+    } else if body.chars().count() > MAX_COMMENT_LENGTH {
+        errors.push(FieldError::new(
+            "body",
+            format!("must not exceed {MAX_COMMENT_LENGTH} characters"),
+        ));
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Group, Payment},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn create_comment_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let input = CreateCommentInput {
+            payment: payment.id.clone(),
+            body: "Wasn't this split three ways?".to_string(),
+        };
+        let (id, group_id) = (payment.id.clone(), group.id.clone());
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_create_comment().returning(Ok);
+        mock.expect_create_notification()
+            .times(1)
+            .withf(move |notification| notification.group == group_id)
+            .returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims.clone());
+
+        let create = usecase.create_comment(&auth, input).await.unwrap();
+        assert_eq!(create.payment, id);
+        assert_eq!(create.author, UserID::new(&claims.sub));
+    }
+
+    #[tokio::test]
+    async fn create_comment_200_notification_failed() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let input = CreateCommentInput {
+            payment: payment.id.clone(),
+            body: "Paid in cash.".to_string(),
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_create_comment().returning(Ok);
+        mock.expect_create_notification()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_comment(&auth, input).await;
+        assert!(create.is_ok());
+    }
+
+    #[tokio::test]
+    async fn create_comment_400() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let input = CreateCommentInput {
+            payment: payment.id.clone(),
+            body: " \n".to_string(),
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_create_comment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_comment(&auth, input).await;
+        assert_eq!(
+            create,
+            Err(UseCaseError::InvalidInput(vec![FieldError::new(
+                "body",
+                "must not be empty"
+            )]))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_comment_404() {
+        let claims: Claims = Faker.fake();
+        let input: CreateCommentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_payment().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_comment(&auth, input).await;
+        assert_eq!(create, Err(UseCaseError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn create_comment_500() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let input = CreateCommentInput {
+            payment: payment.id.clone(),
+            body: "Paid in cash.".to_string(),
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_create_comment()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+        mock.expect_create_notification().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_comment(&auth, input).await;
+        assert_eq!(create, Err(UseCaseError::InternalServerError));
+    }
+}
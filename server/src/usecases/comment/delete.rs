@@ -0,0 +1,146 @@
+use crate::{
+    entities::{AuthState, CommentID},
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct DeleteCommentInput {
+    pub id: CommentID,
+}
+
+impl UseCase {
+    /// Only the author can delete a comment.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn delete_comment(
+        &self,
+        auth: &AuthState,
+        input: DeleteCommentInput,
+    ) -> Result<CommentID, UseCaseError> {
+        let comment = self.get_own_comment(auth, &input.id).await?;
+        self.repository.delete_comment(&comment.id).await?;
+        Ok(comment.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Comment, Group, Payment, UserID},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn delete_comment_200() {
+        let claims: Claims = Faker.fake();
+        let mut comment: Comment = Faker.fake();
+        comment.author = UserID::new(&claims.sub);
+        let input = DeleteCommentInput {
+            id: comment.id.clone(),
+        };
+        let id = comment.id.clone();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+        mock.expect_delete_comment().times(1).returning(|_| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_comment(&auth, input).await.unwrap();
+        assert_eq!(delete, id);
+    }
+
+    #[tokio::test]
+    async fn delete_comment_403() {
+        let claims: Claims = Faker.fake();
+        let comment: Comment = Faker.fake();
+        let input = DeleteCommentInput {
+            id: comment.id.clone(),
+        };
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+        mock.expect_delete_comment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_comment(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn delete_comment_404() {
+        let claims: Claims = Faker.fake();
+        let input: DeleteCommentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_comment().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_comment(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn delete_comment_500() {
+        let claims: Claims = Faker.fake();
+        let mut comment: Comment = Faker.fake();
+        comment.author = UserID::new(&claims.sub);
+        let input = DeleteCommentInput {
+            id: comment.id.clone(),
+        };
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+        mock.expect_delete_comment()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_comment(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::InternalServerError));
+    }
+}
@@ -0,0 +1,8 @@
+mod create;
+mod delete;
+mod read;
+mod update;
+
+pub use create::*;
+pub use delete::*;
+pub use update::*;
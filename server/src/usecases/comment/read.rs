@@ -0,0 +1,181 @@
+use crate::{
+    entities::{AuthState, Comment, CommentID, PaymentID},
+    usecases::{UseCase, UseCaseError},
+};
+use tracing::instrument;
+
+impl UseCase {
+    /// Visible to the participants of the group the payment belongs to.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_comment(
+        &self,
+        auth: &AuthState,
+        id: &CommentID,
+    ) -> Result<Option<Comment>, UseCaseError> {
+        if let Some(comment) = self.repository.get_comment(id).await? {
+            let comment = self
+                .get_payment(auth, &comment.payment)
+                .await?
+                .and(Some(comment));
+            Ok(comment)
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_comments_by_payment(
+        &self,
+        auth: &AuthState,
+        id: &PaymentID,
+    ) -> Result<Vec<Comment>, UseCaseError> {
+        if self.get_payment(auth, id).await?.is_some() {
+            let comments = self.repository.get_comments_by_payment(id).await?;
+            Ok(comments)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+
+    /// Batch form of [`UseCase::get_comments_by_payment`]. Comments on
+    /// payments the caller cannot see are left out.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_comments_by_payments(
+        &self,
+        auth: &AuthState,
+        ids: &[PaymentID],
+    ) -> Result<Vec<Comment>, UseCaseError> {
+        let payments: Vec<PaymentID> = self
+            .get_payments_by_ids(auth, ids)
+            .await?
+            .into_iter()
+            .map(|payment| payment.id)
+            .collect();
+        if payments.is_empty() {
+            return Ok(vec![]);
+        }
+        let comments = self.repository.get_comments_by_payments(&payments).await?;
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{AuthError, Claims, Group, Payment, UserID},
+        repositories::MockRepository,
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn get_comments_by_payment_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let comment: Comment = Faker.fake();
+        let id = comment.payment.clone();
+        let comments = vec![comment.clone()];
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comments_by_payment()
+            .returning(move |_| Ok(comments.clone()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_comments_by_payment(&auth, &id).await.unwrap();
+        assert_eq!(get, vec![comment]);
+    }
+
+    #[tokio::test]
+    async fn get_comments_by_payment_401() {
+        let group: Group = Faker.fake();
+        let comment: Comment = Faker.fake();
+        let id = comment.payment.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comments_by_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let get = usecase.get_comments_by_payment(&auth, &id).await;
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_comment_403() {
+        let claims: Claims = Faker.fake();
+        let group: Group = Faker.fake();
+        let comment: Comment = Faker.fake();
+        let id = comment.id.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_comment(&auth, &id).await;
+        assert_eq!(get, Err(UseCaseError::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn get_comments_by_payments_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let foreign: Payment = Faker.fake();
+        let mut comment: Comment = Faker.fake();
+        comment.payment = payment.id.clone();
+        let ids = vec![payment.id.clone(), foreign.id.clone()];
+        let expected = vec![comment.clone()];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_payments_by_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![payment.clone(), foreign.clone()]));
+        mock.expect_get_groups_by_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![group.clone()]));
+        mock.expect_get_comments_by_payments()
+            .withf(|payments| payments.len() == 1)
+            .times(1)
+            .returning(move |_| Ok(vec![comment.clone()]));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_comments_by_payments(&auth, &ids).await;
+        assert_eq!(get, Ok(expected));
+    }
+}
@@ -0,0 +1,223 @@
+use super::create::check_body;
+use crate::{
+    entities::{AuthState, Comment, CommentID, UserID},
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use chrono::Utc;
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct UpdateCommentInput {
+    pub id: CommentID,
+    pub body: String,
+}
+
+impl UseCase {
+    /// Only the author can edit a comment.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn update_comment(
+        &self,
+        auth: &AuthState,
+        input: UpdateCommentInput,
+    ) -> Result<Comment, UseCaseError> {
+        let comment = self.get_own_comment(auth, &input.id).await?;
+        let errors = check_body(&input.body);
+        if !errors.is_empty() {
+            Err(UseCaseError::InvalidInput(errors))?
+        }
+
+        let comment = Comment {
+            updated_at: Utc::now(),
+            body: input.body,
+            ..comment
+        };
+        let comment = self.repository.update_comment(comment).await?;
+        Ok(comment)
+    }
+
+    /// The comment with `id`, if it exists and was written by the caller.
+    pub(super) async fn get_own_comment(
+        &self,
+        auth: &AuthState,
+        id: &CommentID,
+    ) -> Result<Comment, UseCaseError> {
+        let comment = self
+            .get_comment(auth, id)
+            .await?
+            .ok_or(UseCaseError::NotFound)?;
+        if let AuthState::Authorized(claims) = auth {
+            if comment.author == UserID::new(&claims.sub) {
+                Ok(comment)
+            } else {
+                Err(UseCaseError::Forbidden)?
+            }
+        } else {
+            Err(UseCaseError::from(auth))?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Group, Payment},
+        repositories::{MockRepository, RepositoryError},
+        usecases::FieldError,
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn update_comment_200() {
+        let claims: Claims = Faker.fake();
+        let mut comment: Comment = Faker.fake();
+        comment.author = UserID::new(&claims.sub);
+        let input = UpdateCommentInput {
+            id: comment.id.clone(),
+            body: "Actually it was four of us.".to_string(),
+        };
+        let (id, created_at) = (comment.id.clone(), comment.created_at);
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+        mock.expect_update_comment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_comment(&auth, input).await.unwrap();
+        assert_eq!(update.id, id);
+        assert_eq!(update.body, "Actually it was four of us.");
+        assert_eq!(update.created_at, created_at);
+    }
+
+    #[tokio::test]
+    async fn update_comment_400() {
+        let claims: Claims = Faker.fake();
+        let mut comment: Comment = Faker.fake();
+        comment.author = UserID::new(&claims.sub);
+        let input = UpdateCommentInput {
+            id: comment.id.clone(),
+            body: String::new(),
+        };
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+        mock.expect_update_comment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_comment(&auth, input).await;
+        assert_eq!(
+            update,
+            Err(UseCaseError::InvalidInput(vec![FieldError::new(
+                "body",
+                "must not be empty"
+            )]))
+        );
+    }
+
+    #[tokio::test]
+    async fn update_comment_403() {
+        let claims: Claims = Faker.fake();
+        let comment: Comment = Faker.fake();
+        let input = UpdateCommentInput {
+            id: comment.id.clone(),
+            body: "Not mine to edit.".to_string(),
+        };
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+        mock.expect_update_comment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_comment(&auth, input).await;
+        assert_eq!(update, Err(UseCaseError::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn update_comment_404() {
+        let claims: Claims = Faker.fake();
+        let input: UpdateCommentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_comment().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_comment(&auth, input).await;
+        assert_eq!(update, Err(UseCaseError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn update_comment_500() {
+        let claims: Claims = Faker.fake();
+        let mut comment: Comment = Faker.fake();
+        comment.author = UserID::new(&claims.sub);
+        let input = UpdateCommentInput {
+            id: comment.id.clone(),
+            body: "Fixed a typo.".to_string(),
+        };
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.id = comment.payment.clone();
+        payment.group = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_comment()
+            .returning(move |_| Ok(Some(comment.clone())));
+        mock.expect_update_comment()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_comment(&auth, input).await;
+        assert_eq!(update, Err(UseCaseError::InternalServerError));
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
-    entities::{AuthState, Group, GroupID, UserID},
-    usecases::{UseCase, UseCaseError},
+    entities::{default_categories, AuthState, Group, GroupID, Household, UserID},
+    usecases::{FieldError, UseCase, UseCaseError},
 };
 use async_graphql::InputObject;
 use chrono::Utc;
 use nanoid::nanoid;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -13,46 +14,182 @@ use fake::Dummy;
 #[cfg_attr(test, derive(Dummy))]
 pub struct CreateGroupInput {
     pub title: String,
+    /// Categories payments can be filed under; a default set when omitted.
+    pub categories: Option<Vec<String>>,
+    /// Whether debtors must confirm their share of a payment; off by default.
+    #[graphql(default)]
+    pub require_confirmation: bool,
+    /// Participants that settle as one unit. Only the creator participates
+    /// in a new group, so this can list no one else yet.
+    #[graphql(default)]
+    #[cfg_attr(test, dummy(default))]
+    pub households: Vec<HouseholdInput>,
+    /// Retrying with the same key returns the group created by the first request.
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct HouseholdInput {
+    pub name: String,
+    pub members: Vec<UserID>,
+    /// Defaults to the first member.
+    pub representative: Option<UserID>,
+}
+
+impl From<HouseholdInput> for Household {
+    fn from(input: HouseholdInput) -> Self {
+        Household {
+            name: input.name,
+            members: input.members,
+            representative: input.representative,
+        }
+    }
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn create_group(
         &self,
         auth: &AuthState,
         input: CreateGroupInput,
     ) -> Result<Group, UseCaseError> {
         if let AuthState::Authorized(claims) = auth {
+            let categories = input.categories.unwrap_or_else(default_categories);
+            let participants = vec![UserID::new(&claims.sub)];
+            let households: Vec<Household> =
+                input.households.into_iter().map(Household::from).collect();
+            let mut errors = check_categories(&categories);
+            errors.extend(check_households(&households, &participants));
+            if !errors.is_empty() {
+                Err(UseCaseError::InvalidInput(errors))?
+            }
             let now = Utc::now();
             let group = Group {
                 id: GroupID::new(nanoid!()),
                 created_at: now,
                 updated_at: now,
                 title: input.title,
-                participants: vec![UserID::new(&claims.sub)],
+                participants,
+                categories,
+                require_confirmation: input.require_confirmation,
+                households,
+                version: 0,
+            };
+            let key = input.idempotency_key.as_deref();
+            let id = group.id.to_string();
+            if let Some(id) = self
+                .claim_idempotency_key(auth, key, "createGroup", None, &id)
+                .await?
+            {
+                return self
+                    .get_group(auth, &GroupID::new(id))
+                    .await?
+                    .ok_or(UseCaseError::Conflict);
+            }
+            let group = match self.repository.create_group(group).await {
+                Ok(group) => group,
+                Err(err) => {
+                    self.release_idempotency_key(auth, key).await;
+                    Err(err)?
+                }
             };
-            let group = self
-                .repository
-                .create_group(group)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
             Ok(group)
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
         }
     }
 }
 
+/// Category names must be non-blank and unique within a group.
+pub(super) fn check_categories(categories: &[String]) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for (i, category) in categories.iter().enumerate() {
+        if category.trim().is_empty() {
+            errors.push(FieldError::new(
+                format!("categories[{i}]"),
+                "must not be empty",
+            ));
+        } else if categories[..i].contains(category) {
+            errors.push(FieldError::new(
+                format!("categories[{i}]"),
+                "is listed more than once",
+            ));
+        }
+    }
+    errors
+}
+
+/// Households must be named uniquely and made of participants, each of whom
+/// belongs to at most one household.
+pub(super) fn check_households(
+    households: &[Household],
+    participants: &[UserID],
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for (i, household) in households.iter().enumerate() {
+        let field = format!("households[{i}]");
+        if household.name.trim().is_empty() {
+            errors.push(FieldError::new(
+                format!("{field}.name"),
+                "must not be empty",
+            ));
+        } else if households[..i].iter().any(|h| h.name == household.name) {
+            errors.push(FieldError::new(
+                format!("{field}.name"),
+                "is listed more than once",
+            ));
+        }
+        if household.members.is_empty() {
+            errors.push(FieldError::new(
+                format!("{field}.members"),
+                "must not be empty",
+            ));
+        }
+        for (j, member) in household.members.iter().enumerate() {
+            if !participants.contains(member) {
+                errors.push(FieldError::new(
+                    format!("{field}.members[{j}]"),
+                    "is not a participant of the group",
+                ));
+            } else if household.members[..j].contains(member)
+                || households[..i].iter().any(|h| h.members.contains(member))
+            {
+                errors.push(FieldError::new(
+                    format!("{field}.members[{j}]"),
+                    "already belongs to a household",
+                ));
+            }
+        }
+        if let Some(representative) = &household.representative {
+            if !household.members.contains(representative) {
+                errors.push(FieldError::new(
+                    format!("{field}.representative"),
+                    "must be a member of the household",
+                ));
+            }
+        }
+    }
+    errors
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{entities::Claims, repositories::MockRepository};
+    use crate::{
+        entities::{AuthError, Claims, IdempotencyKey},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use chrono::Duration;
     use fake::{Fake, Faker};
     use std::sync::Arc;
 
     #[tokio::test]
     async fn create_group_200() {
         let claims: Claims = Faker.fake();
-        let input: CreateGroupInput = Faker.fake();
+        let mut input: CreateGroupInput = Faker.fake();
+        input.categories = None;
+        input.idempotency_key = None;
         let group: Group = Faker.fake();
         let id = group.id.clone();
 
@@ -67,6 +204,60 @@ mod tests {
         assert_eq!(create.id, id);
     }
 
+    #[tokio::test]
+    async fn create_group_200_replay() {
+        let claims: Claims = Faker.fake();
+        let mut input: CreateGroupInput = Faker.fake();
+        input.categories = None;
+        input.idempotency_key = Some("retry".to_string());
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut key: IdempotencyKey = Faker.fake();
+        key.operation = "createGroup".to_string();
+        key.entity = group.id.to_string();
+        key.group = None;
+        key.expires_at = Utc::now() + Duration::hours(1);
+        let id = group.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_idempotency_key()
+            .returning(|_| Err(RepositoryError::Conflict("duplicate key".to_string())));
+        mock.expect_get_idempotency_key()
+            .returning(move |_, _| Ok(Some(key.clone())));
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_group().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_group(&auth, input).await.unwrap();
+        assert_eq!(create.id, id);
+    }
+
+    #[tokio::test]
+    async fn create_group_400() {
+        let claims: Claims = Faker.fake();
+        let mut input: CreateGroupInput = Faker.fake();
+        input.categories = Some(vec![
+            "food".to_string(),
+            " ".to_string(),
+            "food".to_string(),
+        ]);
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_group(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = create else {
+            panic!("expected invalid input, got {create:?}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["categories[1]", "categories[2]"]);
+    }
+
     #[tokio::test]
     async fn create_group_401() {
         let input: CreateGroupInput = Faker.fake();
@@ -74,20 +265,29 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let create = usecase.create_group(&auth, input).await;
-        assert_eq!(create, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            create,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
     }
 
     #[tokio::test]
     async fn create_group_500() {
         let claims: Claims = Faker.fake();
-        let input: CreateGroupInput = Faker.fake();
+        let mut input: CreateGroupInput = Faker.fake();
+        input.categories = None;
+        input.idempotency_key = Some("retry".to_string());
 
         let mut mock = MockRepository::new();
+        mock.expect_create_idempotency_key().returning(Ok);
+        mock.expect_delete_idempotency_key()
+            .times(1)
+            .returning(|_, _| Ok(()));
         mock.expect_create_group()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -4,6 +4,7 @@ use crate::{
 };
 use async_graphql::InputObject;
 use futures::future::try_join_all;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -15,41 +16,65 @@ pub struct DeleteGroupInput {
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn delete_group(
         &self,
         auth: &AuthState,
         input: DeleteGroupInput,
     ) -> Result<GroupID, UseCaseError> {
         if self.get_group(auth, &input.id).await?.is_some() {
-            self.repository
-                .delete_group(&input.id)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+            self.repository.delete_group(&input.id).await?;
             try_join_all(
                 self.repository
                     .get_payments_by_group(&input.id)
-                    .await
-                    .or(Err(UseCaseError::InternalServerError))?
+                    .await?
                     .iter()
                     .map(|payment| async {
                         self.repository
                             .delete_payment(&payment.id)
                             .await
-                            .or(Err(UseCaseError::InternalServerError))
+                            .map_err(UseCaseError::from)
                     }),
             )
             .await?;
             try_join_all(
                 self.repository
                     .get_notifications_by_group(&input.id)
-                    .await
-                    .or(Err(UseCaseError::InternalServerError))?
+                    .await?
                     .iter()
                     .map(|payment| async {
                         self.repository
                             .delete_notification(&payment.id)
                             .await
-                            .or(Err(UseCaseError::InternalServerError))
+                            .map_err(UseCaseError::from)
+                    }),
+            )
+            .await?;
+            try_join_all(
+                self.repository
+                    .get_recurring_payments_by_group(&input.id)
+                    .await?
+                    .iter()
+                    .map(|recurring_payment| async {
+                        self.repository
+                            .delete_recurring_payment(&recurring_payment.id)
+                            .await
+                            .map_err(UseCaseError::from)
+                    }),
+            )
+            .await?;
+            let attachments = self.repository.get_attachments_by_group(&input.id).await?;
+            self.remove_attachments(attachments).await?;
+            try_join_all(
+                self.repository
+                    .get_comments_by_group(&input.id)
+                    .await?
+                    .iter()
+                    .map(|comment| async {
+                        self.repository
+                            .delete_comment(&comment.id)
+                            .await
+                            .map_err(UseCaseError::from)
                     }),
             )
             .await?;
@@ -64,8 +89,10 @@ impl UseCase {
 mod tests {
     use super::*;
     use crate::{
-        entities::{Claims, Group, Notification, Payment, UserID},
-        repositories::MockRepository,
+        entities::{
+            Attachment, Claims, Comment, Group, Notification, Payment, RecurringPayment, UserID,
+        },
+        repositories::{MockRepository, RepositoryError},
     };
     use fake::{Fake, Faker};
     use std::sync::Arc;
@@ -82,6 +109,15 @@ mod tests {
         let payments: Vec<Payment> = Faker.fake();
 
         let notifications: Vec<Notification> = Faker.fake();
+        let recurring_payments: Vec<RecurringPayment> = Faker.fake();
+        let comments: Vec<Comment> = Faker.fake();
+        let attachments: Vec<Attachment> = (0..3)
+            .map(|i| {
+                let mut attachment: Attachment = Faker.fake();
+                attachment.key = format!("group/payment/{i}");
+                attachment
+            })
+            .collect();
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
@@ -97,6 +133,21 @@ mod tests {
             .returning(move |_| Ok(payments.clone()));
         mock.expect_get_notifications_by_group()
             .returning(move |_| Ok(notifications.clone()));
+        mock.expect_delete_recurring_payment()
+            .times(recurring_payments.len())
+            .returning(move |_| Ok(()));
+        mock.expect_get_recurring_payments_by_group()
+            .returning(move |_| Ok(recurring_payments.clone()));
+        mock.expect_delete_attachment()
+            .times(attachments.len())
+            .returning(move |_| Ok(()));
+        mock.expect_get_attachments_by_group()
+            .returning(move |_| Ok(attachments.clone()));
+        mock.expect_delete_comment()
+            .times(comments.len())
+            .returning(move |_| Ok(()));
+        mock.expect_get_comments_by_group()
+            .returning(move |_| Ok(comments.clone()));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -131,7 +182,7 @@ mod tests {
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_delete_group()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
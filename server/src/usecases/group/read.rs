@@ -1,9 +1,11 @@
 use crate::{
-    entities::{AuthState, Group, GroupID, UserID},
+    entities::{AuthState, Group, GroupID, Page, PageRequest, UserID},
     usecases::{UseCase, UseCaseError},
 };
+use tracing::instrument;
 
 impl UseCase {
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_group(
         &self,
         auth: &AuthState,
@@ -14,8 +16,7 @@ impl UseCase {
             let group = self
                 .repository
                 .get_group(id)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?
+                .await?
                 .map(|group| {
                     group
                         .participants
@@ -26,10 +27,11 @@ impl UseCase {
                 .transpose()?;
             Ok(group)
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
         }
     }
 
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_groups_by_user(
         &self,
         auth: &AuthState,
@@ -37,17 +39,60 @@ impl UseCase {
     ) -> Result<Vec<Group>, UseCaseError> {
         if let AuthState::Authorized(claims) = auth {
             if id == &UserID::new(&claims.sub) {
-                let groups = self
-                    .repository
-                    .get_groups_by_user(id)
-                    .await
-                    .or(Err(UseCaseError::InternalServerError))?;
+                let groups = self.repository.get_groups_by_user(id).await?;
                 Ok(groups)
             } else {
                 Err(UseCaseError::Forbidden)?
             }
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_groups_page_by_user(
+        &self,
+        auth: &AuthState,
+        id: &UserID,
+        page: PageRequest,
+    ) -> Result<Page<Group>, UseCaseError> {
+        if let AuthState::Authorized(claims) = auth {
+            if id == &UserID::new(&claims.sub) {
+                let page = page.normalize().ok_or(UseCaseError::BadRequest)?;
+                let groups = self.repository.get_groups_page_by_user(id, &page).await?;
+                Ok(groups)
+            } else {
+                Err(UseCaseError::Forbidden)?
+            }
+        } else {
+            Err(UseCaseError::from(auth))?
+        }
+    }
+
+    /// Batch form of [`UseCase::get_group`]: missing groups and groups that do
+    /// not include the caller are skipped, so one foreign key does not fail
+    /// the others batched with it.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_groups_by_ids(
+        &self,
+        auth: &AuthState,
+        ids: &[GroupID],
+    ) -> Result<Vec<Group>, UseCaseError> {
+        if let AuthState::Authorized(claims) = auth {
+            if ids.is_empty() {
+                return Ok(vec![]);
+            }
+            let user = UserID::new(&claims.sub);
+            let groups = self
+                .repository
+                .get_groups_by_ids(ids)
+                .await?
+                .into_iter()
+                .filter(|group| group.participants.contains(&user))
+                .collect();
+            Ok(groups)
+        } else {
+            Err(UseCaseError::from(auth))?
         }
     }
 }
@@ -55,7 +100,10 @@ impl UseCase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{entities::Claims, repositories::MockRepository};
+    use crate::{
+        entities::{AuthError, Claims, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+        repositories::{MockRepository, RepositoryError},
+    };
     use fake::{Fake, Faker};
     use std::sync::Arc;
 
@@ -84,10 +132,13 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let get = usecase.get_group(&auth, &group).await;
-        assert_eq!(get, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
     }
 
     #[tokio::test]
@@ -115,7 +166,7 @@ mod tests {
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -149,10 +200,13 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let get = usecase.get_groups_by_user(&auth, &user).await;
-        assert_eq!(get, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
     }
 
     #[tokio::test]
@@ -176,7 +230,7 @@ mod tests {
 
         let mut mock = MockRepository::new();
         mock.expect_get_groups_by_user()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -184,4 +238,119 @@ mod tests {
         let get = usecase.get_groups_by_user(&auth, &user).await;
         assert_eq!(get, Err(UseCaseError::InternalServerError));
     }
+
+    #[tokio::test]
+    async fn get_groups_page_by_user_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let groups: Vec<Group> = Faker.fake();
+        let len = groups.len();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_groups_page_by_user()
+            .withf(|_, page| page.first == Some(DEFAULT_PAGE_SIZE))
+            .returning(move |_, _| {
+                Ok(Page {
+                    items: groups.clone(),
+                    has_previous_page: false,
+                    has_next_page: false,
+                })
+            });
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .get_groups_page_by_user(&auth, &user, PageRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(get.items.len(), len);
+    }
+
+    #[tokio::test]
+    async fn get_groups_page_by_user_400() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let page = PageRequest {
+            first: Some(MAX_PAGE_SIZE + 1),
+            ..Default::default()
+        };
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_groups_page_by_user(&auth, &user, page).await;
+        assert_eq!(get, Err(UseCaseError::BadRequest));
+    }
+
+    #[tokio::test]
+    async fn get_groups_page_by_user_403() {
+        let claims: Claims = Faker.fake();
+        let user: UserID = Faker.fake();
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .get_groups_page_by_user(&auth, &user, PageRequest::default())
+            .await;
+        assert_eq!(get, Err(UseCaseError::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn get_groups_by_ids_200() {
+        let claims: Claims = Faker.fake();
+        let mut member: Group = Faker.fake();
+        member.participants.push(UserID::new(&claims.sub));
+        let stranger: Group = Faker.fake();
+        let ids = vec![member.id.clone(), stranger.id.clone()];
+        let expected = vec![member.id.clone()];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_groups_by_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![member.clone(), stranger.clone()]));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_groups_by_ids(&auth, &ids).await.unwrap();
+        assert_eq!(get.into_iter().map(|g| g.id).collect::<Vec<_>>(), expected);
+    }
+
+    #[tokio::test]
+    async fn get_groups_by_ids_401() {
+        let ids: Vec<GroupID> = Faker.fake();
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let get = usecase.get_groups_by_ids(&auth, &ids).await;
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_groups_by_ids_500() {
+        let claims: Claims = Faker.fake();
+        let ids = vec![Faker.fake::<GroupID>()];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_groups_by_ids()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_groups_by_ids(&auth, &ids).await;
+        assert_eq!(get, Err(UseCaseError::InternalServerError));
+    }
 }
@@ -1,9 +1,11 @@
+use super::create::{check_categories, check_households};
 use crate::{
-    entities::{AuthState, Group, GroupID, UserID},
-    usecases::{UseCase, UseCaseError},
+    entities::{AuthState, Group, GroupID, Household, UserID},
+    usecases::{HouseholdInput, UseCase, UseCaseError},
 };
 use async_graphql::InputObject;
 use chrono::Utc;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -15,27 +17,62 @@ pub struct UpdateGroupInput {
 
     pub title: Option<String>,
     pub participants: Option<Vec<UserID>>,
+    /// Replaces the categories. Payments filed under a removed category are
+    /// reported by `validatePayments` until they are recategorized.
+    pub categories: Option<Vec<String>>,
+    /// Payments entered before confirmation was required stay pending until
+    /// their debtors confirm them.
+    pub require_confirmation: Option<bool>,
+    /// Replaces the households.
+    #[cfg_attr(test, dummy(default))]
+    pub households: Option<Vec<HouseholdInput>>,
+
+    /// Version the client last read. The update is rejected if the group changed since.
+    pub expected_version: Option<i32>,
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn update_group(
         &self,
         auth: &AuthState,
         input: UpdateGroupInput,
     ) -> Result<Group, UseCaseError> {
         if let Some(group) = self.get_group(auth, &input.id).await? {
+            if input
+                .expected_version
+                .is_some_and(|version| version != group.version)
+            {
+                Err(UseCaseError::Conflict)?
+            }
+            let mut errors = Vec::new();
+            if let Some(categories) = &input.categories {
+                errors.extend(check_categories(categories));
+            }
+            let participants = input.participants.unwrap_or(group.participants);
+            let households: Vec<Household> = match input.households {
+                Some(households) => households.into_iter().map(Household::from).collect(),
+                None => group.households,
+            };
+            // Removing a participant must not leave them in a household.
+            errors.extend(check_households(&households, &participants));
+            if !errors.is_empty() {
+                Err(UseCaseError::InvalidInput(errors))?
+            }
             let group = Group {
                 id: input.id,
                 created_at: group.created_at,
                 updated_at: Utc::now(),
                 title: input.title.unwrap_or(group.title),
-                participants: input.participants.unwrap_or(group.participants),
+                participants,
+                categories: input.categories.unwrap_or(group.categories),
+                require_confirmation: input
+                    .require_confirmation
+                    .unwrap_or(group.require_confirmation),
+                households,
+                version: group.version,
             };
-            let group = self
-                .repository
-                .update_group(group)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+            let group = self.repository.update_group(group).await?;
             Ok(group)
         } else {
             Err(UseCaseError::NotFound)?
@@ -46,16 +83,22 @@ impl UseCase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{entities::Claims, repositories::MockRepository};
+    use crate::{
+        entities::Claims,
+        repositories::{MockRepository, RepositoryError},
+        usecases::FieldError,
+    };
     use fake::{Fake, Faker};
     use std::sync::Arc;
 
     #[tokio::test]
     async fn update_group_200() {
         let claims: Claims = Faker.fake();
-        let input: UpdateGroupInput = Faker.fake();
+        let mut input: UpdateGroupInput = Faker.fake();
         let mut group1: Group = Faker.fake();
         group1.participants.push(UserID::new(&claims.sub));
+        input.categories = None;
+        input.expected_version = Some(group1.version);
         let group2 = group1.clone();
         let id = group1.id.clone();
 
@@ -72,6 +115,42 @@ mod tests {
         assert_eq!(update.id, id);
     }
 
+    #[tokio::test]
+    async fn update_group_400() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let partner = UserID::new("partner");
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), partner.clone()];
+        group.households = vec![Household {
+            name: "us".to_string(),
+            members: vec![user.clone(), partner.clone()],
+            representative: None,
+        }];
+        let mut input: UpdateGroupInput = Faker.fake();
+        input.id = group.id.clone();
+        input.categories = None;
+        input.expected_version = None;
+        input.participants = Some(vec![user.clone()]);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_update_group().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_group(&auth, input).await;
+        assert_eq!(
+            update,
+            Err(UseCaseError::InvalidInput(vec![FieldError::new(
+                "households[0].members[1]",
+                "is not a participant of the group"
+            )]))
+        );
+    }
+
     #[tokio::test]
     async fn update_group_404() {
         let claims: Claims = Faker.fake();
@@ -87,10 +166,31 @@ mod tests {
         assert_eq!(update, Err(UseCaseError::NotFound));
     }
 
+    #[tokio::test]
+    async fn update_group_409() {
+        let claims: Claims = Faker.fake();
+        let mut input: UpdateGroupInput = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        input.expected_version = Some(group.version.wrapping_sub(1));
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_group(&auth, input).await;
+        assert_eq!(update, Err(UseCaseError::Conflict));
+    }
+
     #[tokio::test]
     async fn update_group_500() {
         let claims: Claims = Faker.fake();
-        let input: UpdateGroupInput = Faker.fake();
+        let mut input: UpdateGroupInput = Faker.fake();
+        input.categories = None;
+        input.expected_version = None;
         let mut group: Group = Faker.fake();
         group.participants.push(UserID::new(&claims.sub));
 
@@ -98,7 +198,7 @@ mod tests {
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_update_group()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -0,0 +1,37 @@
+use crate::usecases::{UseCase, UseCaseError};
+use tracing::instrument;
+
+impl UseCase {
+    #[instrument(skip(self), err(level = "debug"))]
+    pub async fn ping(&self) -> Result<(), UseCaseError> {
+        self.repository.ping().await.map_err(UseCaseError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::{MockRepository, RepositoryError};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn ping_200() {
+        let mut mock = MockRepository::new();
+        mock.expect_ping().returning(|| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+
+        assert_eq!(usecase.ping().await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn ping_500() {
+        let mut mock = MockRepository::new();
+        mock.expect_ping()
+            .returning(|| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+
+        assert_eq!(usecase.ping().await, Err(UseCaseError::InternalServerError));
+    }
+}
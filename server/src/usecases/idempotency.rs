@@ -0,0 +1,187 @@
+use crate::{
+    entities::{AuthState, GroupID, IdempotencyKey, UserID},
+    repositories::RepositoryError,
+    usecases::{UseCase, UseCaseError},
+};
+use chrono::Utc;
+use tracing::{instrument, warn};
+
+impl UseCase {
+    /// Reserves `key` for the entity about to be created with `id`. Returns the
+    /// ID of the entity created earlier under the same key instead, if any.
+    /// Without a key every request creates a new entity. Reusing a key for
+    /// another operation or group is a bad request.
+    #[instrument(skip(self, auth, key, id), err(level = "debug"))]
+    pub(crate) async fn claim_idempotency_key(
+        &self,
+        auth: &AuthState,
+        key: Option<&str>,
+        operation: &str,
+        group: Option<&GroupID>,
+        id: &str,
+    ) -> Result<Option<String>, UseCaseError> {
+        let Some(key) = key else {
+            return Ok(None);
+        };
+        let AuthState::Authorized(claims) = auth else {
+            Err(UseCaseError::from(auth))?
+        };
+        let now = Utc::now();
+        let record = IdempotencyKey {
+            user: UserID::new(&claims.sub),
+            key: key.to_string(),
+            operation: operation.to_string(),
+            entity: id.to_string(),
+            group: group.cloned(),
+            created_at: now,
+            expires_at: now + self.idempotency_window,
+        };
+
+        match self.repository.create_idempotency_key(record.clone()).await {
+            Ok(_) => return Ok(None),
+            Err(RepositoryError::Conflict(_)) => {}
+            Err(err) => Err(err)?,
+        }
+        let existing = self
+            .repository
+            .get_idempotency_key(&record.user, key)
+            .await?;
+        match existing {
+            Some(existing) if existing.expires_at > now => {
+                if existing.operation != operation || existing.group.as_ref() != group {
+                    Err(UseCaseError::BadRequest)?
+                }
+                Ok(Some(existing.entity))
+            }
+            expired => {
+                // The key outlived its window but has not been purged yet.
+                if expired.is_some() {
+                    match self
+                        .repository
+                        .delete_idempotency_key(&record.user, key)
+                        .await
+                    {
+                        Ok(()) | Err(RepositoryError::NotFound) => {}
+                        Err(err) => Err(err)?,
+                    }
+                }
+                self.repository.create_idempotency_key(record).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Frees a key claimed for a create that failed, so that a retry can succeed.
+    pub(crate) async fn release_idempotency_key(&self, auth: &AuthState, key: Option<&str>) {
+        let (Some(key), AuthState::Authorized(claims)) = (key, auth) else {
+            return;
+        };
+        let user = UserID::new(&claims.sub);
+        if let Err(err) = self.repository.delete_idempotency_key(&user, key).await {
+            warn!(%err, "failed to release idempotency key");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::Claims, repositories::MockRepository};
+    use chrono::Duration;
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn claim_idempotency_key_200() {
+        let claims: Claims = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_idempotency_key().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let claim = usecase
+            .claim_idempotency_key(&auth, Some("retry"), "createGroup", None, "new")
+            .await;
+        assert_eq!(claim, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn claim_idempotency_key_replay() {
+        let claims: Claims = Faker.fake();
+        let mut existing: IdempotencyKey = Faker.fake();
+        existing.operation = "createGroup".to_string();
+        existing.group = None;
+        existing.entity = "old".to_string();
+        existing.expires_at = Utc::now() + Duration::hours(1);
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_idempotency_key()
+            .returning(|_| Err(RepositoryError::Conflict("duplicate key".to_string())));
+        mock.expect_get_idempotency_key()
+            .returning(move |_, _| Ok(Some(existing.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let claim = usecase
+            .claim_idempotency_key(&auth, Some("retry"), "createGroup", None, "new")
+            .await;
+        assert_eq!(claim, Ok(Some("old".to_string())));
+    }
+
+    #[tokio::test]
+    async fn claim_idempotency_key_expired() {
+        let claims: Claims = Faker.fake();
+        let mut existing: IdempotencyKey = Faker.fake();
+        existing.operation = "createGroup".to_string();
+        existing.group = None;
+        existing.expires_at = Utc::now() - Duration::hours(1);
+
+        let mut mock = MockRepository::new();
+        let mut conflict = true;
+        mock.expect_create_idempotency_key().returning(move |key| {
+            if std::mem::take(&mut conflict) {
+                Err(RepositoryError::Conflict("duplicate key".to_string()))
+            } else {
+                Ok(key)
+            }
+        });
+        mock.expect_get_idempotency_key()
+            .returning(move |_, _| Ok(Some(existing.clone())));
+        mock.expect_delete_idempotency_key()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let claim = usecase
+            .claim_idempotency_key(&auth, Some("retry"), "createGroup", None, "new")
+            .await;
+        assert_eq!(claim, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn claim_idempotency_key_400() {
+        let claims: Claims = Faker.fake();
+        let mut existing: IdempotencyKey = Faker.fake();
+        existing.operation = "createPayment".to_string();
+        existing.expires_at = Utc::now() + Duration::hours(1);
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_idempotency_key()
+            .returning(|_| Err(RepositoryError::Conflict("duplicate key".to_string())));
+        mock.expect_get_idempotency_key()
+            .returning(move |_, _| Ok(Some(existing.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let claim = usecase
+            .claim_idempotency_key(&auth, Some("retry"), "createGroup", None, "new")
+            .await;
+        assert_eq!(claim, Err(UseCaseError::BadRequest));
+    }
+}
@@ -1,34 +1,85 @@
+mod access_token;
+mod attachment;
+mod comment;
 mod group;
+mod health;
+mod idempotency;
 mod notification;
 mod payment;
+mod recurring_payment;
 mod user;
 
+pub use access_token::*;
+pub use attachment::*;
+pub use comment::*;
 pub use group::*;
 pub use notification::*;
 pub use payment::*;
+pub use recurring_payment::*;
 pub use user::*;
 
-use crate::repositories::Repository;
+use crate::{
+    entities::{AuthError, AuthState, PaymentViolation},
+    repositories::{Repository, RepositoryError},
+    storages::{MemoryStorage, Storage, StorageError},
+};
+use chrono::Duration;
+use serde::Serialize;
 use std::sync::Arc;
 use thiserror::Error;
+use tracing::error;
 
+/// How long create mutations remember an idempotency key by default.
+pub const DEFAULT_IDEMPOTENCY_WINDOW: Duration = Duration::hours(24);
+
+/// Largest attachment accepted by default, in bytes.
+pub const DEFAULT_MAX_ATTACHMENT_SIZE: usize = 10 * 1024 * 1024;
+
+#[derive(Clone)]
 pub struct UseCase {
     pub repository: Arc<dyn Repository>,
+    /// Holds attachment content. In memory until [`UseCase::with_storage`] is called.
+    pub storage: Arc<dyn Storage>,
+    pub idempotency_window: Duration,
+    pub max_attachment_size: usize,
 }
 
 impl UseCase {
     pub fn new(repository: Arc<dyn Repository>) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            storage: Arc::new(MemoryStorage::default()),
+            idempotency_window: DEFAULT_IDEMPOTENCY_WINDOW,
+            max_attachment_size: DEFAULT_MAX_ATTACHMENT_SIZE,
+        }
+    }
+
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn with_max_attachment_size(mut self, size: usize) -> Self {
+        self.max_attachment_size = size;
+        self
+    }
+
+    pub fn with_idempotency_window(mut self, window: Duration) -> Self {
+        self.idempotency_window = window;
+        self
     }
 }
 
-#[derive(Debug, Error, PartialEq, Eq)]
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
 pub enum UseCaseError {
     #[error("400 bad request")]
     BadRequest,
 
-    #[error("401 unauthorized")]
-    Unauthorized,
+    #[error("400 bad request: invalid input")]
+    InvalidInput(Vec<FieldError>),
+
+    #[error("401 unauthorized: {0}")]
+    Unauthorized(AuthError),
 
     #[error("403 forbidden")]
     Forbidden,
@@ -36,6 +87,78 @@ pub enum UseCaseError {
     #[error("404 not found")]
     NotFound,
 
+    #[error("409 conflict")]
+    Conflict,
+
     #[error("500 internal server error")]
     InternalServerError,
 }
+
+impl UseCaseError {
+    /// Machine-readable code reported to clients alongside the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            UseCaseError::BadRequest => "BAD_REQUEST",
+            UseCaseError::InvalidInput(_) => "INVALID_INPUT",
+            UseCaseError::Unauthorized(reason) => reason.code(),
+            UseCaseError::Forbidden => "FORBIDDEN",
+            UseCaseError::NotFound => "NOT_FOUND",
+            UseCaseError::Conflict => "CONFLICT",
+            UseCaseError::InternalServerError => "INTERNAL_SERVER_ERROR",
+        }
+    }
+}
+
+/// Explains why one input field was rejected. `field` is a path into the
+/// input object, such as `creditors[1].amount`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<&PaymentViolation> for FieldError {
+    fn from(violation: &PaymentViolation) -> Self {
+        FieldError::new(violation.field(), violation.to_string())
+    }
+}
+
+impl From<RepositoryError> for UseCaseError {
+    fn from(err: RepositoryError) -> Self {
+        match err {
+            RepositoryError::NotFound => UseCaseError::NotFound,
+            RepositoryError::Conflict(_) => UseCaseError::Conflict,
+            RepositoryError::Unavailable(_)
+            | RepositoryError::Serialization(_)
+            | RepositoryError::Other(_) => {
+                error!(%err, "repository call failed");
+                UseCaseError::InternalServerError
+            }
+        }
+    }
+}
+
+impl From<StorageError> for UseCaseError {
+    fn from(err: StorageError) -> Self {
+        error!(%err, "storage call failed");
+        UseCaseError::InternalServerError
+    }
+}
+
+impl From<&AuthState> for UseCaseError {
+    fn from(auth: &AuthState) -> Self {
+        match auth {
+            AuthState::Authorized(_) => UseCaseError::Forbidden,
+            AuthState::Unauthorized(reason) => UseCaseError::Unauthorized(*reason),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
 use async_graphql::InputObject;
 use chrono::Utc;
 use nanoid::nanoid;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -17,6 +18,7 @@ pub struct CreateNotificationInput {
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn create_notification(
         &self,
         auth: &AuthState,
@@ -29,13 +31,10 @@ impl UseCase {
                 created_at: now,
                 updated_at: now,
                 message: input.message,
+                recipients: Vec::new(),
                 group: input.group,
             };
-            let notification = self
-                .repository
-                .create_notification(notification)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+            let notification = self.repository.create_notification(notification).await?;
             Ok(notification)
         } else {
             Err(UseCaseError::NotFound)?
@@ -48,7 +47,7 @@ mod tests {
     use super::*;
     use crate::{
         entities::{Claims, Group, UserID},
-        repositories::MockRepository,
+        repositories::{MockRepository, RepositoryError},
     };
     use fake::{Fake, Faker};
     use std::sync::Arc;
@@ -65,7 +64,7 @@ mod tests {
         let mut mock = MockRepository::new();
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
-        mock.expect_create_notification().returning(|n| Ok(n));
+        mock.expect_create_notification().returning(Ok);
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -85,7 +84,7 @@ mod tests {
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_create_notification()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
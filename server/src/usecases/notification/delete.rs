@@ -3,6 +3,7 @@ use crate::{
     usecases::{UseCase, UseCaseError},
 };
 use async_graphql::InputObject;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -14,6 +15,7 @@ pub struct DeleteNotificationInput {
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn delete_notification(
         &self,
         auth: &AuthState,
@@ -22,8 +24,7 @@ impl UseCase {
         if let Some(notification) = self.get_notification(auth, &input.id).await? {
             self.repository
                 .delete_notification(&notification.id)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+                .await?;
             Ok(notification.id)
         } else {
             Err(UseCaseError::NotFound)?
@@ -36,7 +37,7 @@ mod tests {
     use super::*;
     use crate::{
         entities::{Claims, Group, Notification, UserID},
-        repositories::MockRepository,
+        repositories::{MockRepository, RepositoryError},
     };
     use fake::{Fake, Faker};
     use std::sync::Arc;
@@ -44,7 +45,8 @@ mod tests {
     #[tokio::test]
     async fn delete_notification_200() {
         let claims: Claims = Faker.fake();
-        let notification: Notification = Faker.fake();
+        let mut notification: Notification = Faker.fake();
+        notification.recipients = vec![UserID::new(&claims.sub)];
         let input = DeleteNotificationInput {
             id: notification.id.clone(),
         };
@@ -85,7 +87,8 @@ mod tests {
     #[tokio::test]
     async fn delete_notification_500() {
         let claims: Claims = Faker.fake();
-        let notification: Notification = Faker.fake();
+        let mut notification: Notification = Faker.fake();
+        notification.recipients = vec![];
         let input = DeleteNotificationInput {
             id: notification.id.clone(),
         };
@@ -99,7 +102,7 @@ mod tests {
         mock.expect_get_notification()
             .returning(move |_| Ok(Some(notification.clone())));
         mock.expect_delete_notification()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
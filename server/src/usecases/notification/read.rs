@@ -1,41 +1,70 @@
 use crate::{
-    entities::{AuthState, GroupID, Notification, NotificationID},
+    entities::{AuthState, GroupID, Notification, NotificationID, Page, PageRequest, UserID},
     usecases::{UseCase, UseCaseError},
 };
+use tracing::instrument;
 
 impl UseCase {
+    /// Visible to the participants of the group it is addressed to, or only to
+    /// its recipients if it has any.
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_notification(
         &self,
         auth: &AuthState,
         id: &NotificationID,
     ) -> Result<Option<Notification>, UseCaseError> {
-        if let Some(notification) = self
-            .repository
-            .get_notification(id)
-            .await
-            .or(Err(UseCaseError::InternalServerError))?
-        {
-            let notification = self
-                .get_group(auth, &notification.group)
-                .await?
-                .and(Some(notification));
-            Ok(notification)
+        if let Some(notification) = self.repository.get_notification(id).await? {
+            if let (AuthState::Authorized(claims), Some(_)) =
+                (auth, self.get_group(auth, &notification.group).await?)
+            {
+                if notification.is_for(&UserID::new(&claims.sub)) {
+                    Ok(Some(notification))
+                } else {
+                    Err(UseCaseError::Forbidden)?
+                }
+            } else {
+                Ok(None)
+            }
         } else {
             Ok(None)
         }
     }
 
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_notifications_page_by_group(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+        page: PageRequest,
+    ) -> Result<Page<Notification>, UseCaseError> {
+        let page = page.normalize().ok_or(UseCaseError::BadRequest)?;
+        if let (AuthState::Authorized(claims), Some(_)) = (auth, self.get_group(auth, id).await?) {
+            let user = UserID::new(&claims.sub);
+            let notifications = self
+                .repository
+                .get_notifications_page_by_group(id, &user, &page)
+                .await?;
+            Ok(notifications)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_notifications_by_group(
         &self,
         auth: &AuthState,
         id: &GroupID,
     ) -> Result<Vec<Notification>, UseCaseError> {
-        if self.get_group(auth, id).await?.is_some() {
+        if let (AuthState::Authorized(claims), Some(_)) = (auth, self.get_group(auth, id).await?) {
+            let user = UserID::new(&claims.sub);
             let notifications = self
                 .repository
                 .get_notifications_by_group(id)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+                .await?
+                .into_iter()
+                .filter(|notification| notification.is_for(&user))
+                .collect();
             Ok(notifications)
         } else {
             Err(UseCaseError::NotFound)?
@@ -47,8 +76,8 @@ impl UseCase {
 mod tests {
     use super::*;
     use crate::{
-        entities::{Claims, Group, UserID},
-        repositories::MockRepository,
+        entities::{Claims, Group, UserID, MAX_PAGE_SIZE},
+        repositories::{MockRepository, RepositoryError},
     };
     use fake::{Fake, Faker};
     use std::sync::Arc;
@@ -56,7 +85,8 @@ mod tests {
     #[tokio::test]
     async fn get_notification_200() {
         let claims: Claims = Faker.fake();
-        let notification: Notification = Faker.fake();
+        let mut notification: Notification = Faker.fake();
+        notification.recipients = vec![UserID::new(&claims.sub)];
         let mut group: Group = Faker.fake();
         group.id = notification.group.clone();
         group.participants.push(UserID::new(&claims.sub));
@@ -75,6 +105,29 @@ mod tests {
         assert_eq!(get.map(|g| g.id), Some(id));
     }
 
+    #[tokio::test]
+    async fn get_notification_403() {
+        let claims: Claims = Faker.fake();
+        let mut notification: Notification = Faker.fake();
+        notification.recipients = vec![UserID::new("someone else")];
+        let mut group: Group = Faker.fake();
+        group.id = notification.group.clone();
+        group.participants.push(UserID::new(&claims.sub));
+        let id = notification.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_notification()
+            .returning(move |_| Ok(Some(notification.clone())));
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_notification(&auth, &id).await;
+        assert_eq!(get, Err(UseCaseError::Forbidden));
+    }
+
     #[tokio::test]
     async fn get_notification_500() {
         let claims: Claims = Faker.fake();
@@ -82,7 +135,7 @@ mod tests {
 
         let mut mock = MockRepository::new();
         mock.expect_get_notification()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -97,12 +150,17 @@ mod tests {
         let mut group: Group = Faker.fake();
         group.participants.push(UserID::new(&claims.sub));
         let id = group.id.clone();
+        let mut everyone: Notification = Faker.fake();
+        everyone.recipients = vec![];
+        let mut others: Notification = Faker.fake();
+        others.recipients = vec![UserID::new("someone else")];
+        let expected = vec![everyone.id.clone()];
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_get_notifications_by_group()
-            .returning(move |_| Ok(vec![]));
+            .returning(move |_| Ok(vec![everyone.clone(), others.clone()]));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -111,7 +169,7 @@ mod tests {
             .get_notifications_by_group(&auth, &id)
             .await
             .unwrap();
-        assert_eq!(get.len(), 0);
+        assert_eq!(get.into_iter().map(|n| n.id).collect::<Vec<_>>(), expected);
     }
 
     #[tokio::test]
@@ -140,7 +198,7 @@ mod tests {
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_get_notifications_by_group()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -148,4 +206,61 @@ mod tests {
         let get = usecase.get_notifications_by_group(&auth, &id).await;
         assert_eq!(get, Err(UseCaseError::InternalServerError));
     }
+
+    #[tokio::test]
+    async fn get_notifications_page_by_group_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let id = group.id.clone();
+        let user = UserID::new(&claims.sub);
+        let page = PageRequest {
+            last: Some(10),
+            ..Default::default()
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_notifications_page_by_group()
+            .withf(move |_, recipient, page| {
+                recipient == &user && page.first.is_none() && page.last == Some(10)
+            })
+            .returning(move |_, _, _| {
+                Ok(Page {
+                    items: vec![],
+                    has_previous_page: false,
+                    has_next_page: false,
+                })
+            });
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .get_notifications_page_by_group(&auth, &id, page)
+            .await
+            .unwrap();
+        assert_eq!(get.items.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn get_notifications_page_by_group_400() {
+        let claims: Claims = Faker.fake();
+        let id: GroupID = Faker.fake();
+        let page = PageRequest {
+            last: Some(MAX_PAGE_SIZE + 1),
+            ..Default::default()
+        };
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .get_notifications_page_by_group(&auth, &id, page)
+            .await;
+        assert_eq!(get, Err(UseCaseError::BadRequest));
+    }
 }
@@ -0,0 +1,473 @@
+use crate::{
+    entities::{
+        AuthState, Notification, NotificationID, Payment, PaymentID, PaymentStatus, UserID,
+    },
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use chrono::Utc;
+use nanoid::nanoid;
+use tracing::{instrument, warn};
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct ConfirmPaymentInput {
+    pub id: PaymentID,
+
+    /// Version the client last read. The answer is rejected if the payment changed since.
+    pub expected_version: Option<i32>,
+}
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct DisputePaymentInput {
+    pub id: PaymentID,
+    /// Shown to the payer.
+    pub reason: Option<String>,
+
+    /// Version the client last read. The answer is rejected if the payment changed since.
+    pub expected_version: Option<i32>,
+}
+
+impl UseCase {
+    /// Confirms the caller's share of a payment and notifies the payer.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn confirm_payment(
+        &self,
+        auth: &AuthState,
+        input: ConfirmPaymentInput,
+    ) -> Result<Payment, UseCaseError> {
+        self.answer_payment(
+            auth,
+            &input.id,
+            input.expected_version,
+            PaymentStatus::Confirmed,
+            None,
+        )
+        .await
+    }
+
+    /// Disputes the caller's share of a payment and notifies the payer.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn dispute_payment(
+        &self,
+        auth: &AuthState,
+        input: DisputePaymentInput,
+    ) -> Result<Payment, UseCaseError> {
+        let reason = input
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+        self.answer_payment(
+            auth,
+            &input.id,
+            input.expected_version,
+            PaymentStatus::Disputed,
+            reason,
+        )
+        .await
+    }
+
+    /// Only debtors can answer, and only in groups that require confirmation.
+    async fn answer_payment(
+        &self,
+        auth: &AuthState,
+        id: &PaymentID,
+        expected_version: Option<i32>,
+        status: PaymentStatus,
+        reason: Option<String>,
+    ) -> Result<Payment, UseCaseError> {
+        let Some(mut payment) = self.repository.get_payment(id).await? else {
+            Err(UseCaseError::NotFound)?
+        };
+        let group = self
+            .get_group(auth, &payment.group)
+            .await?
+            .ok_or(UseCaseError::NotFound)?;
+        let AuthState::Authorized(claims) = auth else {
+            Err(UseCaseError::from(auth))?
+        };
+        if expected_version.is_some_and(|version| version != payment.version) {
+            Err(UseCaseError::Conflict)?
+        }
+        if !group.require_confirmation {
+            Err(UseCaseError::BadRequest)?
+        }
+
+        let now = Utc::now();
+        let user = UserID::new(&claims.sub);
+        if !payment.answer(&user, status, reason.clone(), now) {
+            Err(UseCaseError::Forbidden)?
+        }
+        payment.updated_at = now;
+        let payment = self.repository.update_payment(payment).await?;
+
+        // The answer is already saved, so a failed notification must not fail
+        // the request.
+        let name = match self.repository.get_user(&user).await {
+            Ok(Some(user)) => user.name,
+            _ => "A participant".to_string(),
+        };
+        let message = match (status, &reason) {
+            (PaymentStatus::Disputed, Some(reason)) => {
+                format!(
+                    "{name} disputed their share of \"{}\": {reason}",
+                    payment.title
+                )
+            }
+            (PaymentStatus::Disputed, None) => {
+                format!("{name} disputed their share of \"{}\"", payment.title)
+            }
+            _ => format!("{name} confirmed their share of \"{}\"", payment.title),
+        };
+        let recipients: Vec<UserID> = payment
+            .creditors
+            .iter()
+            .map(|creditor| creditor.user.clone())
+            .filter(|creditor| creditor != &user)
+            .collect();
+        if !recipients.is_empty() {
+            let notification = Notification {
+                id: NotificationID::new(nanoid!()),
+                created_at: now,
+                updated_at: now,
+                message,
+                recipients,
+                group: payment.group.clone(),
+            };
+            if let Err(err) = self.repository.create_notification(notification).await {
+                warn!(%err, id = ?payment.id, "failed to notify payer of answer");
+            }
+        }
+        Ok(payment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Amount, Claims, Group, User},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn confirm_payment_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let payer = UserID::new("payer");
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), payer.clone(), UserID::new("bystander")];
+        group.require_confirmation = true;
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.creditors = vec![Amount {
+            user: payer.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![
+            Amount { user, amount: 500 },
+            Amount {
+                user: payer,
+                amount: 500,
+            },
+        ];
+        payment.confirmations = Vec::new();
+        let input = ConfirmPaymentInput {
+            id: payment.id.clone(),
+            expected_version: Some(payment.version),
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .times(1)
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_user().returning(move |id| {
+            let mut user: User = Faker.fake();
+            user.id = id.clone();
+            Ok(Some(user))
+        });
+        mock.expect_update_payment().returning(Ok);
+        mock.expect_create_notification()
+            .times(1)
+            .withf(|notification| notification.recipients == vec![UserID::new("payer")])
+            .returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims.clone());
+
+        let confirm = usecase.confirm_payment(&auth, input).await.unwrap();
+        let user = UserID::new(&claims.sub);
+        assert_eq!(confirm.status_of(&user), PaymentStatus::Confirmed);
+        assert_eq!(
+            confirm.status_of(&UserID::new("payer")),
+            PaymentStatus::Pending
+        );
+    }
+
+    #[tokio::test]
+    async fn dispute_payment_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let payer = UserID::new("payer");
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), payer.clone(), UserID::new("bystander")];
+        group.require_confirmation = true;
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.creditors = vec![Amount {
+            user: payer.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![
+            Amount { user, amount: 500 },
+            Amount {
+                user: payer,
+                amount: 500,
+            },
+        ];
+        payment.confirmations = Vec::new();
+        let input = DisputePaymentInput {
+            id: payment.id.clone(),
+            reason: Some(" I left before dessert ".to_string()),
+            expected_version: None,
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_user().returning(move |id| {
+            let mut user: User = Faker.fake();
+            user.id = id.clone();
+            Ok(Some(user))
+        });
+        mock.expect_update_payment().returning(Ok);
+        mock.expect_create_notification()
+            .times(1)
+            .withf(|notification| notification.message.ends_with(": I left before dessert"))
+            .returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims.clone());
+
+        let dispute = usecase.dispute_payment(&auth, input).await.unwrap();
+        let user = UserID::new(&claims.sub);
+        assert_eq!(dispute.status_of(&user), PaymentStatus::Disputed);
+        assert_eq!(
+            dispute.confirmations[0].reason.as_deref(),
+            Some("I left before dessert")
+        );
+    }
+
+    #[tokio::test]
+    async fn confirm_payment_400() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let payer = UserID::new("payer");
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), payer.clone(), UserID::new("bystander")];
+        group.require_confirmation = false;
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.creditors = vec![Amount {
+            user: payer.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![
+            Amount { user, amount: 500 },
+            Amount {
+                user: payer,
+                amount: 500,
+            },
+        ];
+        payment.confirmations = Vec::new();
+        let input = ConfirmPaymentInput {
+            id: payment.id.clone(),
+            expected_version: None,
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_user().returning(move |id| {
+            let mut user: User = Faker.fake();
+            user.id = id.clone();
+            Ok(Some(user))
+        });
+        mock.expect_update_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let confirm = usecase.confirm_payment(&auth, input).await;
+        assert_eq!(confirm, Err(UseCaseError::BadRequest));
+    }
+
+    #[tokio::test]
+    async fn confirm_payment_403() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let payer = UserID::new("payer");
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), payer.clone(), UserID::new("bystander")];
+        group.require_confirmation = true;
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.creditors = vec![Amount {
+            user: payer.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![
+            Amount { user, amount: 500 },
+            Amount {
+                user: payer,
+                amount: 500,
+            },
+        ];
+        payment.confirmations = Vec::new();
+        let input = ConfirmPaymentInput {
+            id: payment.id.clone(),
+            expected_version: None,
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_user().returning(move |id| {
+            let mut user: User = Faker.fake();
+            user.id = id.clone();
+            Ok(Some(user))
+        });
+        mock.expect_update_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let mut other: Claims = Faker.fake();
+        other.sub = "bystander".to_string();
+        let auth = AuthState::Authorized(other);
+
+        let confirm = usecase.confirm_payment(&auth, input).await;
+        assert_eq!(confirm, Err(UseCaseError::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn confirm_payment_404() {
+        let claims: Claims = Faker.fake();
+        let input: ConfirmPaymentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_payment().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let confirm = usecase.confirm_payment(&auth, input).await;
+        assert_eq!(confirm, Err(UseCaseError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn confirm_payment_409() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let payer = UserID::new("payer");
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), payer.clone(), UserID::new("bystander")];
+        group.require_confirmation = true;
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.creditors = vec![Amount {
+            user: payer.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![
+            Amount { user, amount: 500 },
+            Amount {
+                user: payer,
+                amount: 500,
+            },
+        ];
+        payment.confirmations = Vec::new();
+        let input = ConfirmPaymentInput {
+            id: payment.id.clone(),
+            expected_version: Some(payment.version + 1),
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_user().returning(move |id| {
+            let mut user: User = Faker.fake();
+            user.id = id.clone();
+            Ok(Some(user))
+        });
+        mock.expect_update_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let confirm = usecase.confirm_payment(&auth, input).await;
+        assert_eq!(confirm, Err(UseCaseError::Conflict));
+    }
+
+    #[tokio::test]
+    async fn confirm_payment_500() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let payer = UserID::new("payer");
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), payer.clone(), UserID::new("bystander")];
+        group.require_confirmation = true;
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.creditors = vec![Amount {
+            user: payer.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![
+            Amount { user, amount: 500 },
+            Amount {
+                user: payer,
+                amount: 500,
+            },
+        ];
+        payment.confirmations = Vec::new();
+        let input = ConfirmPaymentInput {
+            id: payment.id.clone(),
+            expected_version: None,
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_get_user().returning(move |id| {
+            let mut user: User = Faker.fake();
+            user.id = id.clone();
+            Ok(Some(user))
+        });
+        mock.expect_update_payment()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+        mock.expect_create_notification().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let confirm = usecase.confirm_payment(&auth, input).await;
+        assert_eq!(confirm, Err(UseCaseError::InternalServerError));
+    }
+}
@@ -1,10 +1,14 @@
 use crate::{
-    entities::{AuthState, GroupID, Payment, PaymentID},
-    usecases::{UseCase, UseCaseError},
+    entities::{
+        split, Amount, AuthState, Group, GroupID, Item, Payment, PaymentID, PaymentKind,
+        PaymentStatus, Receipt, UserID, DEFAULT_CURRENCY,
+    },
+    usecases::{AmountInput, FieldError, UseCase, UseCaseError},
 };
 use async_graphql::InputObject;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use nanoid::nanoid;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -13,31 +17,121 @@ use fake::Dummy;
 #[cfg_attr(test, derive(Dummy))]
 pub struct CreatePaymentInput {
     pub title: String,
+    /// Defaults to an expense.
+    #[graphql(default)]
+    #[cfg_attr(test, dummy(default))]
+    pub kind: PaymentKind,
+    #[graphql(default)]
+    pub tags: Vec<String>,
+    /// One of the group's categories.
+    pub category: Option<String>,
+    /// When the payment happened; defaults to now.
+    pub date: Option<DateTime<Utc>>,
+    /// ISO 4217 code; defaults to JPY.
+    pub currency: Option<String>,
+    #[graphql(default)]
+    pub creditors: Vec<AmountInput>,
+    /// Explicit shares. Leave empty when using `split`.
+    #[graphql(default)]
+    pub debtors: Vec<AmountInput>,
+    /// Derives the debtors from the creditors' total.
+    pub split: Option<SplitInput>,
+    /// Derives the debtors from line items.
+    pub receipt: Option<ReceiptInput>,
     pub group: GroupID,
+    /// Retrying with the same key returns the payment created by the first request.
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct SplitInput {
+    /// Users sharing the cost; every participant of the group when omitted.
+    pub users: Option<Vec<UserID>>,
+    /// Relative shares, one per user; equal shares when omitted.
+    pub weights: Option<Vec<i32>>,
+}
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct ReceiptInput {
+    pub items: Vec<ItemInput>,
+    #[graphql(default)]
+    pub tax: i32,
+    #[graphql(default)]
+    pub service: i32,
+    #[graphql(default)]
+    pub tip: i32,
+}
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct ItemInput {
+    pub name: String,
+    /// Unit price.
+    pub price: i32,
+    #[graphql(default = 1)]
+    pub quantity: i32,
+    /// Participants sharing the item equally.
+    pub users: Vec<UserID>,
+}
+
+impl From<&ReceiptInput> for Receipt {
+    fn from(input: &ReceiptInput) -> Self {
+        Receipt {
+            items: input
+                .items
+                .iter()
+                .map(|item| Item {
+                    name: item.name.clone(),
+                    price: item.price,
+                    quantity: item.quantity,
+                    users: item.users.clone(),
+                })
+                .collect(),
+            tax: input.tax,
+            service: input.service,
+            tip: input.tip,
+        }
+    }
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn create_payment(
         &self,
         auth: &AuthState,
         input: CreatePaymentInput,
     ) -> Result<Payment, UseCaseError> {
-        if self.get_group(auth, &input.group).await?.is_some() {
-            let now = Utc::now();
-            let payment = Payment {
-                id: PaymentID::new(nanoid!()),
-                created_at: now,
-                updated_at: now,
-                title: input.title,
-                creditors: Vec::new(),
-                debtors: Vec::new(),
-                group: input.group,
+        if let Some(group) = self.get_group(auth, &input.group).await? {
+            let key = input.idempotency_key.clone();
+            let key = key.as_deref();
+            // A retry returns the first payment even if the input would no
+            // longer pass validation.
+            let id = PaymentID::new(nanoid!());
+            if let Some(id) = self
+                .claim_idempotency_key(auth, key, "createPayment", Some(&group.id), &id.to_string())
+                .await?
+            {
+                return self
+                    .get_payment(auth, &PaymentID::new(id))
+                    .await?
+                    .ok_or(UseCaseError::Conflict);
+            }
+            let payment = match new_payment(auth, &group, input) {
+                Ok(payment) => Payment { id, ..payment },
+                Err(err) => {
+                    self.release_idempotency_key(auth, key).await;
+                    Err(err)?
+                }
+            };
+            let payment = match self.repository.create_payment(payment).await {
+                Ok(payment) => payment,
+                Err(err) => {
+                    self.release_idempotency_key(auth, key).await;
+                    Err(err)?
+                }
             };
-            let payment = self
-                .repository
-                .create_payment(payment)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
             Ok(payment)
         } else {
             Err(UseCaseError::NotFound)?
@@ -45,36 +139,392 @@ impl UseCase {
     }
 }
 
+/// Builds the payment `input` describes in `group` without storing it.
+pub(super) fn new_payment(
+    auth: &AuthState,
+    group: &Group,
+    input: CreatePaymentInput,
+) -> Result<Payment, UseCaseError> {
+    let mut errors = Vec::new();
+    if input.title.trim().is_empty() {
+        errors.push(FieldError::new("title", "must not be empty"));
+    }
+    let creditors: Vec<Amount> = input.creditors.iter().map(Amount::from).collect();
+    let receipt = input.receipt.as_ref().map(Receipt::from);
+    let debtors = match (&input.split, &receipt) {
+        (Some(_), Some(_)) => {
+            errors.push(FieldError::new("receipt", "cannot be combined with split"));
+            Vec::new()
+        }
+        (Some(spec), None) => split_debtors(group, spec, &input, &creditors, &mut errors),
+        (None, Some(receipt)) => {
+            if !input.debtors.is_empty() {
+                errors.push(FieldError::new(
+                    "debtors",
+                    "cannot be combined with receipt",
+                ));
+            }
+            receipt.debtors().unwrap_or_default()
+        }
+        (None, None) => input.debtors.iter().map(Amount::from).collect(),
+    };
+
+    let now = Utc::now();
+    let mut payment = Payment {
+        id: PaymentID::new(nanoid!()),
+        created_at: now,
+        updated_at: now,
+        title: input.title,
+        kind: input.kind,
+        creditors,
+        debtors,
+        tags: input.tags,
+        category: input.category,
+        occurred_at: Some(input.date.unwrap_or(now)),
+        currency: input
+            .currency
+            .unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+        receipt,
+        confirmations: Vec::new(),
+        group: input.group,
+        version: 0,
+    };
+    // Entering a payment confirms one's own share of it.
+    if let AuthState::Authorized(claims) = auth {
+        payment.answer(
+            &UserID::new(&claims.sub),
+            PaymentStatus::Confirmed,
+            None,
+            now,
+        );
+    }
+    if errors.is_empty() {
+        errors.extend(payment.violations(group).iter().map(FieldError::from));
+    }
+    if !errors.is_empty() {
+        Err(UseCaseError::InvalidInput(errors))?
+    }
+    Ok(payment)
+}
+
+/// Divides the creditors' total according to `spec`. Problems with the spec
+/// are reported under `split` and leave the debtors empty.
+fn split_debtors(
+    group: &Group,
+    spec: &SplitInput,
+    input: &CreatePaymentInput,
+    creditors: &[Amount],
+    errors: &mut Vec<FieldError>,
+) -> Vec<Amount> {
+    let before = errors.len();
+    if !input.debtors.is_empty() {
+        errors.push(FieldError::new("debtors", "cannot be combined with split"));
+    }
+    if creditors.is_empty() {
+        errors.push(FieldError::new("split", "requires at least one creditor"));
+    }
+    let users = spec
+        .users
+        .clone()
+        .unwrap_or_else(|| group.participants.clone());
+    if users.is_empty() {
+        errors.push(FieldError::new("split.users", "must not be empty"));
+    }
+    for (i, user) in users.iter().enumerate() {
+        if !group.participants.contains(user) {
+            errors.push(FieldError::new(
+                format!("split.users[{i}]"),
+                "is not a participant of the group",
+            ));
+        }
+    }
+    let weights = spec.weights.clone().unwrap_or_else(|| vec![1; users.len()]);
+    if weights.len() != users.len() {
+        errors.push(FieldError::new(
+            "split.weights",
+            "must have one weight per user",
+        ));
+    }
+    for (i, weight) in weights.iter().enumerate() {
+        if *weight <= 0 {
+            errors.push(FieldError::new(
+                format!("split.weights[{i}]"),
+                "must be positive",
+            ));
+        }
+    }
+    let total: i64 = creditors.iter().map(|a| i64::from(a.amount)).sum();
+    match i32::try_from(total) {
+        Ok(total) if errors.len() == before => split(total, &users, &weights),
+        _ => Vec::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        entities::{Claims, Group, UserID},
-        repositories::MockRepository,
+        entities::{Claims, IdempotencyKey},
+        repositories::{MockRepository, RepositoryError},
     };
+    use chrono::Duration;
     use fake::{Fake, Faker};
     use std::sync::Arc;
 
     #[tokio::test]
     async fn create_payment_200() {
         let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
         let mut group: Group = Faker.fake();
-        group.participants.push(UserID::new(&claims.sub));
+        group.participants = vec![user.clone(), Faker.fake(), Faker.fake()];
         let mut input: CreatePaymentInput = Faker.fake();
         input.group = group.id.clone();
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 3000,
+        }];
+        input.split = Some(SplitInput {
+            users: None,
+            weights: None,
+        });
+        input.category = None;
+        input.currency = None;
+        input.debtors = Vec::new();
+        input.receipt = None;
+        input.idempotency_key = None;
         let id = group.id.clone();
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
-        mock.expect_create_payment()
-            .returning(move |payment| Ok(payment));
+        mock.expect_create_payment().returning(Ok);
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
 
         let create = usecase.create_payment(&auth, input).await.unwrap();
         assert_eq!(create.group, id);
+        assert_eq!(create.currency, DEFAULT_CURRENCY);
+        assert_eq!(
+            create.debtors.iter().map(|a| a.amount).collect::<Vec<_>>(),
+            vec![1000, 1000, 1000]
+        );
+    }
+
+    #[tokio::test]
+    async fn create_payment_200_receipt() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let other: UserID = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), other.clone()];
+        let mut input: CreatePaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 3300,
+        }];
+        input.split = None;
+        input.category = None;
+        input.currency = None;
+        input.debtors = Vec::new();
+        input.idempotency_key = None;
+        input.receipt = Some(ReceiptInput {
+            items: vec![
+                ItemInput {
+                    name: "Steak".to_string(),
+                    price: 2000,
+                    quantity: 1,
+                    users: vec![user.clone()],
+                },
+                ItemInput {
+                    name: "Wine".to_string(),
+                    price: 500,
+                    quantity: 2,
+                    users: vec![user.clone(), other.clone()],
+                },
+            ],
+            tax: 300,
+            service: 0,
+            tip: 0,
+        });
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_payment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_payment(&auth, input).await.unwrap();
+        assert_eq!(
+            create.debtors,
+            vec![
+                Amount { user, amount: 2750 },
+                Amount {
+                    user: other,
+                    amount: 550
+                },
+            ]
+        );
+        assert!(create.receipt.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_payment_200_replay() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        let mut input: CreatePaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.title = String::new();
+        input.idempotency_key = Some("retry".to_string());
+        let mut key: IdempotencyKey = Faker.fake();
+        key.operation = "createPayment".to_string();
+        key.entity = payment.id.to_string();
+        key.group = Some(group.id.clone());
+        key.expires_at = Utc::now() + Duration::hours(1);
+        let id = payment.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_idempotency_key()
+            .returning(|_| Err(RepositoryError::Conflict("duplicate key".to_string())));
+        mock.expect_get_idempotency_key()
+            .returning(move |_, _| Ok(Some(key.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_create_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_payment(&auth, input).await.unwrap();
+        assert_eq!(create.id, id);
+    }
+
+    #[tokio::test]
+    async fn create_payment_400() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone()];
+        let mut input: CreatePaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 3000,
+        }];
+        input.split = None;
+        input.category = None;
+        input.currency = Some("yen".to_string());
+        input.receipt = None;
+        input.idempotency_key = None;
+        input.debtors = vec![
+            AmountInput {
+                user: user.clone(),
+                amount: -100,
+            },
+            AmountInput {
+                user: Faker.fake(),
+                amount: 100,
+            },
+        ];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_payment(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = create else {
+            panic!("expected invalid input, got {create:?}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "currency",
+                "debtors[0].amount",
+                "debtors[1].user",
+                "debtors"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn create_payment_400_transfer() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), UserID::new("b"), UserID::new("c")];
+        group.categories = vec!["food".to_string()];
+        let mut input: CreatePaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.kind = PaymentKind::Transfer;
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 3000,
+        }];
+        input.split = Some(SplitInput {
+            users: None,
+            weights: None,
+        });
+        input.category = Some("food".to_string());
+        input.currency = None;
+        input.debtors = Vec::new();
+        input.receipt = None;
+        input.idempotency_key = None;
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_payment(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = create else {
+            panic!("expected invalid input, got {create:?}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["category", "debtors"]);
+    }
+
+    #[tokio::test]
+    async fn create_payment_400_idempotency_group() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut input: CreatePaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.idempotency_key = Some("retry".to_string());
+        let mut key: IdempotencyKey = Faker.fake();
+        key.operation = "createPayment".to_string();
+        key.group = Some(Faker.fake());
+        key.expires_at = Utc::now() + Duration::hours(1);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_idempotency_key()
+            .returning(|_| Err(RepositoryError::Conflict("duplicate key".to_string())));
+        mock.expect_get_idempotency_key()
+            .returning(move |_, _| Ok(Some(key.clone())));
+        mock.expect_create_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_payment(&auth, input).await;
+        assert_eq!(create, Err(UseCaseError::BadRequest));
     }
 
     #[tokio::test]
@@ -95,16 +545,30 @@ mod tests {
     #[tokio::test]
     async fn create_payment_500() {
         let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
         let mut group: Group = Faker.fake();
-        group.participants.push(UserID::new(&claims.sub));
+        group.participants.push(user.clone());
         let mut input: CreatePaymentInput = Faker.fake();
         input.group = group.id.clone();
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 3000,
+        }];
+        input.split = Some(SplitInput {
+            users: None,
+            weights: None,
+        });
+        input.category = None;
+        input.currency = None;
+        input.debtors = Vec::new();
+        input.receipt = None;
+        input.idempotency_key = None;
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_create_payment()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -3,6 +3,8 @@ use crate::{
     usecases::{UseCase, UseCaseError},
 };
 use async_graphql::InputObject;
+use futures::future::try_join_all;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -14,16 +16,32 @@ pub struct DeletePaymentInput {
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn delete_payment(
         &self,
         auth: &AuthState,
         input: DeletePaymentInput,
     ) -> Result<PaymentID, UseCaseError> {
         if let Some(payment) = self.get_payment(auth, &input.id).await? {
-            self.repository
-                .delete_payment(&payment.id)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+            self.repository.delete_payment(&payment.id).await?;
+            let attachments = self
+                .repository
+                .get_attachments_by_payment(&payment.id)
+                .await?;
+            self.remove_attachments(attachments).await?;
+            try_join_all(
+                self.repository
+                    .get_comments_by_payment(&payment.id)
+                    .await?
+                    .iter()
+                    .map(|comment| async {
+                        self.repository
+                            .delete_comment(&comment.id)
+                            .await
+                            .map_err(UseCaseError::from)
+                    }),
+            )
+            .await?;
             Ok(payment.id)
         } else {
             Err(UseCaseError::NotFound)?
@@ -35,8 +53,8 @@ impl UseCase {
 mod tests {
     use super::*;
     use crate::{
-        entities::{Claims, Group, Payment, UserID},
-        repositories::MockRepository,
+        entities::{Attachment, Claims, Comment, Group, Payment, UserID},
+        repositories::{MockRepository, RepositoryError},
     };
     use fake::{Fake, Faker};
     use std::sync::Arc;
@@ -51,6 +69,11 @@ mod tests {
         let mut input: DeletePaymentInput = Faker.fake();
         input.id = payment.id.clone();
         let id = payment.id.clone();
+        let mut attachment: Attachment = Faker.fake();
+        attachment.payment = payment.id.clone();
+        attachment.key = "group/payment/attachment".to_string();
+        let attachments = vec![attachment];
+        let comments: Vec<Comment> = Faker.fake();
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
@@ -58,6 +81,16 @@ mod tests {
         mock.expect_get_payment()
             .returning(move |_| Ok(Some(payment.clone())));
         mock.expect_delete_payment().returning(move |_| Ok(()));
+        mock.expect_get_attachments_by_payment()
+            .returning(move |_| Ok(attachments.clone()));
+        mock.expect_delete_attachment()
+            .times(1)
+            .returning(|_| Ok(()));
+        mock.expect_delete_comment()
+            .times(comments.len())
+            .returning(|_| Ok(()));
+        mock.expect_get_comments_by_payment()
+            .returning(move |_| Ok(comments.clone()));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -97,7 +130,7 @@ mod tests {
         mock.expect_get_payment()
             .returning(move |_| Ok(Some(payment.clone())));
         mock.expect_delete_payment()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
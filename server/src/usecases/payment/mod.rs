@@ -1,8 +1,12 @@
+mod confirm;
 mod create;
 mod delete;
+mod preview;
 mod read;
 mod update;
 
+pub use confirm::*;
 pub use create::*;
 pub use delete::*;
+pub use preview::*;
 pub use update::*;
@@ -0,0 +1,383 @@
+use super::{create::new_payment, read::settle_group, update::amend_payment};
+use crate::{
+    entities::{
+        balances, Amount, AuthState, GroupID, Payment, PaymentID, PaymentStatus, Settlement,
+        SettlementOptions, Warikan, WarikanPreview,
+    },
+    usecases::{CreatePaymentInput, FieldError, UpdatePaymentInput, UseCase, UseCaseError},
+};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Changes to try out on a group's payments, read from the arguments of
+/// `previewWarikan`.
+#[derive(Default)]
+pub struct PreviewWarikanInput {
+    pub add_payments: Vec<CreatePaymentInput>,
+    pub remove_payment_ids: Vec<PaymentID>,
+    pub modify_payments: Vec<UpdatePaymentInput>,
+    pub include_unconfirmed: bool,
+    pub by_household: bool,
+    pub options: SettlementOptions,
+}
+
+impl UseCase {
+    /// Settles the group as it is and as it would be with `input` applied.
+    /// Nothing is stored. Added payments count whether or not they would need
+    /// confirming; modified ones count like stored ones once amended, so new
+    /// amounts that need confirming again are left out unless
+    /// `include_unconfirmed` is set. The transfers of a preview carry no
+    /// contributions since they may refer to payments that do not exist.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn preview_warikan(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+        input: PreviewWarikanInput,
+    ) -> Result<WarikanPreview, UseCaseError> {
+        if !input.options.is_valid() {
+            Err(UseCaseError::BadRequest)?
+        }
+        if let Some(group) = self.get_group(auth, id).await? {
+            let stored = self.repository.get_payments_by_group(id).await?;
+            let counts = |payment: &Payment| {
+                input.include_unconfirmed || payment.status(&group) == PaymentStatus::Confirmed
+            };
+
+            let mut errors = Vec::new();
+            for (i, removed) in input.remove_payment_ids.iter().enumerate() {
+                if !stored.iter().any(|payment| payment.id == *removed) {
+                    errors.push(FieldError::new(
+                        format!("removePaymentIds[{i}]"),
+                        format!("{} is not a payment of the group", removed.to_string()),
+                    ));
+                }
+            }
+            let mut amended = Vec::new();
+            let mut added = Vec::new();
+            for (i, update) in input.modify_payments.into_iter().enumerate() {
+                let field = format!("modifyPayments[{i}]");
+                let Some(payment) = stored.iter().find(|payment| {
+                    payment.id == update.id && !input.remove_payment_ids.contains(&payment.id)
+                }) else {
+                    errors.push(FieldError::new(
+                        format!("{field}.id"),
+                        format!("{} is not a payment of the group", update.id.to_string()),
+                    ));
+                    continue;
+                };
+                match amend_payment(auth, &group, payment.clone(), update) {
+                    Ok(payment) => amended.push(payment),
+                    Err(UseCaseError::InvalidInput(invalid)) => {
+                        errors.extend(nested(&field, invalid))
+                    }
+                    Err(err) => Err(err)?,
+                }
+            }
+            for (i, create) in input.add_payments.into_iter().enumerate() {
+                let field = format!("addPayments[{i}]");
+                if create.group != *id {
+                    errors.push(FieldError::new(
+                        format!("{field}.group"),
+                        "must belong to the group",
+                    ));
+                    continue;
+                }
+                match new_payment(auth, &group, create) {
+                    Ok(payment) => added.push(payment),
+                    Err(UseCaseError::InvalidInput(invalid)) => {
+                        errors.extend(nested(&field, invalid))
+                    }
+                    Err(err) => Err(err)?,
+                }
+            }
+            if !errors.is_empty() {
+                Err(UseCaseError::InvalidInput(errors))?
+            }
+
+            let before: Vec<Payment> = stored.iter().filter(|p| counts(p)).cloned().collect();
+            let after: Vec<Payment> = stored
+                .iter()
+                .filter(|payment| {
+                    !input.remove_payment_ids.contains(&payment.id)
+                        && !amended.iter().any(|amended| amended.id == payment.id)
+                })
+                .chain(&amended)
+                .filter(|payment| counts(payment))
+                .cloned()
+                .chain(added)
+                .collect();
+            let settle = |payments: &[Payment]| -> Result<Settlement, UseCaseError> {
+                let mut balances: Vec<Amount> = balances(payments)
+                    .into_iter()
+                    .map(|(user, amount)| Amount { user, amount })
+                    .collect();
+                balances.sort_by(|a, b| a.user.cmp(&b.user));
+                let warikans = settle_group(&group, payments, input.by_household, &input.options)?
+                    .into_iter()
+                    .map(|warikan| Warikan {
+                        payments: Arc::default(),
+                        ..warikan
+                    })
+                    .collect();
+                Ok(Settlement { balances, warikans })
+            };
+            Ok(WarikanPreview {
+                before: settle(&before)?,
+                after: settle(&after)?,
+            })
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+}
+
+/// Locates the errors of one entry of a list argument, e.g. `addPayments[0].title`.
+fn nested(field: &str, errors: Vec<FieldError>) -> impl Iterator<Item = FieldError> + '_ {
+    errors
+        .into_iter()
+        .map(move |error| FieldError::new(format!("{field}.{}", error.field), error.message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Group, UserID},
+        repositories::MockRepository,
+        usecases::AmountInput,
+    };
+    use async_graphql::MaybeUndefined;
+    use chrono::Utc;
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn preview_warikan_200() {
+        let claims: Claims = Faker.fake();
+        let (a, b) = (UserID::new(&claims.sub), UserID::new("b"));
+        let mut group: Group = Faker.fake();
+        group.participants = vec![a.clone(), b.clone()];
+        group.require_confirmation = false;
+        group.households = Vec::new();
+        let id = group.id.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.currency = "JPY".to_string();
+        payment.category = None;
+        payment.receipt = None;
+        payment.creditors = vec![Amount {
+            user: a.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![Amount {
+            user: b.clone(),
+            amount: 1000,
+        }];
+        let removed = payment.id.clone();
+        let mut modify: UpdatePaymentInput = Faker.fake();
+        modify.id = payment.id.clone();
+        modify.title = None;
+        modify.tags = None;
+        modify.date = None;
+        modify.expected_version = None;
+        modify.creditors = Some(vec![AmountInput {
+            user: a,
+            amount: 3000,
+        }]);
+        modify.debtors = Some(vec![AmountInput {
+            user: b,
+            amount: 3000,
+        }]);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payments_by_group()
+            .returning(move |_| Ok(vec![payment.clone()]));
+        mock.expect_create_payment().never();
+        mock.expect_update_payment().never();
+        mock.expect_delete_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let input = PreviewWarikanInput {
+            modify_payments: vec![modify],
+            ..Default::default()
+        };
+        let preview = usecase.preview_warikan(&auth, &id, input).await.unwrap();
+        assert_eq!(preview.before.warikans[0].amount, 1000);
+        assert_eq!(preview.after.warikans[0].amount, 3000);
+        assert_eq!(preview.after.balances.len(), 2);
+        assert!(preview.after.warikans[0].from_contributions().is_empty());
+
+        let input = PreviewWarikanInput {
+            remove_payment_ids: vec![removed],
+            ..Default::default()
+        };
+        let preview = usecase.preview_warikan(&auth, &id, input).await.unwrap();
+        assert_eq!(preview.before.warikans.len(), 1);
+        assert!(preview.after.warikans.is_empty());
+    }
+
+    #[tokio::test]
+    async fn preview_warikan_200_unconfirmed() {
+        let claims: Claims = Faker.fake();
+        let (a, b) = (UserID::new(&claims.sub), UserID::new("b"));
+        let mut group: Group = Faker.fake();
+        group.participants = vec![a.clone(), b.clone()];
+        group.require_confirmation = true;
+        group.households = Vec::new();
+        let id = group.id.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.currency = "JPY".to_string();
+        payment.category = None;
+        payment.receipt = None;
+        payment.creditors = vec![Amount {
+            user: a.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![Amount {
+            user: b.clone(),
+            amount: 1000,
+        }];
+        payment.confirmations = Vec::new();
+        payment.answer(&b, PaymentStatus::Confirmed, None, Utc::now());
+        let modify = || UpdatePaymentInput {
+            id: payment.id.clone(),
+            title: None,
+            kind: None,
+            creditors: Some(vec![AmountInput {
+                user: a.clone(),
+                amount: 3000,
+            }]),
+            debtors: Some(vec![AmountInput {
+                user: b.clone(),
+                amount: 3000,
+            }]),
+            tags: None,
+            category: MaybeUndefined::Undefined,
+            date: None,
+            receipt: MaybeUndefined::Undefined,
+            expected_version: None,
+        };
+        let inputs = [
+            PreviewWarikanInput {
+                modify_payments: vec![modify()],
+                ..Default::default()
+            },
+            PreviewWarikanInput {
+                modify_payments: vec![modify()],
+                include_unconfirmed: true,
+                ..Default::default()
+            },
+        ];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payments_by_group()
+            .returning(move |_| Ok(vec![payment.clone()]));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let [confirmed, unconfirmed] = inputs;
+        let preview = usecase
+            .preview_warikan(&auth, &id, confirmed)
+            .await
+            .unwrap();
+        assert_eq!(preview.before.warikans[0].amount, 1000);
+        assert!(preview.after.warikans.is_empty());
+
+        let preview = usecase
+            .preview_warikan(&auth, &id, unconfirmed)
+            .await
+            .unwrap();
+        assert_eq!(preview.after.warikans[0].amount, 3000);
+    }
+
+    #[tokio::test]
+    async fn preview_warikan_400() {
+        let claims: Claims = Faker.fake();
+        let (a, b) = (UserID::new(&claims.sub), UserID::new("b"));
+        let mut group: Group = Faker.fake();
+        group.participants = vec![a.clone(), b.clone()];
+        group.require_confirmation = false;
+        group.households = Vec::new();
+        let id = group.id.clone();
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.currency = "JPY".to_string();
+        payment.category = None;
+        payment.receipt = None;
+        payment.creditors = vec![Amount {
+            user: a,
+            amount: 1000,
+        }];
+        payment.debtors = vec![Amount {
+            user: b,
+            amount: 1000,
+        }];
+        let mut modify: UpdatePaymentInput = Faker.fake();
+        modify.id = payment.id.clone();
+        modify.expected_version = None;
+        modify.creditors = Some(vec![AmountInput {
+            user: UserID::new("stranger"),
+            amount: 1000,
+        }]);
+        modify.debtors = None;
+        let foreign: CreatePaymentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payments_by_group()
+            .returning(move |_| Ok(vec![payment.clone()]));
+        mock.expect_create_payment().never();
+        mock.expect_update_payment().never();
+        mock.expect_delete_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let input = PreviewWarikanInput {
+            add_payments: vec![foreign],
+            remove_payment_ids: vec![Faker.fake()],
+            modify_payments: vec![modify],
+            ..Default::default()
+        };
+        let preview = usecase.preview_warikan(&auth, &id, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = preview else {
+            panic!("expected invalid input, got {preview:?}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "removePaymentIds[0]",
+                "modifyPayments[0].creditors[0].user",
+                "addPayments[0].group"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn preview_warikan_404() {
+        let claims: Claims = Faker.fake();
+        let id: GroupID = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let preview = usecase
+            .preview_warikan(&auth, &id, PreviewWarikanInput::default())
+            .await;
+        assert_eq!(preview, Err(UseCaseError::NotFound));
+    }
+}
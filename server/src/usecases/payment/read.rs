@@ -1,20 +1,21 @@
 use crate::{
-    entities::{warikan, AuthState, GroupID, Payment, PaymentID, Warikan},
+    entities::{
+        household_balances, statement, warikan, warikan_by_household, AuthState, Group, GroupID,
+        HouseholdBalance, InvalidPayment, Page, PageRequest, Payment, PaymentFilter, PaymentID,
+        PaymentStatus, SettlementOptions, StatementEntry, UserID, Warikan,
+    },
     usecases::{UseCase, UseCaseError},
 };
+use tracing::instrument;
 
 impl UseCase {
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_payment(
         &self,
         auth: &AuthState,
         id: &PaymentID,
     ) -> Result<Option<Payment>, UseCaseError> {
-        if let Some(payment) = self
-            .repository
-            .get_payment(id)
-            .await
-            .or(Err(UseCaseError::InternalServerError))?
-        {
+        if let Some(payment) = self.repository.get_payment(id).await? {
             let payment = self
                 .get_group(auth, &payment.group)
                 .await?
@@ -25,30 +26,239 @@ impl UseCase {
         }
     }
 
+    /// Batch form of [`UseCase::get_payment`]; membership of every group the
+    /// payments belong to is checked with a single group lookup. Payments
+    /// whose group is missing or does not include the caller are skipped.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_payments_by_ids(
+        &self,
+        auth: &AuthState,
+        ids: &[PaymentID],
+    ) -> Result<Vec<Payment>, UseCaseError> {
+        if let AuthState::Authorized(_) = auth {
+            if ids.is_empty() {
+                return Ok(vec![]);
+            }
+            let payments = self.repository.get_payments_by_ids(ids).await?;
+            let mut groups: Vec<GroupID> = payments
+                .iter()
+                .map(|payment| payment.group.clone())
+                .collect();
+            groups.sort();
+            groups.dedup();
+            let groups: Vec<GroupID> = self
+                .get_groups_by_ids(auth, &groups)
+                .await?
+                .into_iter()
+                .map(|group| group.id)
+                .collect();
+            let payments = payments
+                .into_iter()
+                .filter(|payment| groups.contains(&payment.group))
+                .collect();
+            Ok(payments)
+        } else {
+            Err(UseCaseError::from(auth))?
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_payments_page_by_group(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+        page: PageRequest,
+    ) -> Result<Page<Payment>, UseCaseError> {
+        let page = page.normalize().ok_or(UseCaseError::BadRequest)?;
+        if self.get_group(auth, id).await?.is_some() {
+            let payments = self
+                .repository
+                .get_payments_page_by_group(id, &page)
+                .await?;
+            Ok(payments)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_payments_by_group(
         &self,
         auth: &AuthState,
         id: &GroupID,
     ) -> Result<Vec<Payment>, UseCaseError> {
         if self.get_group(auth, id).await?.is_some() {
-            let payments = self
+            let payments = self.repository.get_payments_by_group(id).await?;
+            Ok(payments)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn find_payments_by_group(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+        filter: PaymentFilter,
+    ) -> Result<Vec<Payment>, UseCaseError> {
+        if !filter.is_valid() {
+            Err(UseCaseError::BadRequest)?
+        }
+        if self.get_group(auth, id).await?.is_some() {
+            let filter = PaymentFilter {
+                groups: vec![id.clone()],
+                ..filter
+            };
+            let payments = self.repository.find_payments(&filter).await?;
+            Ok(payments)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+
+    /// Searches the payments of every group the caller participates in.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn search_payments(
+        &self,
+        auth: &AuthState,
+        filter: PaymentFilter,
+    ) -> Result<Vec<Payment>, UseCaseError> {
+        if let AuthState::Authorized(claims) = auth {
+            if !filter.is_valid() {
+                Err(UseCaseError::BadRequest)?
+            }
+            let groups = self
                 .repository
-                .get_payments_by_group(id)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+                .get_groups_by_user(&UserID::new(&claims.sub))
+                .await?;
+            if groups.is_empty() {
+                return Ok(vec![]);
+            }
+            let filter = PaymentFilter {
+                groups: groups.into_iter().map(|group| group.id).collect(),
+                ..filter
+            };
+            let payments = self.repository.find_payments(&filter).await?;
             Ok(payments)
+        } else {
+            Err(UseCaseError::from(auth))?
+        }
+    }
+
+    /// Reports the stored payments of a group that break payment invariants.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn validate_payments(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+    ) -> Result<Vec<InvalidPayment>, UseCaseError> {
+        if let Some(group) = self.get_group(auth, id).await? {
+            let payments = self.repository.get_payments_by_group(id).await?;
+            let invalid = payments
+                .into_iter()
+                .filter_map(|payment| {
+                    let violations = payment.violations(&group);
+                    (!violations.is_empty()).then_some(InvalidPayment {
+                        payment,
+                        violations,
+                    })
+                })
+                .collect();
+            Ok(invalid)
         } else {
             Err(UseCaseError::NotFound)?
         }
     }
 
+    /// Settles the group's payments. Payments still pending or disputed are
+    /// left out unless `include_unconfirmed` is set. With `by_household`,
+    /// households settle as one unit through their representatives. Options
+    /// that no set of transfers satisfies are a bad request.
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn warikan_by_group(
         &self,
         auth: &AuthState,
         id: &GroupID,
+        include_unconfirmed: bool,
+        by_household: bool,
+        options: &SettlementOptions,
     ) -> Result<Vec<Warikan>, UseCaseError> {
-        let payments = self.get_payments_by_group(auth, id).await?;
-        warikan(&payments).ok_or(UseCaseError::BadRequest)
+        if !options.is_valid() {
+            Err(UseCaseError::BadRequest)?
+        }
+        let (group, payments) = self
+            .get_settled_payments(auth, id, include_unconfirmed)
+            .await?;
+        settle_group(&group, &payments, by_household, options)
+    }
+
+    /// Balance of every household of the group, broken down per member.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn household_balances_by_group(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+        include_unconfirmed: bool,
+    ) -> Result<Vec<HouseholdBalance>, UseCaseError> {
+        let (group, payments) = self
+            .get_settled_payments(auth, id, include_unconfirmed)
+            .await?;
+        Ok(household_balances(&payments, &group.households))
+    }
+
+    /// Every payment of the group that moved `user`'s balance, with the
+    /// running balance after each.
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn statement_by_group(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+        user: &UserID,
+        include_unconfirmed: bool,
+    ) -> Result<Vec<StatementEntry>, UseCaseError> {
+        let (_, payments) = self
+            .get_settled_payments(auth, id, include_unconfirmed)
+            .await?;
+        Ok(statement(&payments, user))
+    }
+
+    /// The group with the payments that count towards settling it.
+    async fn get_settled_payments(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+        include_unconfirmed: bool,
+    ) -> Result<(Group, Vec<Payment>), UseCaseError> {
+        if let Some(group) = self.get_group(auth, id).await? {
+            let payments: Vec<Payment> = self
+                .repository
+                .get_payments_by_group(id)
+                .await?
+                .into_iter()
+                .filter(|payment| {
+                    include_unconfirmed || payment.status(&group) == PaymentStatus::Confirmed
+                })
+                .collect();
+            Ok((group, payments))
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+}
+
+/// Transfers that settle `payments` of `group`, or a bad request when
+/// `options` cannot be satisfied.
+pub(super) fn settle_group(
+    group: &Group,
+    payments: &[Payment],
+    by_household: bool,
+    options: &SettlementOptions,
+) -> Result<Vec<Warikan>, UseCaseError> {
+    if by_household {
+        warikan_by_household(payments, &group.households, options).ok_or(UseCaseError::BadRequest)
+    } else {
+        warikan(payments, options).ok_or(UseCaseError::BadRequest)
     }
 }
 
@@ -56,9 +266,10 @@ impl UseCase {
 mod tests {
     use super::*;
     use crate::{
-        entities::{Claims, Group, UserID},
-        repositories::MockRepository,
+        entities::{Amount, AuthError, Claims, PaymentViolation, MAX_PAGE_SIZE},
+        repositories::{MockRepository, RepositoryError},
     };
+    use chrono::Utc;
     use fake::{Fake, Faker};
     use std::sync::Arc;
 
@@ -91,7 +302,7 @@ mod tests {
 
         let mut mock = MockRepository::new();
         mock.expect_get_payment()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -148,7 +359,7 @@ mod tests {
         mock.expect_get_group()
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_get_payments_by_group()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -156,4 +367,390 @@ mod tests {
         let get = usecase.get_payments_by_group(&auth, &id).await;
         assert_eq!(get, Err(UseCaseError::InternalServerError));
     }
+
+    #[tokio::test]
+    async fn get_payments_by_ids_200() {
+        let claims: Claims = Faker.fake();
+        let payment: Payment = Faker.fake();
+        let foreign: Payment = Faker.fake();
+        let orphan: Payment = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.id = payment.group.clone();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut stranger: Group = Faker.fake();
+        stranger.id = foreign.group.clone();
+        let ids = vec![payment.id.clone(), foreign.id.clone(), orphan.id.clone()];
+        let expected = vec![payment.id.clone()];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_payments_by_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![payment.clone(), foreign.clone(), orphan.clone()]));
+        // The orphan's group no longer exists, so the repository leaves it out.
+        mock.expect_get_groups_by_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![group.clone(), stranger.clone()]));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_payments_by_ids(&auth, &ids).await.unwrap();
+        assert_eq!(get.into_iter().map(|p| p.id).collect::<Vec<_>>(), expected);
+    }
+
+    #[tokio::test]
+    async fn get_payments_by_ids_401() {
+        let ids: Vec<PaymentID> = Faker.fake();
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let get = usecase.get_payments_by_ids(&auth, &ids).await;
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_payments_by_ids_500() {
+        let claims: Claims = Faker.fake();
+        let ids = vec![Faker.fake::<PaymentID>()];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_payments_by_ids()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_payments_by_ids(&auth, &ids).await;
+        assert_eq!(get, Err(UseCaseError::InternalServerError));
+    }
+
+    #[tokio::test]
+    async fn get_payments_page_by_group_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let id = group.id.clone();
+        let page = PageRequest {
+            last: Some(10),
+            ..Default::default()
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payments_page_by_group()
+            .withf(|_, page| page.first.is_none() && page.last == Some(10))
+            .returning(move |_, _| {
+                Ok(Page {
+                    items: vec![],
+                    has_previous_page: false,
+                    has_next_page: false,
+                })
+            });
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .get_payments_page_by_group(&auth, &id, page)
+            .await
+            .unwrap();
+        assert_eq!(get.items.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn get_payments_page_by_group_400() {
+        let claims: Claims = Faker.fake();
+        let id: GroupID = Faker.fake();
+        let page = PageRequest {
+            last: Some(MAX_PAGE_SIZE + 1),
+            ..Default::default()
+        };
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_payments_page_by_group(&auth, &id, page).await;
+        assert_eq!(get, Err(UseCaseError::BadRequest));
+    }
+
+    #[tokio::test]
+    async fn find_payments_by_group_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let id = group.id.clone();
+        let payments: Vec<Payment> = Faker.fake();
+        let len = payments.len();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        let expected = id.clone();
+        mock.expect_find_payments()
+            .withf(move |filter| filter.groups == vec![expected.clone()])
+            .returning(move |_| Ok(payments.clone()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .find_payments_by_group(&auth, &id, PaymentFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(get.len(), len);
+    }
+
+    #[tokio::test]
+    async fn find_payments_by_group_400() {
+        let claims: Claims = Faker.fake();
+        let id: GroupID = Faker.fake();
+        let filter = PaymentFilter {
+            min_amount: Some(100),
+            max_amount: Some(10),
+            ..Default::default()
+        };
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.find_payments_by_group(&auth, &id, filter).await;
+        assert_eq!(get, Err(UseCaseError::BadRequest));
+    }
+
+    #[tokio::test]
+    async fn search_payments_200() {
+        let claims: Claims = Faker.fake();
+        let groups: Vec<Group> = vec![Faker.fake(), Faker.fake()];
+        let ids: Vec<GroupID> = groups.iter().map(|group| group.id.clone()).collect();
+        let payments: Vec<Payment> = Faker.fake();
+        let len = payments.len();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_groups_by_user()
+            .returning(move |_| Ok(groups.clone()));
+        mock.expect_find_payments()
+            .withf(move |filter| filter.groups == ids)
+            .returning(move |_| Ok(payments.clone()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .search_payments(&auth, PaymentFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(get.len(), len);
+    }
+
+    #[tokio::test]
+    async fn search_payments_401() {
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let get = usecase
+            .search_payments(&auth, PaymentFilter::default())
+            .await;
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn search_payments_500() {
+        let claims: Claims = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_groups_by_user()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .search_payments(&auth, PaymentFilter::default())
+            .await;
+        assert_eq!(get, Err(UseCaseError::InternalServerError));
+    }
+
+    #[tokio::test]
+    async fn validate_payments_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone()];
+        let id = group.id.clone();
+
+        let mut valid: Payment = Faker.fake();
+        valid.currency = "JPY".to_string();
+        valid.category = None;
+        valid.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 1000,
+        }];
+        valid.debtors = valid.creditors.clone();
+        let mut invalid = valid.clone();
+        invalid.currency = "yen".to_string();
+        let expected = invalid.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payments_by_group()
+            .returning(move |_| Ok(vec![valid.clone(), invalid.clone()]));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.validate_payments(&auth, &id).await.unwrap();
+        assert_eq!(get.len(), 1);
+        assert_eq!(get[0].payment.id, expected);
+        assert_eq!(get[0].violations, vec![PaymentViolation::InvalidCurrency]);
+    }
+
+    #[tokio::test]
+    async fn warikan_by_group_200() {
+        let claims: Claims = Faker.fake();
+        let (a, b) = (UserID::new(&claims.sub), UserID::new("b"));
+        let mut group: Group = Faker.fake();
+        group.participants = vec![a.clone(), b.clone()];
+        group.require_confirmation = true;
+        let id = group.id.clone();
+
+        let mut confirmed: Payment = Faker.fake();
+        confirmed.creditors = vec![Amount {
+            user: a.clone(),
+            amount: 1000,
+        }];
+        confirmed.debtors = vec![Amount {
+            user: b.clone(),
+            amount: 1000,
+        }];
+        confirmed.confirmations = Vec::new();
+        let pending = confirmed.clone();
+        confirmed.answer(&b, PaymentStatus::Confirmed, None, Utc::now());
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payments_by_group()
+            .returning(move |_| Ok(vec![confirmed.clone(), pending.clone()]));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let confirmed = usecase
+            .warikan_by_group(&auth, &id, false, false, &SettlementOptions::default())
+            .await
+            .unwrap();
+        let all = usecase
+            .warikan_by_group(&auth, &id, true, false, &SettlementOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(confirmed.iter().map(|w| w.amount).sum::<i32>(), 1000);
+        assert_eq!(all.iter().map(|w| w.amount).sum::<i32>(), 2000);
+    }
+
+    #[tokio::test]
+    async fn warikan_by_group_400() {
+        let claims: Claims = Faker.fake();
+        let id: GroupID = Faker.fake();
+        let options = SettlementOptions {
+            rounding: Some(3),
+            ..Default::default()
+        };
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .warikan_by_group(&auth, &id, false, false, &options)
+            .await;
+        assert_eq!(get, Err(UseCaseError::BadRequest));
+    }
+
+    #[tokio::test]
+    async fn statement_by_group_200() {
+        let claims: Claims = Faker.fake();
+        let (a, b) = (UserID::new(&claims.sub), UserID::new("b"));
+        let mut group: Group = Faker.fake();
+        group.participants = vec![a.clone(), b.clone()];
+        group.require_confirmation = false;
+        let id = group.id.clone();
+
+        let mut payment: Payment = Faker.fake();
+        payment.creditors = vec![Amount {
+            user: a.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = vec![Amount {
+            user: b.clone(),
+            amount: 1000,
+        }];
+        let payments = vec![payment.clone(), payment];
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payments_by_group()
+            .returning(move |_| Ok(payments.clone()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase
+            .statement_by_group(&auth, &id, &b, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            get.iter().map(|entry| entry.balance).collect::<Vec<_>>(),
+            vec![-1000, -2000]
+        );
+    }
+
+    #[tokio::test]
+    async fn statement_by_group_404() {
+        let claims: Claims = Faker.fake();
+        let id: GroupID = Faker.fake();
+        let user: UserID = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.statement_by_group(&auth, &id, &user, false).await;
+        assert_eq!(get, Err(UseCaseError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn validate_payments_404() {
+        let claims: Claims = Faker.fake();
+        let id: GroupID = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group().returning(move |_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.validate_payments(&auth, &id).await;
+        assert_eq!(get, Err(UseCaseError::NotFound));
+    }
 }
@@ -1,9 +1,12 @@
 use crate::{
-    entities::{Amount, AuthState, Payment, PaymentID, UserID},
-    usecases::{UseCase, UseCaseError},
+    entities::{
+        Amount, AuthState, Group, Payment, PaymentID, PaymentKind, PaymentStatus, Receipt, UserID,
+    },
+    usecases::{FieldError, ReceiptInput, UseCase, UseCaseError},
 };
-use async_graphql::InputObject;
-use chrono::Utc;
+use async_graphql::{InputObject, MaybeUndefined};
+use chrono::{DateTime, Utc};
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -13,8 +16,23 @@ use fake::Dummy;
 pub struct UpdatePaymentInput {
     pub id: PaymentID,
     pub title: Option<String>,
+    #[cfg_attr(test, dummy(default))]
+    pub kind: Option<PaymentKind>,
     pub creditors: Option<Vec<AmountInput>>,
     pub debtors: Option<Vec<AmountInput>>,
+    pub tags: Option<Vec<String>>,
+    /// `null` removes the payment from its category.
+    #[cfg_attr(test, dummy(default))]
+    pub category: MaybeUndefined<String>,
+    /// When the payment happened.
+    pub date: Option<DateTime<Utc>>,
+    /// Replaces the line items and recomputes the debtors from them. `null`
+    /// drops the receipt and keeps the debtors as they are.
+    #[cfg_attr(test, dummy(default))]
+    pub receipt: MaybeUndefined<ReceiptInput>,
+
+    /// Version the client last read. The update is rejected if the payment changed since.
+    pub expected_version: Option<i32>,
 }
 
 // https://github.com/async-graphql/async-graphql/issues/218
@@ -34,33 +52,29 @@ impl From<AmountInput> for Amount {
     }
 }
 
+impl From<&AmountInput> for Amount {
+    fn from(input: &AmountInput) -> Self {
+        Amount {
+            user: input.user.clone(),
+            amount: input.amount,
+        }
+    }
+}
+
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn update_payment(
         &self,
         auth: &AuthState,
         input: UpdatePaymentInput,
     ) -> Result<Payment, UseCaseError> {
-        if let Some(payment) = self.get_payment(auth, &input.id).await? {
-            let payment = Payment {
-                id: input.id,
-                created_at: payment.created_at,
-                updated_at: Utc::now(),
-                title: input.title.unwrap_or(payment.title),
-                creditors: input
-                    .creditors
-                    .map(|v| v.into_iter().map(|a| a.into()).collect())
-                    .unwrap_or(payment.creditors),
-                debtors: input
-                    .debtors
-                    .map(|v| v.into_iter().map(|a| a.into()).collect())
-                    .unwrap_or(payment.debtors),
-                group: payment.group,
-            };
-            let payment = self
-                .repository
-                .update_payment(payment)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+        if let Some(payment) = self.repository.get_payment(&input.id).await? {
+            let group = self
+                .get_group(auth, &payment.group)
+                .await?
+                .ok_or(UseCaseError::NotFound)?;
+            let payment = amend_payment(auth, &group, payment, input)?;
+            let payment = self.repository.update_payment(payment).await?;
             Ok(payment)
         } else {
             Err(UseCaseError::NotFound)?
@@ -68,12 +82,88 @@ impl UseCase {
     }
 }
 
+/// Applies `input` to `payment` in `group` without storing the result.
+pub(super) fn amend_payment(
+    auth: &AuthState,
+    group: &Group,
+    payment: Payment,
+    input: UpdatePaymentInput,
+) -> Result<Payment, UseCaseError> {
+    if input
+        .expected_version
+        .is_some_and(|version| version != payment.version)
+    {
+        Err(UseCaseError::Conflict)?
+    }
+    if input.debtors.is_some() && input.receipt.is_value() {
+        let error = FieldError::new("receipt", "cannot be combined with debtors");
+        Err(UseCaseError::InvalidInput(vec![error]))?
+    }
+    let mut category = payment.category;
+    input.category.update_to(&mut category);
+    let mut receipt = payment.receipt;
+    input
+        .receipt
+        .map_value(|receipt| Receipt::from(&receipt))
+        .update_to(&mut receipt);
+    let debtors = match (input.debtors, &receipt) {
+        (Some(debtors), _) => debtors.into_iter().map(Amount::from).collect(),
+        (None, Some(receipt)) => receipt.debtors().unwrap_or_default(),
+        (None, None) => payment.debtors.clone(),
+    };
+    let creditors = input
+        .creditors
+        .map(|v| v.into_iter().map(|a| a.into()).collect())
+        .unwrap_or(payment.creditors.clone());
+    // Changing the amounts asks every debtor to confirm their share again.
+    let amended = creditors != payment.creditors
+        || debtors != payment.debtors
+        || input.kind.is_some_and(|kind| kind != payment.kind);
+    let now = Utc::now();
+    let mut payment = Payment {
+        id: input.id,
+        created_at: payment.created_at,
+        updated_at: now,
+        title: input.title.unwrap_or(payment.title),
+        kind: input.kind.unwrap_or(payment.kind),
+        creditors,
+        debtors,
+        tags: input.tags.unwrap_or(payment.tags),
+        category,
+        occurred_at: input.date.or(payment.occurred_at),
+        currency: payment.currency,
+        receipt,
+        confirmations: if amended {
+            Vec::new()
+        } else {
+            payment.confirmations
+        },
+        group: payment.group,
+        version: payment.version,
+    };
+    if let (true, AuthState::Authorized(claims)) = (amended, auth) {
+        payment.answer(
+            &UserID::new(&claims.sub),
+            PaymentStatus::Confirmed,
+            None,
+            now,
+        );
+    }
+    let violations = payment.violations(group);
+    if !violations.is_empty() {
+        let errors = violations.iter().map(FieldError::from).collect();
+        Err(UseCaseError::InvalidInput(errors))?
+    }
+    Ok(payment)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        entities::{Claims, Group},
-        repositories::MockRepository,
+        entities::{Claims, Group, Item, DEFAULT_CURRENCY},
+        repositories::{MockRepository, RepositoryError},
+        usecases::ItemInput,
     };
     use fake::{Fake, Faker};
     use std::sync::Arc;
@@ -81,21 +171,35 @@ mod tests {
     #[tokio::test]
     async fn update_payment_200() {
         let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
         let mut group: Group = Faker.fake();
-        group.participants.push(UserID::new(&claims.sub));
+        group.participants.push(user.clone());
         let mut payment: Payment = Faker.fake();
         payment.group = group.id.clone();
+        payment.currency = DEFAULT_CURRENCY.to_string();
+        payment.category = None;
+        payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = payment.creditors.clone();
         let mut input: UpdatePaymentInput = Faker.fake();
         input.id = payment.id.clone();
+        input.kind = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.receipt = MaybeUndefined::Undefined;
+        input.expected_version = Some(payment.version);
         let id = group.id.clone();
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
+            .times(1)
             .returning(move |_| Ok(Some(group.clone())));
         mock.expect_get_payment()
             .returning(move |_| Ok(Some(payment.clone())));
-        mock.expect_update_payment()
-            .returning(move |payment| Ok(payment));
+        mock.expect_update_payment().returning(Ok);
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -104,6 +208,124 @@ mod tests {
         assert_eq!(update.group, id);
     }
 
+    #[tokio::test]
+    async fn update_payment_200_receipt() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let other: UserID = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants = vec![user.clone(), other.clone()];
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.currency = DEFAULT_CURRENCY.to_string();
+        payment.category = None;
+        payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = payment.creditors.clone();
+        payment.receipt = Some(Receipt {
+            items: vec![Item {
+                name: "Pizza".to_string(),
+                price: 1000,
+                quantity: 1,
+                users: vec![user.clone()],
+            }],
+            tax: 0,
+            service: 0,
+            tip: 0,
+        });
+        let mut input: UpdatePaymentInput = Faker.fake();
+        input.id = payment.id.clone();
+        input.kind = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.receipt = MaybeUndefined::Undefined;
+        input.expected_version = Some(payment.version);
+        input.receipt = MaybeUndefined::Value(ReceiptInput {
+            items: vec![ItemInput {
+                name: "Pizza".to_string(),
+                price: 1000,
+                quantity: 1,
+                users: vec![user.clone(), other.clone()],
+            }],
+            tax: 0,
+            service: 0,
+            tip: 0,
+        });
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_update_payment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_payment(&auth, input).await.unwrap();
+        assert_eq!(
+            update.debtors,
+            vec![
+                Amount { user, amount: 500 },
+                Amount {
+                    user: other,
+                    amount: 500
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn update_payment_400() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.currency = DEFAULT_CURRENCY.to_string();
+        payment.category = None;
+        payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = payment.creditors.clone();
+        let mut input: UpdatePaymentInput = Faker.fake();
+        input.id = payment.id.clone();
+        input.kind = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.receipt = MaybeUndefined::Undefined;
+        input.expected_version = Some(payment.version);
+        input.debtors = Some(vec![AmountInput {
+            user: Faker.fake(),
+            amount: -1000,
+        }]);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_payment(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = update else {
+            panic!("expected invalid input, got {update:?}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["debtors[0].user", "debtors[0].amount", "debtors"]
+        );
+    }
+
     #[tokio::test]
     async fn update_payment_404() {
         let claims: Claims = Faker.fake();
@@ -119,15 +341,71 @@ mod tests {
         assert_eq!(update, Err(UseCaseError::NotFound));
     }
 
+    #[tokio::test]
+    async fn update_payment_409() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut payment: Payment = Faker.fake();
+        payment.group = group.id.clone();
+        payment.currency = DEFAULT_CURRENCY.to_string();
+        payment.category = None;
+        payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = payment.creditors.clone();
+        let mut input: UpdatePaymentInput = Faker.fake();
+        input.id = payment.id.clone();
+        input.kind = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.receipt = MaybeUndefined::Undefined;
+        input.expected_version = Some(payment.version);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_payment()
+            .returning(move |_| Ok(Some(payment.clone())));
+        mock.expect_update_payment().returning(move |_| {
+            Err(RepositoryError::Conflict(
+                "version is no longer current".to_string(),
+            ))
+        });
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_payment(&auth, input).await;
+        assert_eq!(update, Err(UseCaseError::Conflict));
+    }
+
     #[tokio::test]
     async fn update_payment_500() {
         let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
         let mut group: Group = Faker.fake();
-        group.participants.push(UserID::new(&claims.sub));
+        group.participants.push(user.clone());
         let mut payment: Payment = Faker.fake();
         payment.group = group.id.clone();
+        payment.currency = DEFAULT_CURRENCY.to_string();
+        payment.category = None;
+        payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 1000,
+        }];
+        payment.debtors = payment.creditors.clone();
         let mut input: UpdatePaymentInput = Faker.fake();
         input.id = payment.id.clone();
+        input.kind = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.receipt = MaybeUndefined::Undefined;
+        input.expected_version = Some(payment.version);
 
         let mut mock = MockRepository::new();
         mock.expect_get_group()
@@ -135,7 +413,7 @@ mod tests {
         mock.expect_get_payment()
             .returning(move |_| Ok(Some(payment.clone())));
         mock.expect_update_payment()
-            .returning(move |_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -0,0 +1,315 @@
+use crate::{
+    entities::{
+        Amount, AuthState, Group, GroupID, PaymentID, RecurrenceUnit, RecurringPayment,
+        RecurringPaymentID, UserID, DEFAULT_CURRENCY,
+    },
+    usecases::{AmountInput, FieldError, UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use chrono::{DateTime, Duration, Utc};
+use nanoid::nanoid;
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct CreateRecurringPaymentInput {
+    pub title: String,
+    #[graphql(default)]
+    pub tags: Vec<String>,
+    /// One of the group's categories.
+    pub category: Option<String>,
+    /// ISO 4217 code; defaults to JPY.
+    pub currency: Option<String>,
+    pub creditors: Vec<AmountInput>,
+    pub debtors: Vec<AmountInput>,
+    pub unit: RecurrenceUnit,
+    /// Number of `unit`s between two occurrences.
+    #[graphql(default = 1)]
+    pub interval: i32,
+    /// Date of the first occurrence, at most 31 days in the past. Occurrences
+    /// already due are created by the next scheduler run.
+    pub starts_at: DateTime<Utc>,
+    /// No occurrences are created after this date.
+    pub ends_at: Option<DateTime<Utc>>,
+    pub group: GroupID,
+}
+
+impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn create_recurring_payment(
+        &self,
+        auth: &AuthState,
+        input: CreateRecurringPaymentInput,
+    ) -> Result<RecurringPayment, UseCaseError> {
+        if let (AuthState::Authorized(claims), Some(group)) =
+            (auth, self.get_group(auth, &input.group).await?)
+        {
+            let now = Utc::now();
+            let mut recurring_payment = RecurringPayment {
+                id: RecurringPaymentID::new(nanoid!()),
+                created_at: now,
+                updated_at: now,
+                owner: UserID::new(&claims.sub),
+                title: input.title,
+                creditors: input.creditors.iter().map(Amount::from).collect(),
+                debtors: input.debtors.iter().map(Amount::from).collect(),
+                tags: input.tags,
+                category: input.category,
+                currency: input
+                    .currency
+                    .unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+                unit: input.unit,
+                interval: input.interval,
+                starts_at: input.starts_at,
+                ends_at: input.ends_at,
+                occurrences: 0,
+                next_at: None,
+                paused: false,
+                group: input.group,
+                version: 0,
+            };
+            recurring_payment.restart(input.starts_at);
+            let mut errors = check_recurring_payment(&recurring_payment, &group);
+            errors.extend(check_start("startsAt", input.starts_at, now));
+            if !errors.is_empty() {
+                Err(UseCaseError::InvalidInput(errors))?
+            }
+            let recurring_payment = self
+                .repository
+                .create_recurring_payment(recurring_payment)
+                .await?;
+            Ok(recurring_payment)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+}
+
+/// How far back a schedule may start. Every occurrence since then is
+/// created at once, so this bounds how many payments one request can add.
+pub(super) const MAX_BACKFILL_DAYS: i64 = 31;
+
+/// Checks a date the client (re)starts the schedule from.
+pub(super) fn check_start(
+    field: &str,
+    at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<FieldError> {
+    (at < now - Duration::days(MAX_BACKFILL_DAYS)).then(|| {
+        FieldError::new(
+            field,
+            format!("must not be more than {MAX_BACKFILL_DAYS} days in the past"),
+        )
+    })
+}
+
+/// Checks the schedule, and the payment every occurrence would create
+/// against the group.
+pub(super) fn check_recurring_payment(
+    recurring_payment: &RecurringPayment,
+    group: &Group,
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if recurring_payment.title.trim().is_empty() {
+        errors.push(FieldError::new("title", "must not be empty"));
+    }
+    if recurring_payment.interval < 1 {
+        errors.push(FieldError::new("interval", "must be at least 1"));
+    }
+    if recurring_payment
+        .ends_at
+        .is_some_and(|ends_at| ends_at < recurring_payment.starts_at)
+    {
+        errors.push(FieldError::new("endsAt", "must not be before startsAt"));
+    }
+    let payment = recurring_payment.payment(
+        PaymentID::new(recurring_payment.id.to_string()),
+        recurring_payment.starts_at,
+    );
+    errors.extend(payment.violations(group).iter().map(FieldError::from));
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{AuthError, Claims},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn create_recurring_payment_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut input: CreateRecurringPaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.category = None;
+        input.currency = None;
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.debtors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.unit = RecurrenceUnit::Month;
+        input.interval = 1;
+        input.starts_at = Utc::now();
+        input.ends_at = None;
+        let starts_at = input.starts_at;
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_recurring_payment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase
+            .create_recurring_payment(&auth, input)
+            .await
+            .unwrap();
+        assert_eq!(create.owner, user);
+        assert_eq!(create.next_at, Some(starts_at));
+    }
+
+    #[tokio::test]
+    async fn create_recurring_payment_400() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut input: CreateRecurringPaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.category = None;
+        input.currency = None;
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.debtors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.unit = RecurrenceUnit::Month;
+        input.starts_at = Utc::now();
+        input.interval = 0;
+        input.ends_at = Some(input.starts_at - Duration::days(1));
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_recurring_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_recurring_payment(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = create else {
+            panic!("expected invalid input, got {create:?}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["interval", "endsAt"]);
+    }
+
+    #[tokio::test]
+    async fn create_recurring_payment_400_far_past() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut input: CreateRecurringPaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.category = None;
+        input.currency = None;
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.debtors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.interval = 1;
+        input.ends_at = None;
+        input.unit = RecurrenceUnit::Day;
+        input.starts_at = Utc::now() - Duration::days(MAX_BACKFILL_DAYS + 1);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_recurring_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_recurring_payment(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = create else {
+            panic!("expected invalid input, got {create:?}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["startsAt"]);
+    }
+
+    #[tokio::test]
+    async fn create_recurring_payment_401() {
+        let input: CreateRecurringPaymentInput = Faker.fake();
+
+        let mock = MockRepository::new();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
+
+        let create = usecase.create_recurring_payment(&auth, input).await;
+        assert_eq!(
+            create,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_recurring_payment_500() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut input: CreateRecurringPaymentInput = Faker.fake();
+        input.group = group.id.clone();
+        input.category = None;
+        input.currency = None;
+        input.creditors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.debtors = vec![AmountInput {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        input.unit = RecurrenceUnit::Month;
+        input.interval = 1;
+        input.starts_at = Utc::now();
+        input.ends_at = None;
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_recurring_payment()
+            .returning(move |_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_recurring_payment(&auth, input).await;
+        assert_eq!(create, Err(UseCaseError::InternalServerError));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    entities::{AuthState, RecurringPaymentID},
+    usecases::{UseCase, UseCaseError},
+};
+use async_graphql::InputObject;
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct DeleteRecurringPaymentInput {
+    pub id: RecurringPaymentID,
+}
+
+impl UseCase {
+    /// Stops the schedule. Payments already created from it are kept.
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn delete_recurring_payment(
+        &self,
+        auth: &AuthState,
+        input: DeleteRecurringPaymentInput,
+    ) -> Result<RecurringPaymentID, UseCaseError> {
+        if let Some(recurring_payment) = self.get_recurring_payment(auth, &input.id).await? {
+            self.repository
+                .delete_recurring_payment(&recurring_payment.id)
+                .await?;
+            Ok(recurring_payment.id)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Group, RecurringPayment, UserID},
+        repositories::MockRepository,
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn delete_recurring_payment_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        let input = DeleteRecurringPaymentInput {
+            id: recurring_payment.id.clone(),
+        };
+        let id = recurring_payment.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_delete_recurring_payment()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase
+            .delete_recurring_payment(&auth, input)
+            .await
+            .unwrap();
+        assert_eq!(delete, id);
+    }
+
+    #[tokio::test]
+    async fn delete_recurring_payment_404() {
+        let claims: Claims = Faker.fake();
+        let input: DeleteRecurringPaymentInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_recurring_payment().returning(|_| Ok(None));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let delete = usecase.delete_recurring_payment(&auth, input).await;
+        assert_eq!(delete, Err(UseCaseError::NotFound));
+    }
+}
@@ -0,0 +1,9 @@
+mod create;
+mod delete;
+mod read;
+mod schedule;
+mod update;
+
+pub use create::*;
+pub use delete::*;
+pub use update::*;
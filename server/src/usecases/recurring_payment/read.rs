@@ -0,0 +1,92 @@
+use crate::{
+    entities::{AuthState, GroupID, RecurringPayment, RecurringPaymentID},
+    usecases::{UseCase, UseCaseError},
+};
+use tracing::instrument;
+
+impl UseCase {
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_recurring_payment(
+        &self,
+        auth: &AuthState,
+        id: &RecurringPaymentID,
+    ) -> Result<Option<RecurringPayment>, UseCaseError> {
+        if let Some(recurring_payment) = self.repository.get_recurring_payment(id).await? {
+            let recurring_payment = self
+                .get_group(auth, &recurring_payment.group)
+                .await?
+                .and(Some(recurring_payment));
+            Ok(recurring_payment)
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[instrument(skip(self, auth), err(level = "debug"))]
+    pub async fn get_recurring_payments_by_group(
+        &self,
+        auth: &AuthState,
+        id: &GroupID,
+    ) -> Result<Vec<RecurringPayment>, UseCaseError> {
+        if self.get_group(auth, id).await?.is_some() {
+            let recurring_payments = self.repository.get_recurring_payments_by_group(id).await?;
+            Ok(recurring_payments)
+        } else {
+            Err(UseCaseError::NotFound)?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, Group, UserID},
+        repositories::MockRepository,
+    };
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn get_recurring_payment_200() {
+        let claims: Claims = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(UserID::new(&claims.sub));
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        let id = recurring_payment.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_recurring_payment(&auth, &id).await.unwrap();
+        assert_eq!(get.map(|r| r.id), Some(id));
+    }
+
+    #[tokio::test]
+    async fn get_recurring_payment_403() {
+        let claims: Claims = Faker.fake();
+        let group: Group = Faker.fake();
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        let id = recurring_payment.id.clone();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let get = usecase.get_recurring_payment(&auth, &id).await;
+        assert_eq!(get, Err(UseCaseError::Forbidden));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    entities::{Amount, AuthState, PaymentKind, RecurringPayment},
+    usecases::{AmountInput, CreatePaymentInput, UseCase, UseCaseError},
+};
+use chrono::{DateTime, Utc};
+use tracing::{instrument, warn};
+
+/// Most payments one template catches up on per run, so that a long outage
+/// does not flood a group all at once.
+const MAX_OCCURRENCES_PER_RUN: usize = 31;
+
+impl UseCase {
+    /// Creates a payment for every occurrence due at `now`, acting as each
+    /// template's owner, and returns how many were created. Templates whose
+    /// payments are rejected, e.g. because the owner left the group, are
+    /// paused; other failures are retried on the next run.
+    #[instrument(skip(self), err(level = "debug"))]
+    pub async fn run_recurring_payments(&self, now: DateTime<Utc>) -> Result<usize, UseCaseError> {
+        let mut created = 0;
+        for recurring_payment in self.repository.get_due_recurring_payments(&now).await? {
+            created += self.run_recurring_payment(recurring_payment, now).await;
+        }
+        Ok(created)
+    }
+
+    async fn run_recurring_payment(
+        &self,
+        mut recurring_payment: RecurringPayment,
+        now: DateTime<Utc>,
+    ) -> usize {
+        let auth = AuthState::Authorized(recurring_payment.owner_claims(now));
+        let mut created = 0;
+        let mut changed = false;
+        while created < MAX_OCCURRENCES_PER_RUN {
+            let Some(at) = recurring_payment.next_at.filter(|at| *at <= now) else {
+                break;
+            };
+            let input = CreatePaymentInput {
+                title: recurring_payment.title.clone(),
+                kind: PaymentKind::Expense,
+                tags: recurring_payment.tags.clone(),
+                category: recurring_payment.category.clone(),
+                date: Some(at),
+                currency: Some(recurring_payment.currency.clone()),
+                creditors: amount_inputs(&recurring_payment.creditors),
+                debtors: amount_inputs(&recurring_payment.debtors),
+                split: None,
+                receipt: None,
+                group: recurring_payment.group.clone(),
+                // Keyed by occurrence so that a retry after a lost update does not duplicate it.
+                idempotency_key: Some(format!(
+                    "recurring:{}:{}",
+                    recurring_payment.id.to_string(),
+                    at.timestamp()
+                )),
+            };
+            match self.create_payment(&auth, input).await {
+                Ok(_) => {
+                    created += 1;
+                    changed = true;
+                    recurring_payment.advance();
+                }
+                Err(UseCaseError::InternalServerError | UseCaseError::Conflict) => break,
+                Err(err) => {
+                    warn!(id = ?recurring_payment.id, %err, "pausing recurring payment");
+                    recurring_payment.paused = true;
+                    changed = true;
+                    break;
+                }
+            }
+        }
+        if changed {
+            recurring_payment.updated_at = now;
+            if let Err(err) = self
+                .repository
+                .update_recurring_payment(recurring_payment)
+                .await
+            {
+                warn!(%err, "failed to record recurring payment progress");
+            }
+        }
+        created
+    }
+}
+
+fn amount_inputs(amounts: &[Amount]) -> Vec<AmountInput> {
+    amounts
+        .iter()
+        .map(|amount| AmountInput {
+            user: amount.user.clone(),
+            amount: amount.amount,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Group, RecurrenceUnit, UserID, DEFAULT_CURRENCY},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use chrono::Duration;
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn run_recurring_payments_200() {
+        let now = Utc::now();
+        let user: UserID = Faker.fake();
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.owner = user.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Week;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.paused = false;
+        recurring_payment.restart(now - Duration::days(10));
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_due_recurring_payments()
+            .returning(move |_| Ok(vec![recurring_payment.clone()]));
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_idempotency_key().returning(Ok);
+        mock.expect_create_payment().times(2).returning(Ok);
+        mock.expect_update_recurring_payment()
+            .withf(move |r| r.occurrences == 2 && r.next_at.is_some_and(|at| at > now))
+            .times(1)
+            .returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+
+        let run = usecase.run_recurring_payments(now).await.unwrap();
+        assert_eq!(run, 2);
+    }
+
+    #[tokio::test]
+    async fn run_recurring_payments_200_paused() {
+        let now = Utc::now();
+        let user: UserID = Faker.fake();
+        let group: Group = Faker.fake();
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.owner = user.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Week;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.paused = false;
+        recurring_payment.restart(now - Duration::days(10));
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_due_recurring_payments()
+            .returning(move |_| Ok(vec![recurring_payment.clone()]));
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_create_payment().never();
+        mock.expect_update_recurring_payment()
+            .withf(|r| r.paused && r.occurrences == 0)
+            .times(1)
+            .returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+
+        let run = usecase.run_recurring_payments(now).await.unwrap();
+        assert_eq!(run, 0);
+    }
+
+    #[tokio::test]
+    async fn run_recurring_payments_500() {
+        let now = Utc::now();
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_due_recurring_payments()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+
+        let run = usecase.run_recurring_payments(now).await;
+        assert_eq!(run, Err(UseCaseError::InternalServerError));
+    }
+}
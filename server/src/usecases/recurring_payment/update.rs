@@ -0,0 +1,532 @@
+use super::create::{check_recurring_payment, check_start};
+use crate::{
+    entities::{Amount, AuthState, Group, RecurrenceUnit, RecurringPayment, RecurringPaymentID},
+    usecases::{AmountInput, FieldError, UseCase, UseCaseError},
+};
+use async_graphql::{InputObject, MaybeUndefined};
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+
+#[cfg(test)]
+use fake::Dummy;
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct UpdateRecurringPaymentInput {
+    pub id: RecurringPaymentID,
+    pub title: Option<String>,
+    pub creditors: Option<Vec<AmountInput>>,
+    pub debtors: Option<Vec<AmountInput>>,
+    pub tags: Option<Vec<String>>,
+    /// `null` removes the category from future payments.
+    #[cfg_attr(test, dummy(default))]
+    pub category: MaybeUndefined<String>,
+    pub unit: Option<RecurrenceUnit>,
+    pub interval: Option<i32>,
+    /// Moves the next occurrence, at most 31 days into the past. Changing
+    /// `unit`, `interval` or this restarts the schedule from the next
+    /// occurrence. An ended schedule stays ended unless this or `endsAt` moves.
+    pub next_at: Option<DateTime<Utc>>,
+    /// `null` keeps the schedule running indefinitely.
+    #[cfg_attr(test, dummy(default))]
+    pub ends_at: MaybeUndefined<DateTime<Utc>>,
+    /// Paused templates create no payments. Occurrences that fell due while
+    /// paused are skipped when resuming.
+    pub paused: Option<bool>,
+
+    /// Version the client last read. The update is rejected if the template changed since.
+    pub expected_version: Option<i32>,
+}
+
+#[derive(InputObject)]
+#[cfg_attr(test, derive(Dummy))]
+pub struct SkipRecurringPaymentInput {
+    pub id: RecurringPaymentID,
+    /// Number of upcoming occurrences to skip.
+    #[graphql(default = 1)]
+    pub count: i32,
+    pub expected_version: Option<i32>,
+}
+
+impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn update_recurring_payment(
+        &self,
+        auth: &AuthState,
+        input: UpdateRecurringPaymentInput,
+    ) -> Result<RecurringPayment, UseCaseError> {
+        let (mut recurring_payment, group) = self
+            .get_recurring_payment_for_update(auth, &input.id, input.expected_version)
+            .await?;
+        let now = Utc::now();
+        let restart = input.unit.is_some() || input.interval.is_some() || input.next_at.is_some();
+
+        if let Some(title) = input.title {
+            recurring_payment.title = title;
+        }
+        if let Some(creditors) = input.creditors {
+            recurring_payment.creditors = creditors.iter().map(Amount::from).collect();
+        }
+        if let Some(debtors) = input.debtors {
+            recurring_payment.debtors = debtors.iter().map(Amount::from).collect();
+        }
+        if let Some(tags) = input.tags {
+            recurring_payment.tags = tags;
+        }
+        input.category.update_to(&mut recurring_payment.category);
+        if let Some(unit) = input.unit {
+            recurring_payment.unit = unit;
+        }
+        if let Some(interval) = input.interval {
+            recurring_payment.interval = interval;
+        }
+        let ends_at_moved = !input.ends_at.is_undefined();
+        input.ends_at.update_to(&mut recurring_payment.ends_at);
+        let at = input.next_at.or(recurring_payment.next_at);
+        if let Some(at) = at.filter(|_| restart) {
+            recurring_payment.restart(at);
+        } else if recurring_payment.next_at.is_some() || ends_at_moved {
+            recurring_payment.next_at = recurring_payment.occurrence(recurring_payment.occurrences);
+        }
+        match input.paused {
+            Some(true) => recurring_payment.paused = true,
+            Some(false) if recurring_payment.paused => {
+                recurring_payment.paused = false;
+                recurring_payment.skip_until(now);
+            }
+            _ => {}
+        }
+        recurring_payment.updated_at = now;
+
+        let mut errors = check_recurring_payment(&recurring_payment, &group);
+        if let Some(next_at) = input.next_at {
+            errors.extend(check_start("nextAt", next_at, now));
+        }
+        if !errors.is_empty() {
+            Err(UseCaseError::InvalidInput(errors))?
+        }
+        let recurring_payment = self
+            .repository
+            .update_recurring_payment(recurring_payment)
+            .await?;
+        Ok(recurring_payment)
+    }
+
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
+    pub async fn skip_recurring_payment(
+        &self,
+        auth: &AuthState,
+        input: SkipRecurringPaymentInput,
+    ) -> Result<RecurringPayment, UseCaseError> {
+        if input.count < 1 {
+            let error = FieldError::new("count", "must be at least 1");
+            Err(UseCaseError::InvalidInput(vec![error]))?
+        }
+        let (mut recurring_payment, _) = self
+            .get_recurring_payment_for_update(auth, &input.id, input.expected_version)
+            .await?;
+        for _ in 0..input.count {
+            if recurring_payment.next_at.is_none() {
+                break;
+            }
+            recurring_payment.advance();
+        }
+        recurring_payment.updated_at = Utc::now();
+        let recurring_payment = self
+            .repository
+            .update_recurring_payment(recurring_payment)
+            .await?;
+        Ok(recurring_payment)
+    }
+
+    async fn get_recurring_payment_for_update(
+        &self,
+        auth: &AuthState,
+        id: &RecurringPaymentID,
+        expected_version: Option<i32>,
+    ) -> Result<(RecurringPayment, Group), UseCaseError> {
+        let recurring_payment = self
+            .get_recurring_payment(auth, id)
+            .await?
+            .ok_or(UseCaseError::NotFound)?;
+        if expected_version.is_some_and(|version| version != recurring_payment.version) {
+            Err(UseCaseError::Conflict)?
+        }
+        let group = self
+            .get_group(auth, &recurring_payment.group)
+            .await?
+            .ok_or(UseCaseError::NotFound)?;
+        Ok((recurring_payment, group))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Claims, UserID, DEFAULT_CURRENCY},
+        repositories::{MockRepository, RepositoryError},
+    };
+    use chrono::{Duration, TimeZone};
+    use fake::{Fake, Faker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn update_recurring_payment_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Month;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.paused = false;
+        recurring_payment.restart(Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap());
+        let mut input: UpdateRecurringPaymentInput = Faker.fake();
+        input.id = recurring_payment.id.clone();
+        input.title = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.next_at = None;
+        input.ends_at = MaybeUndefined::Undefined;
+        input.paused = None;
+        input.expected_version = Some(recurring_payment.version);
+        input.unit = Some(RecurrenceUnit::Week);
+        input.interval = Some(2);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_update_recurring_payment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase
+            .update_recurring_payment(&auth, input)
+            .await
+            .unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap();
+        assert_eq!(update.starts_at, start);
+        assert_eq!(update.occurrence(1), Some(start + Duration::weeks(2)));
+    }
+
+    #[tokio::test]
+    async fn update_recurring_payment_200_resume() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Month;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.restart(Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap());
+        recurring_payment.paused = true;
+        let mut input: UpdateRecurringPaymentInput = Faker.fake();
+        input.id = recurring_payment.id.clone();
+        input.title = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.unit = None;
+        input.interval = None;
+        input.next_at = None;
+        input.ends_at = MaybeUndefined::Undefined;
+        input.expected_version = Some(recurring_payment.version);
+        input.paused = Some(false);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_update_recurring_payment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase
+            .update_recurring_payment(&auth, input)
+            .await
+            .unwrap();
+        assert!(!update.paused);
+        assert!(update.next_at.unwrap() >= Utc::now() - Duration::minutes(1));
+    }
+
+    #[tokio::test]
+    async fn update_recurring_payment_200_ended() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Month;
+        recurring_payment.interval = 1;
+        recurring_payment.paused = false;
+        recurring_payment.ends_at = None;
+        recurring_payment.restart(Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap());
+        recurring_payment.ends_at = recurring_payment.next_at;
+        recurring_payment.advance();
+        assert_eq!(recurring_payment.next_at, None);
+        let mut restarted: UpdateRecurringPaymentInput = Faker.fake();
+        restarted.id = recurring_payment.id.clone();
+        restarted.title = None;
+        restarted.creditors = None;
+        restarted.debtors = None;
+        restarted.category = MaybeUndefined::Undefined;
+        restarted.interval = None;
+        restarted.next_at = None;
+        restarted.ends_at = MaybeUndefined::Undefined;
+        restarted.paused = None;
+        restarted.expected_version = Some(recurring_payment.version);
+        restarted.unit = Some(RecurrenceUnit::Day);
+        let mut extended: UpdateRecurringPaymentInput = Faker.fake();
+        extended.id = recurring_payment.id.clone();
+        extended.title = None;
+        extended.creditors = None;
+        extended.debtors = None;
+        extended.category = MaybeUndefined::Undefined;
+        extended.unit = None;
+        extended.interval = None;
+        extended.next_at = None;
+        extended.paused = None;
+        extended.expected_version = Some(recurring_payment.version);
+        extended.ends_at = MaybeUndefined::Null;
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_update_recurring_payment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase
+            .update_recurring_payment(&auth, restarted)
+            .await
+            .unwrap();
+        assert_eq!(update.next_at, None);
+
+        let update = usecase
+            .update_recurring_payment(&auth, extended)
+            .await
+            .unwrap();
+        assert_eq!(
+            update.next_at,
+            Some(Utc.with_ymd_and_hms(2024, 2, 25, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn update_recurring_payment_400() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Month;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.paused = false;
+        recurring_payment.restart(Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap());
+        let mut input: UpdateRecurringPaymentInput = Faker.fake();
+        input.id = recurring_payment.id.clone();
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.unit = None;
+        input.interval = None;
+        input.next_at = None;
+        input.ends_at = MaybeUndefined::Undefined;
+        input.paused = None;
+        input.expected_version = Some(recurring_payment.version);
+        input.title = Some(" ".to_string());
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_update_recurring_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_recurring_payment(&auth, input).await;
+        let Err(UseCaseError::InvalidInput(errors)) = update else {
+            panic!("expected invalid input, got {update:?}");
+        };
+        assert_eq!(errors[0].field, "title");
+    }
+
+    #[tokio::test]
+    async fn update_recurring_payment_409() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Month;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.paused = false;
+        recurring_payment.restart(Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap());
+        let mut input: UpdateRecurringPaymentInput = Faker.fake();
+        input.id = recurring_payment.id.clone();
+        input.title = None;
+        input.creditors = None;
+        input.debtors = None;
+        input.category = MaybeUndefined::Undefined;
+        input.unit = None;
+        input.interval = None;
+        input.next_at = None;
+        input.ends_at = MaybeUndefined::Undefined;
+        input.paused = None;
+        input.expected_version = Some(recurring_payment.version + 1);
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_update_recurring_payment().never();
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let update = usecase.update_recurring_payment(&auth, input).await;
+        assert_eq!(update, Err(UseCaseError::Conflict));
+    }
+
+    #[tokio::test]
+    async fn skip_recurring_payment_200() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Month;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.paused = false;
+        recurring_payment.restart(Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap());
+        let input = SkipRecurringPaymentInput {
+            id: recurring_payment.id.clone(),
+            count: 2,
+            expected_version: None,
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_update_recurring_payment().returning(Ok);
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let skip = usecase.skip_recurring_payment(&auth, input).await.unwrap();
+        assert_eq!(skip.occurrences, 2);
+        assert_eq!(
+            skip.next_at,
+            Some(Utc.with_ymd_and_hms(2024, 3, 25, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn skip_recurring_payment_500() {
+        let claims: Claims = Faker.fake();
+        let user = UserID::new(&claims.sub);
+        let mut group: Group = Faker.fake();
+        group.participants.push(user.clone());
+        let mut recurring_payment: RecurringPayment = Faker.fake();
+        recurring_payment.group = group.id.clone();
+        recurring_payment.currency = DEFAULT_CURRENCY.to_string();
+        recurring_payment.category = None;
+        recurring_payment.creditors = vec![Amount {
+            user: user.clone(),
+            amount: 80000,
+        }];
+        recurring_payment.debtors = recurring_payment.creditors.clone();
+        recurring_payment.unit = RecurrenceUnit::Month;
+        recurring_payment.interval = 1;
+        recurring_payment.ends_at = None;
+        recurring_payment.paused = false;
+        recurring_payment.restart(Utc.with_ymd_and_hms(2024, 1, 25, 0, 0, 0).unwrap());
+        let input = SkipRecurringPaymentInput {
+            id: recurring_payment.id.clone(),
+            count: 1,
+            expected_version: None,
+        };
+
+        let mut mock = MockRepository::new();
+        mock.expect_get_group()
+            .returning(move |_| Ok(Some(group.clone())));
+        mock.expect_get_recurring_payment()
+            .returning(move |_| Ok(Some(recurring_payment.clone())));
+        mock.expect_update_recurring_payment()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let skip = usecase.skip_recurring_payment(&auth, input).await;
+        assert_eq!(skip, Err(UseCaseError::InternalServerError));
+    }
+}
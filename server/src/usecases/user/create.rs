@@ -4,6 +4,7 @@ use crate::{
 };
 use async_graphql::InputObject;
 use chrono::Utc;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -15,6 +16,7 @@ pub struct CreateUserInput {
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn create_user(
         &self,
         auth: &AuthState,
@@ -28,14 +30,10 @@ impl UseCase {
                 updated_at: now,
                 name: input.name,
             };
-            let user = self
-                .repository
-                .create_user(user)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+            let user = self.repository.create_user(user).await?;
             Ok(user)
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
         }
     }
 }
@@ -43,7 +41,10 @@ impl UseCase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{entities::Claims, repositories::MockRepository};
+    use crate::{
+        entities::{AuthError, Claims},
+        repositories::{MockRepository, RepositoryError},
+    };
     use fake::{Fake, Faker};
     use std::sync::Arc;
 
@@ -54,7 +55,7 @@ mod tests {
         let name = input.name.clone();
 
         let mut mock = MockRepository::new();
-        mock.expect_create_user().returning(|user| Ok(user));
+        mock.expect_create_user().returning(Ok);
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -70,10 +71,29 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let create = usecase.create_user(&auth, input).await;
-        assert_eq!(create, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            create,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_user_409() {
+        let claims: Claims = Faker.fake();
+        let input: CreateUserInput = Faker.fake();
+
+        let mut mock = MockRepository::new();
+        mock.expect_create_user()
+            .returning(|_| Err(RepositoryError::Conflict("duplicate key".to_string())));
+
+        let usecase = UseCase::new(Arc::new(mock));
+        let auth = AuthState::Authorized(claims);
+
+        let create = usecase.create_user(&auth, input).await;
+        assert_eq!(create, Err(UseCaseError::Conflict));
     }
 
     #[tokio::test]
@@ -83,7 +103,7 @@ mod tests {
 
         let mut mock = MockRepository::new();
         mock.expect_create_user()
-            .returning(|_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -3,6 +3,7 @@ use crate::{
     usecases::{UseCase, UseCaseError},
 };
 use async_graphql::InputObject;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -14,6 +15,7 @@ pub struct DeleteUserInput {
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn delete_user(
         &self,
         auth: &AuthState,
@@ -22,10 +24,7 @@ impl UseCase {
         if let AuthState::Authorized(claims) = auth {
             if input.id == UserID::new(&claims.sub) {
                 if self.get_user_opt(auth, &input.id).await?.is_some() {
-                    self.repository
-                        .delete_user(&input.id)
-                        .await
-                        .or(Err(UseCaseError::InternalServerError))?;
+                    self.repository.delete_user(&input.id).await?;
                     Ok(input.id.clone())
                 } else {
                     Err(UseCaseError::NotFound)?
@@ -34,7 +33,7 @@ impl UseCase {
                 Err(UseCaseError::Forbidden)?
             }
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
         }
     }
 }
@@ -43,8 +42,8 @@ impl UseCase {
 mod tests {
     use super::*;
     use crate::{
-        entities::{Claims, User},
-        repositories::MockRepository,
+        entities::{AuthError, Claims, User},
+        repositories::{MockRepository, RepositoryError},
     };
     use fake::{Fake, Faker};
     use std::sync::Arc;
@@ -76,10 +75,13 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let delete = usecase.delete_user(&auth, input).await;
-        assert_eq!(delete, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            delete,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
     }
 
     #[tokio::test]
@@ -124,7 +126,7 @@ mod tests {
         mock.expect_get_user()
             .returning(move |_| Ok(Some(user.clone())));
         mock.expect_delete_user()
-            .returning(|_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -2,47 +2,44 @@ use crate::{
     entities::{AuthState, User, UserID},
     usecases::{UseCase, UseCaseError},
 };
-use futures::future::try_join_all;
+use tracing::instrument;
 
 impl UseCase {
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_user_opt(
         &self,
         auth: &AuthState,
         id: &UserID,
     ) -> Result<Option<User>, UseCaseError> {
         if let AuthState::Authorized(_) = auth {
-            let user = self
-                .repository
-                .get_user(id)
-                .await
-                .or(Err(UseCaseError::InternalServerError))?;
+            let user = self.repository.get_user(id).await?;
             Ok(user)
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
         }
     }
 
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_user(&self, auth: &AuthState, id: &UserID) -> Result<User, UseCaseError> {
         self.get_user_opt(auth, id)
             .await?
             .ok_or(UseCaseError::NotFound)
     }
 
+    #[instrument(skip(self, auth), err(level = "debug"))]
     pub async fn get_user_vec(
         &self,
         auth: &AuthState,
         ids: &[UserID],
     ) -> Result<Vec<User>, UseCaseError> {
         if let AuthState::Authorized(_) = auth {
-            let users = try_join_all(ids.iter().map(|id| self.repository.get_user(id)))
-                .await
-                .or(Err(UseCaseError::InternalServerError))?
-                .into_iter()
-                .flatten()
-                .collect();
+            if ids.is_empty() {
+                return Ok(vec![]);
+            }
+            let users = self.repository.get_users_by_ids(ids).await?;
             Ok(users)
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
         }
     }
 }
@@ -50,7 +47,10 @@ impl UseCase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{entities::Claims, repositories::MockRepository};
+    use crate::{
+        entities::{AuthError, Claims},
+        repositories::{MockRepository, RepositoryError},
+    };
     use fake::{Fake, Faker};
     use itertools::Itertools;
     use std::sync::Arc;
@@ -80,10 +80,13 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let get = usecase.get_user_opt(&auth, &id).await;
-        assert_eq!(get, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
     }
 
     #[tokio::test]
@@ -93,7 +96,7 @@ mod tests {
 
         let mut mock = MockRepository::new();
         mock.expect_get_user()
-            .returning(|_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -109,12 +112,18 @@ mod tests {
         let user: User = Faker.fake();
 
         let mut mock = MockRepository::new();
-        mock.expect_get_user()
-            .times(ids.len())
-            .returning(move |id| {
-                let mut user = user.clone();
-                user.id = id.clone();
-                Ok(Some(user))
+        mock.expect_get_users_by_ids()
+            .times(usize::from(!ids.is_empty()))
+            .returning(move |ids| {
+                let users = ids
+                    .iter()
+                    .map(|id| {
+                        let mut user = user.clone();
+                        user.id = id.clone();
+                        user
+                    })
+                    .collect();
+                Ok(users)
             });
 
         let usecase = UseCase::new(Arc::new(mock));
@@ -134,10 +143,13 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let get = usecase.get_user_vec(&auth, &ids).await;
-        assert_eq!(get, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            get,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
     }
 
     #[tokio::test]
@@ -146,15 +158,15 @@ mod tests {
         let ids: Vec<UserID> = Faker.fake();
 
         let mut mock = MockRepository::new();
-        mock.expect_get_user()
-            .returning(|_| Err(Box::new(UseCaseError::InternalServerError)));
+        mock.expect_get_users_by_ids()
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
 
         let get = usecase.get_user_vec(&auth, &ids).await;
 
-        if ids.len() == 0 {
+        if ids.is_empty() {
             assert_eq!(get, Ok(vec![]));
         } else {
             assert_eq!(get, Err(UseCaseError::InternalServerError));
@@ -4,6 +4,7 @@ use crate::{
 };
 use async_graphql::InputObject;
 use chrono::Utc;
+use tracing::instrument;
 
 #[cfg(test)]
 use fake::Dummy;
@@ -16,6 +17,7 @@ pub struct UpdateUserInput {
 }
 
 impl UseCase {
+    #[instrument(skip(self, auth, input), err(level = "debug"))]
     pub async fn update_user(
         &self,
         auth: &AuthState,
@@ -30,11 +32,7 @@ impl UseCase {
                         updated_at: Utc::now(),
                         name: input.name.unwrap_or(user.name),
                     };
-                    let user = self
-                        .repository
-                        .update_user(user)
-                        .await
-                        .or(Err(UseCaseError::InternalServerError))?;
+                    let user = self.repository.update_user(user).await?;
                     Ok(user)
                 } else {
                     Err(UseCaseError::NotFound)?
@@ -43,7 +41,7 @@ impl UseCase {
                 Err(UseCaseError::Forbidden)?
             }
         } else {
-            Err(UseCaseError::Unauthorized)?
+            Err(UseCaseError::from(auth))?
         }
     }
 }
@@ -51,7 +49,10 @@ impl UseCase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{entities::Claims, repositories::MockRepository};
+    use crate::{
+        entities::{AuthError, Claims},
+        repositories::{MockRepository, RepositoryError},
+    };
     use fake::{Fake, Faker};
     use std::sync::Arc;
 
@@ -67,7 +68,7 @@ mod tests {
         let mut mock = MockRepository::new();
         mock.expect_get_user()
             .returning(move |_| Ok(Some(user.clone())));
-        mock.expect_update_user().returning(|user| Ok(user));
+        mock.expect_update_user().returning(Ok);
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -83,10 +84,13 @@ mod tests {
         let mock = MockRepository::new();
 
         let usecase = UseCase::new(Arc::new(mock));
-        let auth = AuthState::Unauthorized;
+        let auth = AuthState::Unauthorized(AuthError::MissingToken);
 
         let update = usecase.update_user(&auth, input).await;
-        assert_eq!(update, Err(UseCaseError::Unauthorized));
+        assert_eq!(
+            update,
+            Err(UseCaseError::Unauthorized(AuthError::MissingToken))
+        );
     }
 
     #[tokio::test]
@@ -133,7 +137,7 @@ mod tests {
         mock.expect_get_user()
             .returning(move |_| Ok(Some(user.clone())));
         mock.expect_update_user()
-            .returning(|_| Err(Box::new(UseCaseError::InternalServerError)));
+            .returning(|_| Err(RepositoryError::Other("mock".to_string())));
 
         let usecase = UseCase::new(Arc::new(mock));
         let auth = AuthState::Authorized(claims);
@@ -3,7 +3,7 @@ use fake::{Fake, Faker};
 use indoc::indoc;
 use serde_json::json;
 use server::{
-    controllers::{Mutation, Query},
+    controllers::{Loaders, Mutation, Query},
     entities::{AuthState, Claims},
     repositories::{MongoRepository, MongoRepositoryConfig},
     usecases::UseCase,
@@ -37,6 +37,7 @@ async fn scenario_simple() {
     let usecase = UseCase::new(Arc::new(mongo));
 
     let schema = Schema::build(Query::default(), Mutation::default(), EmptySubscription)
+        .data(Loaders::new(&usecase, &auth))
         .data(auth)
         .data(usecase)
         .finish();